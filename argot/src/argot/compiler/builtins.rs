@@ -0,0 +1,10 @@
+/// Functions provided by the compiler, that programs cannot redefine.
+///
+/// - `print(value)` prints a `string`, an `int` or a `bool`.
+/// - `println(value)` does the same, followed by a newline. The value is optional.
+/// - `len(array)` returns the number of elements of an array, as an `int`.
+pub const BUILTIN_FUNCTIONS: &[&str] = &["len", "print", "println"];
+
+pub fn is_builtin(name: &str) -> bool {
+    BUILTIN_FUNCTIONS.contains(&name)
+}
@@ -0,0 +1,327 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    compiler::builtins,
+    ir,
+    syntax::types::*,
+    visitor::{Visitable, Visitor},
+};
+
+/// Functions called by each function of a program. Builtins are not part of the graph.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CallGraph {
+    calls: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of a syntax tree.
+    pub fn from_syntax(program: &mut Program) -> CallGraph {
+        let mut visitor = CallGraphVisitor {
+            graph: CallGraph::default(),
+            caller: String::new(),
+        };
+        program.accept(&mut visitor);
+        visitor.graph
+    }
+
+    /// Builds the call graph of a program lowered to the intermediate representation.
+    pub fn from_ir(program: &ir::Program) -> CallGraph {
+        let mut graph = CallGraph::default();
+        for function in program.functions.iter() {
+            graph.add_function(&function.name);
+            for block in function.blocks.iter() {
+                for instruction in block.instructions.iter() {
+                    if let ir::Instruction::Call {
+                        function: callee, ..
+                    } = instruction
+                    {
+                        graph.add_call(&function.name, callee);
+                    }
+                }
+                if let ir::Terminator::TailCall {
+                    function: callee, ..
+                } = &block.terminator
+                {
+                    graph.add_call(&function.name, callee);
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_function(&mut self, name: &str) {
+        self.calls.entry(String::from(name)).or_default();
+    }
+
+    fn add_call(&mut self, caller: &str, callee: &str) {
+        self.add_function(callee);
+        self.calls
+            .entry(String::from(caller))
+            .or_default()
+            .insert(String::from(callee));
+    }
+
+    /// Returns the functions called by a function.
+    pub fn callees(&self, name: &str) -> impl Iterator<Item = &String> {
+        self.calls.get(name).into_iter().flatten()
+    }
+
+    /// Returns the functions reachable from a function through calls, including itself.
+    pub fn reachable(&self, root: &str) -> BTreeSet<String> {
+        let mut reachable = BTreeSet::new();
+        let mut stack = vec![root];
+        while let Some(name) = stack.pop() {
+            if reachable.insert(String::from(name)) {
+                stack.extend(self.callees(name).map(String::as_str));
+            }
+        }
+        reachable
+    }
+
+    /// Returns whether a function can call itself, directly or through other functions.
+    pub fn is_recursive(&self, name: &str) -> bool {
+        self.callees(name)
+            .any(|callee| self.reachable(callee).contains(name))
+    }
+
+    /// Returns the functions reachable from a function, each after the functions it calls.
+    /// Functions calling each other are ordered arbitrarily.
+    pub fn postorder(&self, root: &str) -> Vec<String> {
+        let mut visited = BTreeSet::new();
+        let mut postorder = Vec::new();
+
+        visited.insert(root);
+        let mut stack = vec![(root, self.callees(root))];
+        while let Some((name, callees)) = stack.last_mut() {
+            match callees.next() {
+                Some(callee) => {
+                    if visited.insert(callee.as_str()) {
+                        stack.push((callee.as_str(), self.callees(callee)));
+                    }
+                }
+                None => {
+                    postorder.push(String::from(*name));
+                    stack.pop();
+                }
+            }
+        }
+
+        postorder
+    }
+}
+
+/// Records the calls of each function of a syntax tree.
+struct CallGraphVisitor {
+    graph: CallGraph,
+
+    /// Function being visited.
+    caller: String,
+}
+
+impl CallGraphVisitor {
+    fn visit_trailers(&mut self, trailers: &mut [Trailer]) {
+        for trailer in trailers.iter_mut() {
+            if let Trailer::Index(index) = trailer {
+                index.accept(self);
+            }
+        }
+    }
+}
+
+impl Visitor for CallGraphVisitor {
+    type Result = ();
+
+    fn visit_factor(&mut self, v: &mut Factor) -> Self::Result {
+        match v {
+            Factor::Atomic(atom) => atom.accept(self),
+            Factor::Unary(_, factor) => factor.accept(self),
+            Factor::Expression(expr) => expr.accept(self),
+            Factor::FunctionCall(call) => call.accept(self),
+            Factor::IfExpression(if_expr) => if_expr.accept(self),
+            Factor::Allocation(allocation) => allocation.accept(self),
+        }
+    }
+
+    fn visit_factor_operator(&mut self, _v: &mut FactorOperator) -> Self::Result {}
+
+    fn visit_term(&mut self, v: &mut Term) -> Self::Result {
+        v.root_factor.accept(self);
+        for (_, factor) in v.trail.iter_mut() {
+            factor.accept(self);
+        }
+    }
+
+    fn visit_term_operator(&mut self, _v: &mut TermOperator) -> Self::Result {}
+
+    fn visit_comparison_operator(&mut self, _v: &mut ComparisonOperator) -> Self::Result {}
+
+    fn visit_unary_operator(&mut self, _v: &mut UnaryOperator) -> Self::Result {}
+
+    fn visit_function_declaration(&mut self, v: &mut FunctionDeclaration) -> Self::Result {
+        self.caller = v.name.clone();
+        self.graph.add_function(&v.name);
+        v.block.accept(self);
+    }
+
+    fn visit_statement(&mut self, v: &mut Statement) -> Self::Result {
+        match v {
+            Statement::VarDecl(declaration) => declaration.accept(self),
+            Statement::VarAssign(assignment) => assignment.accept(self),
+            Statement::Return(value, _) => {
+                if let Some(expr) = value.as_mut() {
+                    expr.accept(self);
+                }
+            }
+            Statement::IfExpression(if_expr) => if_expr.accept(self),
+            Statement::WhileLoop(while_loop) => while_loop.accept(self),
+            Statement::ForLoop(for_loop) => for_loop.accept(self),
+            Statement::Expr(expr) => expr.accept(self),
+            Statement::Break(_) | Statement::Continue(_) => {}
+        }
+    }
+
+    fn visit_variable_declaration(&mut self, v: &mut VariableDeclaration) -> Self::Result {
+        if let Some(expr) = v.expression.as_mut() {
+            expr.accept(self);
+        }
+    }
+
+    fn visit_expression(&mut self, v: &mut Expression) -> Self::Result {
+        v.root.accept(self);
+        for conjunction in v.trail.iter_mut() {
+            conjunction.accept(self);
+        }
+    }
+
+    fn visit_conjunction(&mut self, v: &mut Conjunction) -> Self::Result {
+        v.root.accept(self);
+        for comparison in v.trail.iter_mut() {
+            comparison.accept(self);
+        }
+    }
+
+    fn visit_comparison(&mut self, v: &mut Comparison) -> Self::Result {
+        v.root.accept(self);
+        if let Some((_, rhs)) = v.comparison.as_mut() {
+            rhs.accept(self);
+        }
+    }
+
+    fn visit_arithmetic_expression(&mut self, v: &mut ArithmeticExpression) -> Self::Result {
+        v.root_term.accept(self);
+        for (_, term) in v.trail.iter_mut() {
+            term.accept(self);
+        }
+    }
+
+    fn visit_program(&mut self, v: &mut Program) -> Self::Result {
+        for decl in v.functions.values_mut() {
+            decl.accept(self);
+        }
+    }
+
+    fn visit_atomic_expression(&mut self, v: &mut AtomicExpression) -> Self::Result {
+        self.visit_trailers(&mut v.trailers);
+    }
+
+    fn visit_atom(&mut self, _v: &mut Atom) -> Self::Result {}
+
+    fn visit_block(&mut self, v: &mut Block) -> Self::Result {
+        for statement in v.body.iter_mut() {
+            statement.accept(self);
+        }
+    }
+
+    fn visit_variable_assignment(&mut self, v: &mut VariableAssignment) -> Self::Result {
+        self.visit_trailers(&mut v.trailers);
+        v.expression.accept(self);
+    }
+
+    fn visit_function_call(&mut self, v: &mut FunctionCall) -> Self::Result {
+        if !builtins::is_builtin(&v.name) {
+            let caller = self.caller.clone();
+            self.graph.add_call(&caller, &v.name);
+        }
+        for argument in v.arguments.iter_mut() {
+            argument.accept(self);
+        }
+    }
+
+    fn visit_if_expression(&mut self, v: &mut IfExpression) -> Self::Result {
+        v.condition.accept(self);
+        v.if_block.accept(self);
+        if let Some(else_block) = v.else_block.as_mut() {
+            else_block.accept(self);
+        }
+    }
+
+    fn visit_while_loop(&mut self, v: &mut WhileLoop) -> Self::Result {
+        v.condition.accept(self);
+        v.block.accept(self);
+    }
+
+    fn visit_for_loop(&mut self, v: &mut ForLoop) -> Self::Result {
+        if let Some(init) = v.init.as_mut() {
+            init.accept(self);
+        }
+        if let Some(condition) = v.condition.as_mut() {
+            condition.accept(self);
+        }
+        v.block.accept(self);
+        if let Some(step) = v.step.as_mut() {
+            step.accept(self);
+        }
+    }
+
+    fn visit_allocation(&mut self, v: &mut Allocation) -> Self::Result {
+        if let Some(length) = v.length.as_mut() {
+            length.accept(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CallGraph;
+    use crate::syntax::program::program;
+
+    fn call_graph(source: &str) -> CallGraph {
+        let (_, mut p) = program(source).unwrap();
+        CallGraph::from_syntax(&mut p)
+    }
+
+    #[test]
+    fn calls() {
+        let graph = call_graph(
+            "fn main() {\n    int[4] a;\n    a[g()] = h(2) + 1;\n    println(a[0]);\n}\n\
+             fn f(int x) -> int {\n    return x;\n}\n\
+             fn g() -> int {\n    if (f(0) > 1) {\n        return 1;\n    }\n    return 0;\n}\n\
+             fn h(int x) -> int {\n    while (x > 0) {\n        x = x - 1;\n    }\n    return x;\n}\n\
+             fn unused() {\n    f(3);\n}",
+        );
+
+        let callees: Vec<&String> = graph.callees("main").collect();
+        assert_eq!(callees, vec!["g", "h"]);
+        assert_eq!(graph.callees("g").collect::<Vec<_>>(), vec!["f"]);
+        assert_eq!(graph.callees("h").count(), 0);
+
+        let reachable: Vec<String> = graph.reachable("main").into_iter().collect();
+        assert_eq!(reachable, vec!["f", "g", "h", "main"]);
+        assert_eq!(graph.postorder("main"), vec!["f", "g", "h", "main"]);
+    }
+
+    #[test]
+    fn recursion() {
+        let graph = call_graph(
+            "fn main() {\n    even(4);\n    fact(3);\n}\n\
+             fn even(int n) -> bool {\n    if (n == 0) {\n        return true;\n    }\n    return odd(n - 1);\n}\n\
+             fn odd(int n) -> bool {\n    if (n == 0) {\n        return false;\n    }\n    return even(n - 1);\n}\n\
+             fn fact(int n) -> int {\n    if (n < 2) {\n        return 1;\n    }\n    return n * fact(n - 1);\n}",
+        );
+
+        assert!(graph.is_recursive("even"));
+        assert!(graph.is_recursive("odd"));
+        assert!(graph.is_recursive("fact"));
+        assert!(!graph.is_recursive("main"));
+    }
+}
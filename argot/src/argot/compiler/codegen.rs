@@ -0,0 +1,638 @@
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::mem;
+
+use instructor::SysCall;
+
+use crate::{
+    compiler::{
+        label::LabelGenerator,
+        regalloc::{self, Allocation, Location, Registers},
+        typing::Type,
+    },
+    ir::{
+        self, Address, Argument, Base, BinaryOperator, BlockId, Instruction, Temp, Terminator,
+        UnaryOperator,
+    },
+};
+
+const WORD_SIZE: usize = mem::size_of::<i32>();
+
+/// Returns the label of a string of the program.
+///
+/// Labels start with `s` and contain a digit, so they cannot clash with function names or
+/// generated labels.
+fn string_label(index: usize) -> String {
+    format!("s{}", index)
+}
+
+/// Returns the mnemonic of an operation on `size` bytes, such as `lw` for a word load.
+fn sized(operation: &str, size: usize) -> String {
+    format!("{}{}", operation, if size == 1 { 'b' } else { 'w' })
+}
+
+/// Returns the number of bytes a temporary of type `t` takes in memory.
+fn temp_size(t: &Type) -> usize {
+    match t {
+        Type::Boolean => 1,
+        _ => WORD_SIZE,
+    }
+}
+
+/// Returns whether each block of a function is jumped to, rather than only reached by falling
+/// through from the previous block.
+fn jump_targets(function: &ir::Function) -> Vec<bool> {
+    let mut targets = vec![false; function.blocks.len()];
+    for (id, block) in function.blocks.iter().enumerate() {
+        let next = BlockId(id + 1);
+        match block.terminator {
+            Terminator::Jump(target) if target != next => targets[target.0] = true,
+            Terminator::Branch {
+                then, otherwise, ..
+            } => {
+                targets[otherwise.0] = true;
+                targets[then.0] |= then != next;
+            }
+            _ => {}
+        }
+    }
+    targets
+}
+
+/// Stack frame of a function.
+///
+/// The spill slots come first, followed by the frame slots and a word for each register saved
+/// by the function. The arguments are pushed in order by the caller before the return address
+/// and the saved `$ebp`, so the last argument is right below them.
+struct Frame {
+    spills: Vec<i32>,
+    slots: Vec<i32>,
+    arguments: Vec<i32>,
+    saves: HashMap<u8, i32>,
+    size: usize,
+}
+
+impl Frame {
+    fn new(function: &ir::Function, allocation: &Allocation, callee_saved: &[u8]) -> Frame {
+        let spills = (0..allocation.spills)
+            .map(|spill| (spill * WORD_SIZE) as i32)
+            .collect();
+
+        let mut size = allocation.spills * WORD_SIZE;
+        let mut slots = Vec::with_capacity(function.slots.len());
+        for slot_size in function.slots.iter() {
+            slots.push(size as i32);
+            size += slot_size;
+        }
+
+        let saved: BTreeSet<u8> = callee_saved
+            .iter()
+            .chain(allocation.call_saves.values().flatten())
+            .copied()
+            .collect();
+        let mut saves = HashMap::new();
+        for register in saved {
+            saves.insert(register, size as i32);
+            size += WORD_SIZE;
+        }
+
+        let mut capture_offset =
+            2 * WORD_SIZE + function.parameters.iter().map(|p| p.size).sum::<usize>();
+        let mut arguments = Vec::with_capacity(function.parameters.len());
+        for parameter in function.parameters.iter() {
+            arguments.push(-(capture_offset as i32));
+            capture_offset -= parameter.size;
+        }
+
+        Frame {
+            spills,
+            slots,
+            arguments,
+            saves,
+            size,
+        }
+    }
+}
+
+/// Generates slang assembly from the intermediate representation of a program.
+///
+/// Temporaries are kept in the registers assigned by the register allocator. Registers `$0` to
+/// `$5` are used as scratch registers by each instruction, to hold spilled temporaries among
+/// others.
+pub struct CodeGenerator {
+    labels: LabelGenerator,
+    registers: Registers,
+    instructions: Vec<String>,
+}
+
+impl Default for CodeGenerator {
+    fn default() -> CodeGenerator {
+        CodeGenerator::new()
+    }
+}
+
+impl CodeGenerator {
+    pub fn new() -> CodeGenerator {
+        CodeGenerator {
+            labels: LabelGenerator::new(),
+            registers: Registers::default(),
+            instructions: Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, program: &ir::Program) -> String {
+        for function in program.functions.iter() {
+            self.function(function);
+        }
+
+        let data: String = program
+            .strings
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("{}: .asciiz \"{}\"\n", string_label(i), s))
+            .collect();
+
+        let instructions = mem::take(&mut self.instructions);
+        format!(
+            ".data\n{}.text\njmp @main\n{}",
+            data,
+            instructions.join("\n")
+        )
+    }
+
+    fn emit(&mut self, instruction: String) {
+        self.instructions.push(instruction);
+    }
+
+    fn label(&mut self, label: &str) {
+        self.emit(format!("{}:", label));
+    }
+
+    /// Pops `size` bytes off the stack, discarding them.
+    fn discard(&mut self, size: usize) {
+        for _ in 0..size / WORD_SIZE {
+            self.emit(String::from("popw $0"));
+        }
+        for _ in 0..size % WORD_SIZE {
+            self.emit(String::from("popb $0"));
+        }
+    }
+
+    fn syscall(&mut self, call: SysCall) {
+        self.emit(format!("ld $v0 {:#06x}", call as u16));
+        self.emit(String::from("syscall"));
+    }
+
+    fn function(&mut self, function: &ir::Function) {
+        let allocation = regalloc::allocate(function, &self.registers);
+
+        // The program ends when `main` returns, so it does not need to restore registers.
+        let callee_saved = if function.name == "main" {
+            &[]
+        } else {
+            allocation.callee_saved.as_slice()
+        };
+        let frame = Frame::new(function, &allocation, callee_saved);
+
+        // Only the blocks that are jumped to need a label.
+        let block_labels: Vec<Option<String>> = jump_targets(function)
+            .into_iter()
+            .map(|target| target.then(|| self.labels.next().unwrap()))
+            .collect();
+
+        // Returns jump to the epilogue, unless they are in the last block. An empty last block
+        // returning nothing is the epilogue, as labels need an instruction to point to.
+        let returns_early = function
+            .blocks
+            .iter()
+            .rev()
+            .skip(1)
+            .any(|block| matches!(block.terminator, Terminator::Return(_)));
+        let epilogue = match function.blocks.last() {
+            Some(last)
+                if last.instructions.is_empty()
+                    && last.terminator == Terminator::Return(None)
+                    && block_labels.last().unwrap().is_some() =>
+            {
+                block_labels.last().unwrap().clone()
+            }
+            _ => returns_early.then(|| self.labels.next().unwrap()),
+        };
+
+        self.label(&function.name);
+
+        // Storing to the end of the frame grows the stack over the whole frame.
+        if frame.size >= WORD_SIZE {
+            self.emit(format!("sw $0 {}[$ebp]", frame.size - WORD_SIZE));
+        } else if frame.size > 0 {
+            self.emit(format!("sb $0 {}[$ebp]", frame.size - 1));
+        }
+
+        for register in callee_saved.iter() {
+            self.emit(format!("sw ${} {}[$ebp]", register, frame.saves[register]));
+        }
+
+        let mut context = FunctionContext {
+            function,
+            allocation: &allocation,
+            frame: &frame,
+            callee_saved,
+            generator: self,
+        };
+        for (id, block) in function.blocks.iter().enumerate() {
+            if let Some(label) = &block_labels[id] {
+                context.generator.label(label);
+            }
+            for (index, instruction) in block.instructions.iter().enumerate() {
+                context.instruction(instruction, (BlockId(id), index));
+            }
+            context.terminator(
+                &block.terminator,
+                BlockId(id + 1),
+                &block_labels,
+                epilogue.as_deref(),
+            );
+        }
+
+        if let Some(epilogue) = &epilogue {
+            if block_labels.last() != Some(&Some(epilogue.clone())) {
+                self.label(epilogue);
+            }
+        }
+
+        for register in callee_saved.iter() {
+            self.emit(format!("lw ${} {}[$ebp]", register, frame.saves[register]));
+        }
+
+        self.discard(frame.size);
+
+        // The program ends when `main` returns.
+        if function.name == "main" {
+            self.syscall(SysCall::EXIT);
+        } else {
+            self.emit(String::from("ret"));
+        }
+    }
+}
+
+/// Function being generated.
+struct FunctionContext<'a> {
+    function: &'a ir::Function,
+    allocation: &'a Allocation,
+    frame: &'a Frame,
+
+    /// Registers restored by the function before it returns.
+    callee_saved: &'a [u8],
+
+    generator: &'a mut CodeGenerator,
+}
+
+impl<'a> FunctionContext<'a> {
+    fn emit(&mut self, instruction: String) {
+        self.generator.emit(instruction);
+    }
+
+    /// Returns the register holding a temporary. Spilled temporaries are loaded in `scratch`.
+    fn read(&mut self, temp: Temp, scratch: u8) -> u8 {
+        match self.allocation.location(temp) {
+            Location::Register(register) => register,
+            Location::Spill(spill) => {
+                let offset = self.frame.spills[spill];
+                self.emit(format!("lw ${} {}[$ebp]", scratch, offset));
+                scratch
+            }
+        }
+    }
+
+    /// Returns the register a temporary is computed in. Spilled temporaries are computed in
+    /// `scratch`, and stored by `write`.
+    fn dest(&self, temp: Temp, scratch: u8) -> u8 {
+        match self.allocation.location(temp) {
+            Location::Register(register) => register,
+            Location::Spill(_) => scratch,
+        }
+    }
+
+    /// Assigns the value of a register to a temporary.
+    fn write(&mut self, temp: Temp, register: u8) {
+        match self.allocation.location(temp) {
+            Location::Register(dest) => {
+                if dest != register {
+                    self.emit(format!("move ${} ${}", register, dest));
+                }
+            }
+            Location::Spill(spill) => {
+                let offset = self.frame.spills[spill];
+                self.emit(format!("sw ${} {}[$ebp]", register, offset));
+            }
+        }
+    }
+
+    /// Returns the operand designating an address. Heap addresses are loaded in `register`.
+    fn address(&mut self, address: Address, register: u8) -> String {
+        match address.base {
+            Base::Slot(slot) => format!("{}[$ebp]", self.frame.slots[slot] + address.offset),
+            Base::Argument(index) => {
+                format!("{}[$ebp]", self.frame.arguments[index] + address.offset)
+            }
+            Base::Heap(temp) => {
+                let register = self.read(temp, register);
+                format!("{}(${})", address.offset, register)
+            }
+        }
+    }
+
+    /// Generates an instruction, identified by its block and its index in the block.
+    fn instruction(&mut self, instruction: &Instruction, position: (BlockId, usize)) {
+        match instruction {
+            Instruction::Const { dest, value } => {
+                let register = self.dest(*dest, 0);
+                self.load_constant(register, *value);
+                self.write(*dest, register);
+            }
+            Instruction::String { dest, index } => {
+                let register = self.dest(*dest, 0);
+                self.emit(format!("ld ${} @{}", register, string_label(*index)));
+                self.write(*dest, register);
+            }
+            Instruction::Copy { dest, source } => {
+                let register = self.read(*source, 0);
+                self.write(*dest, register);
+            }
+            Instruction::Unary {
+                dest,
+                operator,
+                operand,
+            } => {
+                // Unary operations are done in place, on a copy of the operand.
+                let operand = self.read(*operand, 0);
+                let register = self.dest(*dest, 0);
+                if operand != register {
+                    self.emit(format!("move ${} ${}", operand, register));
+                }
+                match operator {
+                    UnaryOperator::Negate => self.emit(format!("neg ${}", register)),
+                    UnaryOperator::Not => self.negation(register),
+                }
+                self.write(*dest, register);
+            }
+            Instruction::Binary {
+                dest,
+                operator,
+                left,
+                right,
+            } => {
+                let left = self.read(*left, 1);
+                let right = self.read(*right, 2);
+                let register = self.dest(*dest, 0);
+                self.binary(*operator, left, right, register);
+                self.write(*dest, register);
+            }
+            Instruction::Load { dest, address } => {
+                let size = temp_size(self.function.temp_type(*dest));
+                let address = self.address(*address, 3);
+                let register = self.dest(*dest, 0);
+                self.emit(format!("{} ${} {}", sized("l", size), register, address));
+                self.write(*dest, register);
+            }
+            Instruction::Store { address, value } => {
+                let size = temp_size(self.function.temp_type(*value));
+                let register = self.read(*value, 0);
+                let address = self.address(*address, 3);
+                self.emit(format!("{} ${} {}", sized("s", size), register, address));
+            }
+            Instruction::CopyMemory { dest, source, size } => {
+                for (offset, chunk) in ir::chunks(*size) {
+                    let source = self.address(source.offset(offset), 3);
+                    self.emit(format!("{} $1 {}", sized("l", chunk), source));
+                    let dest = self.address(dest.offset(offset), 4);
+                    self.emit(format!("{} $1 {}", sized("s", chunk), dest));
+                }
+            }
+            Instruction::Call {
+                dest,
+                function,
+                arguments,
+            } => self.call(*dest, function, arguments, position),
+            Instruction::Syscall {
+                dest,
+                call,
+                argument,
+            } => {
+                // The argument of a syscall is expected in $0, and its result is in $v0.
+                if let Some(argument) = argument {
+                    let register = self.read(*argument, 0);
+                    if register != 0 {
+                        self.emit(format!("move ${} $0", register));
+                    }
+                }
+                self.generator.syscall(*call);
+                if let Some(dest) = dest {
+                    let register = self.dest(*dest, 0);
+                    self.emit(format!("move $v0 ${}", register));
+                    self.write(*dest, register);
+                }
+            }
+        }
+    }
+
+    /// Loads a constant in a register. `ld` only loads 16-bit values, so larger values are
+    /// built from their two halves, using `$1`.
+    fn load_constant(&mut self, register: u8, value: i32) {
+        if let Ok(value) = u16::try_from(value) {
+            self.emit(format!("ld ${} {:#06x}", register, value));
+        } else if let Ok(negated) = u16::try_from(value.wrapping_neg()) {
+            self.emit(format!("ld ${} {:#06x}", register, negated));
+            self.emit(format!("neg ${}", register));
+        } else {
+            self.emit(format!("ld ${} {:#06x}", register, (value as u32) >> 16));
+            self.emit(String::from("ld $1 0x0010"));
+            self.emit(format!("shl ${} $1", register));
+            self.emit(format!("ld $1 {:#06x}", value & 0xffff));
+            self.emit(format!("add ${} $1 ${}", register, register));
+        }
+    }
+
+    fn binary(&mut self, operator: BinaryOperator, left: u8, right: u8, dest: u8) {
+        let operation = operator.to_string();
+        if operator == BinaryOperator::ShiftLeft {
+            // Shifts are done in place. The right operand must not be overwritten by the left
+            // one before that.
+            let register = if dest == right && dest != left {
+                0
+            } else {
+                dest
+            };
+            if left != register {
+                self.emit(format!("move ${} ${}", left, register));
+            }
+            self.emit(format!("{} ${} ${}", operation, register, right));
+            if register != dest {
+                self.emit(format!("move ${} ${}", register, dest));
+            }
+            return;
+        }
+
+        if !operator.is_comparison() {
+            self.emit(format!("{} ${} ${} ${}", operation, left, right, dest));
+            return;
+        }
+
+        // Comparisons set the equal flag, which is loaded as a boolean.
+        let label = self.generator.labels.next().unwrap();
+        self.emit(format!("{} ${} ${}", operation, left, right));
+        self.emit(format!("ld ${} 0x0001", dest));
+        self.emit(format!("jeq @{}", label));
+        self.emit(format!("ld ${} 0x0000", dest));
+        self.generator.label(&label);
+    }
+
+    /// Computes the logical negation of the boolean in a register.
+    fn negation(&mut self, register: u8) {
+        // Logical negation of 32-bit signed integers using bitwise operators:
+        // (!(x >> 1) + x) >> 31
+        let swap_register = 5;
+        let one_register = 1;
+        let thirty_one = 2;
+
+        self.emit(format!("ld ${} 0x0001", one_register));
+        self.emit(format!("ld ${} 0x001f", thirty_one));
+        self.emit(format!("move ${} ${}", register, swap_register));
+        self.emit(format!("shr ${} ${}", register, one_register));
+        self.emit(format!("not ${}", register));
+        self.emit(format!(
+            "add ${} ${} ${}",
+            register, swap_register, register
+        ));
+        self.emit(format!("shr ${} ${}", register, thirty_one));
+    }
+
+    /// Pushes the arguments of a call in order, returning the size of each value pushed.
+    fn push_arguments(&mut self, arguments: &[Argument]) -> Vec<usize> {
+        let mut pushed = Vec::new();
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Value(temp) => {
+                    let size = temp_size(self.function.temp_type(*temp));
+                    let register = self.read(*temp, 1);
+                    self.emit(format!("{} ${}", sized("push", size), register));
+                    pushed.push(size);
+                }
+                Argument::Memory { address, size } => {
+                    for (offset, chunk) in ir::chunks(*size) {
+                        let source = self.address(address.offset(offset), 3);
+                        self.emit(format!("{} $1 {}", sized("l", chunk), source));
+                        self.emit(format!("{} $1", sized("push", chunk)));
+                        pushed.push(chunk);
+                    }
+                }
+            }
+        }
+        pushed
+    }
+
+    /// Calls a function in place of the current one. The arguments are all pushed before any of
+    /// them overwrites the arguments of the current function, which they may be computed from.
+    /// The frame is then torn down as by a return, and the called function takes over the
+    /// return address and saved `$ebp` of the current one.
+    fn tail_call(&mut self, function: &str, arguments: &[Argument]) {
+        let pushed = self.push_arguments(arguments);
+
+        // The last argument is right below the return address and the saved `$ebp`.
+        let mut offset = -(2 * WORD_SIZE as i32);
+        for size in pushed.into_iter().rev() {
+            offset -= size as i32;
+            self.emit(format!("{} $1", sized("pop", size)));
+            self.emit(format!("{} $1 {}[$ebp]", sized("s", size), offset));
+        }
+
+        for register in self.callee_saved.iter() {
+            let offset = self.frame.saves[register];
+            self.emit(format!("lw ${} {}[$ebp]", register, offset));
+        }
+        self.generator.discard(self.frame.size);
+
+        self.emit(format!("jmp @{}", function));
+    }
+
+    /// Pushes the arguments of a function on the stack and calls it, popping the arguments once
+    /// it returns.
+    ///
+    /// The caller-saved registers holding values still needed after the call are saved to the
+    /// frame around it.
+    fn call(
+        &mut self,
+        dest: Option<Temp>,
+        function: &str,
+        arguments: &[Argument],
+        position: (BlockId, usize),
+    ) {
+        let saved = self.allocation.call_saves[&position].clone();
+        for register in saved.iter() {
+            let offset = self.frame.saves[register];
+            self.emit(format!("sw ${} {}[$ebp]", register, offset));
+        }
+
+        let pushed = self.push_arguments(arguments);
+        self.emit(format!("call @{}", function));
+        self.generator.discard(pushed.iter().sum());
+
+        for register in saved.iter() {
+            let offset = self.frame.saves[register];
+            self.emit(format!("lw ${} {}[$ebp]", register, offset));
+        }
+
+        // The return value is in $v0.
+        if let Some(dest) = dest {
+            let register = self.dest(dest, 0);
+            self.emit(format!("move $v0 ${}", register));
+            self.write(dest, register);
+        }
+    }
+
+    fn terminator(
+        &mut self,
+        terminator: &Terminator,
+        next: BlockId,
+        block_labels: &[Option<String>],
+        epilogue: Option<&str>,
+    ) {
+        let label = |block: &BlockId| block_labels[block.0].clone().unwrap_or_default();
+
+        match terminator {
+            Terminator::Jump(target) => {
+                if *target != next {
+                    self.emit(format!("jmp @{}", label(target)));
+                }
+            }
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let register = self.read(*condition, 0);
+                self.emit(format!("jez ${} @{}", register, label(otherwise)));
+                if *then != next {
+                    self.emit(format!("jmp @{}", label(then)));
+                }
+            }
+            Terminator::Return(value) => {
+                if let Some(value) = value {
+                    let register = self.read(*value, 0);
+                    self.emit(format!("move ${} $v0", register));
+                }
+                if let Some(epilogue) = epilogue {
+                    if next.0 < self.function.blocks.len() {
+                        self.emit(format!("jmp @{}", epilogue));
+                    }
+                }
+            }
+            Terminator::TailCall {
+                function,
+                arguments,
+            } => self.tail_call(function, arguments),
+            // The block ends with a syscall that does not return.
+            Terminator::Unreachable => {}
+        }
+    }
+}
@@ -1,61 +1,238 @@
 use std::fmt;
 
-use assembler::AssemblerError;
+use assembler::AssemblerErrors;
 
 use snafu::Snafu;
 
-#[derive(Debug)]
-pub struct ParseError {
-    pub message: String,
+use crate::{compiler::typing::Type, syntax::Span};
+
+/// Position of a diagnostic in the Argot source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    /// 1-based line number.
+    pub line: usize,
+
+    /// 1-based column number, in characters.
+    pub column: usize,
+
+    /// The full source line, without its terminator.
+    pub source_line: String,
+
+    /// Number of characters underlined on the source line.
+    pub length: usize,
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.message)
+impl Location {
+    /// Computes the location of a span in the source it was parsed from.
+    pub fn new(source: &str, span: Span) -> Location {
+        let range = span.range(source);
+        let line_start = source[..range.start]
+            .rfind('\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let line_end = source[range.start..]
+            .find('\n')
+            .map(|i| range.start + i)
+            .unwrap_or(source.len());
+
+        let source_line = source[line_start..line_end].trim_end_matches('\r');
+        let underlined = &source[range.start..range.end.min(line_start + source_line.len())];
+
+        Location {
+            line: source[..line_start].matches('\n').count() + 1,
+            column: source[line_start..range.start].chars().count() + 1,
+            source_line: String::from(source_line),
+            length: underlined.chars().count().max(1),
+        }
     }
 }
 
-impl std::error::Error for ParseError {}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub))]
 pub enum CompileError {
-    AssemblyError {
-        source: AssemblerError,
-    },
-    DuplicateFunction {
-        name: String,
-    },
-    IncompleteParse {
-        source: ParseError,
-    },
-    InvalidArguments, // TODO: Details
-    InvalidOperator {
-        t: String,
-    },
+    #[snafu(display("Failed to assemble the generated program:\n{}", source))]
+    AssemblyError { source: AssemblerErrors },
+
+    #[snafu(display("Function '{}' is a builtin and cannot be redefined", name))]
+    BuiltinRedefinition { name: String },
+
+    #[snafu(display("Field '{}' is defined multiple times", name))]
+    DuplicateField { name: String },
+
+    #[snafu(display("Function '{}' is defined multiple times", name))]
+    DuplicateFunction { name: String },
+
+    #[snafu(display("Functions cannot return values of type '{}'", t))]
+    InvalidReturnType { t: Type },
+
+    #[snafu(display("Invalid arguments"))]
+    InvalidArguments,
+
+    #[snafu(display("Invalid arguments: '{}' has type '{}'", name, t))]
+    InvalidCall { name: String, t: Type },
+
+    #[snafu(display("Operator is not defined for type '{}'", t))]
+    InvalidOperator { t: Type },
+
+    #[snafu(display("Invalid register state"))]
     InvalidRegisterState,
+
+    #[snafu(display("'{}' outside of a loop", keyword))]
+    JumpOutsideOfLoop { keyword: String },
+
+    /// Wraps an error raised while compiling a syntax node with the span of that node.
+    #[snafu(display("{}", source))]
+    Located {
+        source: Box<CompileError>,
+        span: Span,
+    },
+
+    #[snafu(display("Values of type '{}' cannot be indexed", t))]
+    NotAnArray { t: Type },
+
+    #[snafu(display("Missing entry point: no 'main' function is defined"))]
     MissingEntryPoint,
+
+    #[snafu(display("Missing ';' after statement"))]
+    MissingSemicolon,
+
+    #[snafu(display("Missing scope"))]
     MissingScope,
+
+    #[snafu(display("Missing type"))]
     MissingType,
+
+    #[snafu(display("Missing value"))]
+    MissingValue,
+
+    #[snafu(display("Values of type '{}' have no fields", t))]
+    NotAStruct { t: Type },
+
+    #[snafu(display("Values of type '{}' can only be copied", t))]
+    NotAValue { t: Type },
+
+    #[snafu(display("Values of type '{}' cannot be printed", t))]
+    NotPrintable { t: Type },
+
+    #[snafu(display("Not all paths return a value"))]
     NotAllPathsReturnAValue,
-    NoUsedRegisters,
-    TypeMismatch {
-        t1: String,
-        t2: String,
-    },
-    UnknownFunction {
-        name: String,
-    },
-    UnknownIdentifier {
-        name: String,
-    },
-    UnknownType {
-        name: String,
-        source: super::typing::UnknownType,
-    },
-    VariableAlreadyDefined {
-        name: String,
-    },
+
+    #[snafu(display("Struct '{}' contains itself", name))]
+    RecursiveStruct { name: String },
+
+    #[snafu(display("Invalid syntax"))]
+    SyntaxError,
+
+    #[snafu(display("Type mismatch: '{}' and '{}'", t1, t2))]
+    TypeMismatch { t1: Type, t2: Type },
+
+    #[snafu(display("Type '{}' is already defined", name))]
+    TypeAlreadyDefined { name: String },
+
+    #[snafu(display("Unknown field: '{}' has no field '{}'", t, field))]
+    UnknownField { t: Type, field: String },
+
+    #[snafu(display("Unknown function: {}", name))]
+    UnknownFunction { name: String },
+
+    #[snafu(display("Unknown identifier: {}", name))]
+    UnknownIdentifier { name: String },
+
+    #[snafu(display("Unknown type: '{}'", name))]
+    UnknownType { name: String },
+
+    #[snafu(display("Variable '{}' is already defined", name))]
+    VariableAlreadyDefined { name: String },
+}
+
+impl CompileError {
+    /// Attaches a span to the error, unless it already has one.
+    ///
+    /// Since nodes wrap the errors of their children, errors end up pointing at the innermost
+    /// node that has a span.
+    pub fn at(self, span: Span) -> CompileError {
+        match self {
+            CompileError::Located { .. } => self,
+            e => CompileError::Located {
+                source: Box::new(e),
+                span,
+            },
+        }
+    }
+
+    /// Returns the span of the source the error points at.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CompileError::Located { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+
+    /// Returns the location the error points at in the source it was raised for.
+    pub fn location(&self, source: &str) -> Option<Location> {
+        self.span().map(|span| Location::new(source, span))
+    }
+
+    /// Formats the error along with its location and source line, underlining the offending code.
+    ///
+    /// # Examples
+    /// ```
+    /// let source = "fn main() {\n    int a = b;\n}";
+    /// let error = argot::compile_asm(source).unwrap_err();
+    /// assert_eq!(
+    ///     error.report("main.gt", source),
+    ///     "Unknown identifier: b\n --> main.gt:2:13\n  |\n2 |     int a = b;\n  |             ^"
+    /// );
+    /// ```
+    pub fn report(&self, file: &str, source: &str) -> String {
+        let location = match self.location(source) {
+            Some(l) => l,
+            None => return format!("{}\n --> {}", self, file),
+        };
+
+        let line_number = location.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        format!(
+            "{}\n{} --> {}:{}\n{} |\n{} | {}\n{} | {}{}",
+            self,
+            &gutter[1..],
+            file,
+            location,
+            gutter,
+            line_number,
+            location.source_line,
+            gutter,
+            " ".repeat(location.column - 1),
+            "^".repeat(location.length)
+        )
+    }
 }
 
 pub type Result<T> = std::result::Result<T, CompileError>;
+
+#[cfg(test)]
+mod tests {
+    use super::Location;
+    use crate::syntax::Span;
+
+    #[test]
+    fn location_from_span() {
+        let source = "fn main() {\n  int a = 3 +\n 4;\n}";
+
+        // Spans are measured from the end of the source.
+        let start = source.find("3").unwrap();
+        let end = source.find(";").unwrap();
+        let location = Location::new(source, Span::new(source.len() - start, source.len() - end));
+
+        assert_eq!((location.line, location.column), (2, 11));
+        assert_eq!(location.source_line, "  int a = 3 +");
+        assert_eq!(location.length, 3);
+    }
+}
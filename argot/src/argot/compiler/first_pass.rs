@@ -3,28 +3,50 @@ use std::mem;
 
 use snafu::ensure;
 
-use crate::compiler::error::*;
-use crate::syntax::types::{Argument, FunctionDeclaration, Program};
+use crate::compiler::{
+    builtins,
+    call_graph::CallGraph,
+    error::*,
+    typing::{Type, TypeTable},
+};
+use crate::syntax::types::{FunctionDeclaration, Program};
 
+/// Function declared by a program, with its argument and return types resolved.
 #[derive(Clone, Debug)]
 pub struct FunctionDecl {
     pub name: String,
-    pub arguments: Vec<Argument>,
-    pub return_type: String,
+    pub arguments: Vec<Type>,
+    pub return_type: Type,
 }
 
-impl From<FunctionDeclaration> for FunctionDecl {
-    fn from(d: FunctionDeclaration) -> Self {
-        FunctionDecl {
-            name: d.name,
-            return_type: d.return_type,
-            arguments: d.args.arguments,
+impl FunctionDecl {
+    /// Resolves the types of a function declaration.
+    fn resolve(d: &FunctionDeclaration, types: &TypeTable) -> Result<FunctionDecl> {
+        let mut arguments = Vec::with_capacity(d.args.arguments.len());
+        for arg in d.args.arguments.iter() {
+            arguments.push(types.resolve(&arg.arg_type).map_err(|e| e.at(arg.span))?);
+        }
+
+        Ok(FunctionDecl {
+            name: d.name.clone(),
+            arguments,
+            return_type: types.resolve_return_type(&d.return_type)?,
+        })
+    }
+
+    /// Returns the type of the function.
+    pub fn signature(&self) -> Type {
+        Type::Function {
+            arguments: self.arguments.clone(),
+            return_type: Box::new(self.return_type.clone()),
         }
     }
 }
 
 pub struct FirstPassOutput {
     pub functions: HashMap<String, FunctionDecl>,
+    pub types: TypeTable,
+    pub calls: CallGraph,
 }
 
 pub struct FirstPassVisitor {
@@ -38,14 +60,37 @@ impl FirstPassVisitor {
         }
     }
 
-    fn visit_function_declaration(&mut self, decl: FunctionDeclaration) {
-        self.functions
-            .insert(decl.name.clone(), FunctionDecl::from(decl));
+    fn visit_function_declaration(
+        &mut self,
+        decl: &FunctionDeclaration,
+        types: &TypeTable,
+    ) -> Result<()> {
+        if builtins::is_builtin(&decl.name) {
+            return Err(CompileError::BuiltinRedefinition {
+                name: decl.name.clone(),
+            }
+            .at(decl.span));
+        }
+
+        let function = FunctionDecl::resolve(decl, types).map_err(|e| e.at(decl.span))?;
+        self.functions.insert(decl.name.clone(), function);
+        Ok(())
     }
 
     pub fn apply(&mut self, program: &mut Program) -> Result<FirstPassOutput> {
-        for (_function_name, function_decl) in program.functions.iter_mut() {
-            self.visit_function_declaration(function_decl.clone());
+        if let Some(decl) = program.redefinitions.first() {
+            return Err(CompileError::DuplicateFunction {
+                name: decl.name.clone(),
+            }
+            .at(decl.span));
+        }
+
+        let types = TypeTable::from_declarations(&program.structs)?;
+
+        let mut function_names: Vec<&String> = program.functions.keys().collect();
+        function_names.sort();
+        for name in function_names.into_iter() {
+            self.visit_function_declaration(&program.functions[name], &types)?;
         }
         ensure!(self.functions.contains_key("main"), MissingEntryPointSnafu);
 
@@ -53,7 +98,11 @@ impl FirstPassVisitor {
 
         mem::swap(&mut functions, &mut self.functions);
 
-        Ok(FirstPassOutput { functions })
+        Ok(FirstPassOutput {
+            functions,
+            types,
+            calls: CallGraph::from_syntax(program),
+        })
     }
 }
 
@@ -0,0 +1,1051 @@
+use std::collections::HashMap;
+use std::mem;
+
+use instructor::SysCall;
+
+use snafu::ensure;
+
+use crate::{
+    compiler::{
+        builtins,
+        error::*,
+        first_pass::FunctionDecl,
+        typing::{self, Type, TypeTable},
+    },
+    ir::{self, Address, Base, BinaryOperator, BlockId, Instruction, Temp, Terminator},
+    syntax::types::*,
+    visitor::{Visitable, Visitor},
+};
+
+/// Where a value is stored.
+#[derive(Clone, Copy, Debug)]
+enum Place {
+    /// In a temporary, like scalar variables and intermediate values.
+    Temp(Temp),
+
+    /// In memory, like structs and the elements of arrays.
+    Memory(Address),
+}
+
+#[derive(Clone, Debug)]
+struct Variable {
+    place: Place,
+    var_type: Type,
+}
+
+/// Blocks `continue` and `break` jump to.
+struct LoopContext {
+    continue_block: BlockId,
+    break_block: BlockId,
+}
+
+/// Returns the type the type checker annotated an expression with.
+fn value_type(expr: &Expression) -> Result<&Type> {
+    expr.value_type.as_ref().ok_or(CompileError::MissingType)
+}
+
+fn term_operator(operator: &TermOperator) -> Result<BinaryOperator> {
+    match operator {
+        TermOperator::Plus => Ok(BinaryOperator::Add),
+        TermOperator::Minus => Ok(BinaryOperator::Sub),
+        TermOperator::Unknown => Err(CompileError::SyntaxError),
+    }
+}
+
+fn factor_operator(operator: &FactorOperator) -> Result<BinaryOperator> {
+    match operator {
+        FactorOperator::Mult => Ok(BinaryOperator::Mul),
+        FactorOperator::Div => Ok(BinaryOperator::Div),
+        FactorOperator::Unknown => Err(CompileError::SyntaxError),
+    }
+}
+
+fn comparison_operator(operator: &ComparisonOperator) -> Result<BinaryOperator> {
+    match operator {
+        ComparisonOperator::Equal => Ok(BinaryOperator::Equal),
+        ComparisonOperator::NotEqual => Ok(BinaryOperator::NotEqual),
+        ComparisonOperator::Greater => Ok(BinaryOperator::Greater),
+        ComparisonOperator::Lower => Ok(BinaryOperator::Lower),
+        ComparisonOperator::GreaterOrEqual => Ok(BinaryOperator::GreaterOrEqual),
+        ComparisonOperator::LowerOrEqual => Ok(BinaryOperator::LowerOrEqual),
+        ComparisonOperator::Unknown => Err(CompileError::SyntaxError),
+    }
+}
+
+/// Lowers a type checked program to the intermediate representation.
+///
+/// Expressions are lowered to the temporary holding their value, and statements to nothing.
+pub struct LowerVisitor<'a> {
+    functions: &'a HashMap<String, FunctionDecl>,
+    types: &'a TypeTable,
+
+    /// Functions lowered so far, and the strings they use.
+    program: ir::Program,
+
+    /// Function being lowered.
+    function: ir::Function,
+
+    /// Block instructions are added to. After a terminator, there is no current block until the
+    /// next block starts.
+    current: Option<BlockId>,
+
+    /// Blocks of the current function aborting the program, by message.
+    abort_blocks: HashMap<String, BlockId>,
+
+    loops: Vec<LoopContext>,
+
+    /// Variables in scope, innermost scope last.
+    scopes: Vec<HashMap<String, Variable>>,
+}
+
+impl<'a> LowerVisitor<'a> {
+    pub fn new(
+        functions: &'a HashMap<String, FunctionDecl>,
+        types: &'a TypeTable,
+    ) -> LowerVisitor<'a> {
+        LowerVisitor {
+            functions,
+            types,
+            program: ir::Program::default(),
+            function: ir::Function::new("", Vec::new(), Type::Void),
+            current: None,
+            abort_blocks: HashMap::new(),
+            loops: Vec::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, program: &mut Program) -> Result<ir::Program> {
+        program.accept(self)?;
+        debug_assert!(self.scopes.is_empty());
+        Ok(mem::take(&mut self.program))
+    }
+
+    /// Lowers a syntax node, attaching its span to the errors it raises.
+    fn lower_node<T: Visitable + Spanned>(&mut self, node: &mut T) -> Result<Option<Temp>> {
+        let span = node.span();
+        node.accept(self).map_err(|e| e.at(span))
+    }
+
+    /// Lowers a syntax node that has a value.
+    fn lower_operand<T: Visitable + Spanned>(&mut self, node: &mut T) -> Result<Temp> {
+        let span = node.span();
+        self.lower_node(node)?
+            .ok_or_else(|| CompileError::MissingValue.at(span))
+    }
+
+    /// Adds an instruction to the current block.
+    fn push(&mut self, instruction: Instruction) {
+        // Code following a terminator is unreachable. It goes to a block of its own, which is
+        // removed once the function is lowered.
+        let block = match self.current {
+            Some(block) => block,
+            None => self.function.new_block(),
+        };
+        self.current = Some(block);
+        self.function
+            .block_mut(block)
+            .instructions
+            .push(instruction);
+    }
+
+    /// Ends the current block.
+    fn terminate(&mut self, terminator: Terminator) {
+        let block = match self.current.take() {
+            Some(block) => block,
+            None => self.function.new_block(),
+        };
+        self.function.block_mut(block).terminator = terminator;
+    }
+
+    /// Starts adding instructions to a block. The current block must be terminated.
+    fn switch_to(&mut self, block: BlockId) {
+        debug_assert!(self.current.is_none());
+        self.current = Some(block);
+    }
+
+    /// Adds a string to the read-only block, returning its index.
+    fn intern_string(&mut self, s: &str) -> usize {
+        let strings = &mut self.program.strings;
+        match strings.iter().position(|existing| existing == s) {
+            Some(i) => i,
+            None => {
+                strings.push(String::from(s));
+                strings.len() - 1
+            }
+        }
+    }
+
+    fn constant(&mut self, t: Type, value: i32) -> Temp {
+        let dest = self.function.new_temp(t);
+        self.push(Instruction::Const { dest, value });
+        dest
+    }
+
+    fn string(&mut self, s: &str) -> Temp {
+        let index = self.intern_string(s);
+        let dest = self.function.new_temp(Type::String);
+        self.push(Instruction::String { dest, index });
+        dest
+    }
+
+    fn binary(&mut self, operator: BinaryOperator, left: Temp, right: Temp) -> Temp {
+        let t = if operator.is_comparison() {
+            Type::Boolean
+        } else {
+            self.function.temp_type(left).clone()
+        };
+
+        let dest = self.function.new_temp(t);
+        self.push(Instruction::Binary {
+            dest,
+            operator,
+            left,
+            right,
+        });
+        dest
+    }
+
+    fn declare(&mut self, name: &str, variable: Variable) -> Result<()> {
+        let scope = self.scopes.last_mut().ok_or(CompileError::MissingScope)?;
+        scope.insert(String::from(name), variable);
+        Ok(())
+    }
+
+    /// Looks up a variable, starting from the innermost scope.
+    fn variable(&self, name: &str) -> Result<Variable> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.get(name))
+            .cloned()
+            .ok_or(CompileError::UnknownIdentifier {
+                name: String::from(name),
+            })
+    }
+
+    /// Returns the block aborting the program with a message, creating it if needed.
+    fn abort_block(&mut self, message: &str) -> BlockId {
+        if let Some(block) = self.abort_blocks.get(message) {
+            return *block;
+        }
+
+        let index = self.intern_string(message);
+        let message_temp = self.function.new_temp(Type::String);
+        let block = self.function.new_block();
+        self.function.block_mut(block).instructions = vec![
+            Instruction::String {
+                dest: message_temp,
+                index,
+            },
+            Instruction::Syscall {
+                dest: None,
+                call: SysCall::ABORT,
+                argument: Some(message_temp),
+            },
+        ];
+
+        self.abort_blocks.insert(String::from(message), block);
+        block
+    }
+
+    /// Aborts the program with a message, unless a condition holds.
+    fn check(&mut self, condition: Temp, message: &str) {
+        let next = self.function.new_block();
+        let otherwise = self.abort_block(message);
+        self.terminate(Terminator::Branch {
+            condition,
+            then: next,
+            otherwise,
+        });
+        self.switch_to(next);
+    }
+
+    /// Allocates an array on the heap.
+    ///
+    /// Arrays are never freed.
+    fn allocate_array(&mut self, length: Temp, element_type: &Type) -> Temp {
+        let zero = self.constant(Type::Integer, 0);
+        let valid = self.binary(BinaryOperator::GreaterOrEqual, length, zero);
+        self.check(valid, "Negative array length");
+
+        let element_size = self.constant(Type::Integer, self.types.size_of(element_type) as i32);
+        let elements_size = self.binary(BinaryOperator::Mul, length, element_size);
+        let header_size = self.constant(Type::Integer, typing::ARRAY_HEADER_SIZE as i32);
+        let size = self.binary(BinaryOperator::Add, elements_size, header_size);
+
+        let array = self
+            .function
+            .new_temp(Type::Array(Box::new(element_type.clone())));
+        self.push(Instruction::Syscall {
+            dest: Some(array),
+            call: SysCall::ALLOC,
+            argument: Some(size),
+        });
+        self.push(Instruction::Store {
+            address: Address::new(Base::Heap(array)),
+            value: length,
+        });
+        array
+    }
+
+    /// Computes the address of an array element, aborting the program if the index is out of
+    /// bounds.
+    ///
+    /// The address is relative to the array header.
+    fn element_address(&mut self, array: Temp, index: Temp, element_type: &Type) -> Temp {
+        // Bounds check: 0 <= index < length.
+        let zero = self.constant(Type::Integer, 0);
+        let positive = self.binary(BinaryOperator::GreaterOrEqual, index, zero);
+        self.check(positive, "Index out of bounds");
+
+        let length = self.function.new_temp(Type::Integer);
+        self.push(Instruction::Load {
+            dest: length,
+            address: Address::new(Base::Heap(array)),
+        });
+        let in_bounds = self.binary(BinaryOperator::Lower, index, length);
+        self.check(in_bounds, "Index out of bounds");
+
+        let element_size = self.constant(Type::Integer, self.types.size_of(element_type) as i32);
+        let offset = self.binary(BinaryOperator::Mul, index, element_size);
+        let address = self
+            .function
+            .new_temp(Type::Pointer(Box::new(element_type.clone())));
+        self.push(Instruction::Binary {
+            dest: address,
+            operator: BinaryOperator::Add,
+            left: array,
+            right: offset,
+        });
+        address
+    }
+
+    /// Lowers an atom and its trailers, returning where the resulting value is stored along with
+    /// its type.
+    fn lower_place(&mut self, atom: &mut Atom, trailers: &mut [Trailer]) -> Result<(Place, Type)> {
+        let (mut place, mut t) = match atom {
+            Atom::Identifier(name) => {
+                let var = self.variable(name)?;
+                (var.place, var.var_type)
+            }
+            _ => {
+                let temp = atom.accept(self)?.ok_or(CompileError::MissingValue)?;
+                let t = typing::literal_type(atom).ok_or(CompileError::MissingType)?;
+                (Place::Temp(temp), t)
+            }
+        };
+
+        for trailer in trailers.iter_mut() {
+            match trailer {
+                Trailer::Field(name) => {
+                    // Structs on the heap are accessed through a pointer.
+                    if let Type::Pointer(pointee) = &t {
+                        let pointee = pointee.as_ref().clone();
+                        let pointer = self.load(place, &t);
+                        place = Place::Memory(Address::new(Base::Heap(pointer)));
+                        t = pointee;
+                    }
+
+                    let structure = match &t {
+                        Type::Struct(s) => self.types.get_struct(s),
+                        _ => None,
+                    }
+                    .ok_or_else(|| CompileError::NotAStruct { t: t.clone() })?;
+                    let field =
+                        structure
+                            .field(name)
+                            .ok_or_else(|| CompileError::UnknownField {
+                                t: t.clone(),
+                                field: name.clone(),
+                            })?;
+
+                    place = match place {
+                        Place::Memory(address) => {
+                            Place::Memory(address.offset(field.offset as i32))
+                        }
+                        Place::Temp(_) => return Err(CompileError::InvalidRegisterState),
+                    };
+                    t = field.field_type.clone();
+                }
+                Trailer::Index(index) => {
+                    let element_type = t
+                        .element_type()
+                        .cloned()
+                        .ok_or_else(|| CompileError::NotAnArray { t: t.clone() })?;
+
+                    let array = self.load(place, &t);
+                    let index = self.lower_operand(index.as_mut())?;
+                    let address = self.element_address(array, index, &element_type);
+                    place = Place::Memory(
+                        Address::new(Base::Heap(address)).offset(typing::ARRAY_HEADER_SIZE as i32),
+                    );
+                    t = element_type;
+                }
+                Trailer::ArgumentList(_) => return Err(CompileError::SyntaxError),
+            }
+        }
+
+        Ok((place, t))
+    }
+
+    /// Returns a temporary holding the value of a place, loading it from memory if needed.
+    fn load(&mut self, place: Place, t: &Type) -> Temp {
+        match place {
+            Place::Temp(temp) => temp,
+            Place::Memory(address) => {
+                let dest = self.function.new_temp(t.clone());
+                self.push(Instruction::Load { dest, address });
+                dest
+            }
+        }
+    }
+
+    /// Lowers an expression whose value may not fit in a temporary, returning where it is.
+    ///
+    /// Structs are left in place, to be copied by the caller.
+    fn lower_value(&mut self, expr: &mut Expression) -> Result<Place> {
+        if !value_type(expr)?.is_struct() {
+            return Ok(Place::Temp(self.lower_operand(expr)?));
+        }
+
+        let atomic = expr
+            .as_atomic_mut()
+            .ok_or(CompileError::InvalidRegisterState)?;
+        let span = atomic.span;
+        let (place, _) = self
+            .lower_place(&mut atomic.atom, &mut atomic.trailers)
+            .map_err(|e| e.at(span))?;
+        Ok(place)
+    }
+
+    /// Stores a value of type `t` to a place. Values that are not in a temporary are copied.
+    fn store(&mut self, destination: Place, value: Place, t: &Type) -> Result<()> {
+        let instruction = match (destination, value) {
+            (Place::Temp(dest), Place::Temp(source)) => Instruction::Copy { dest, source },
+            (Place::Memory(address), Place::Temp(value)) => Instruction::Store { address, value },
+            (Place::Memory(dest), Place::Memory(source)) => Instruction::CopyMemory {
+                dest,
+                source,
+                size: self.types.size_of(t),
+            },
+            (Place::Temp(_), Place::Memory(_)) => return Err(CompileError::InvalidRegisterState),
+        };
+        self.push(instruction);
+        Ok(())
+    }
+
+    /// Sets a place of type `t` to zero.
+    fn store_zero(&mut self, destination: Place, t: &Type) {
+        match destination {
+            Place::Temp(dest) => self.push(Instruction::Const { dest, value: 0 }),
+            Place::Memory(address) => {
+                // Words and bytes are stored from temporaries of the matching size.
+                let word = self.constant(Type::Integer, 0);
+                let mut byte = None;
+                for (offset, size) in ir::chunks(self.types.size_of(t)) {
+                    let value = if size == 1 {
+                        *byte.get_or_insert_with(|| self.constant(Type::Boolean, 0))
+                    } else {
+                        word
+                    };
+                    self.push(Instruction::Store {
+                        address: address.offset(offset),
+                        value,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Lowers a call to a builtin function.
+    fn lower_builtin_call(&mut self, v: &mut FunctionCall) -> Result<Option<Temp>> {
+        if v.name == "len" {
+            ensure!(v.arguments.len() == 1, InvalidArgumentsSnafu);
+
+            // The length is stored in the header of the array.
+            let array = self.lower_operand(&mut v.arguments[0])?;
+            let length = self.function.new_temp(Type::Integer);
+            self.push(Instruction::Load {
+                dest: length,
+                address: Address::new(Base::Heap(array)),
+            });
+            return Ok(Some(length));
+        }
+
+        let newline = v.name == "println";
+        if let Some(expr) = v.arguments.first_mut() {
+            let value = self.lower_operand(expr)?;
+            let (call, argument) = match value_type(expr)?.clone() {
+                Type::String => (SysCall::CPRINT, value),
+                Type::Integer => (SysCall::PRINTI, value),
+                Type::Boolean => (SysCall::CPRINT, self.boolean_string(value)),
+                t => return Err(CompileError::NotPrintable { t }.at(expr.span)),
+            };
+            self.push(Instruction::Syscall {
+                dest: None,
+                call,
+                argument: Some(argument),
+            });
+        }
+
+        if newline {
+            let character = self.constant(Type::Integer, i32::from(b'\n'));
+            self.push(Instruction::Syscall {
+                dest: None,
+                call: SysCall::PRINTC,
+                argument: Some(character),
+            });
+        }
+
+        Ok(None)
+    }
+
+    /// Returns a temporary holding the string `true` or `false`, depending on a boolean.
+    fn boolean_string(&mut self, value: Temp) -> Temp {
+        let dest = self.function.new_temp(Type::String);
+        let then = self.function.new_block();
+        let otherwise = self.function.new_block();
+        let end = self.function.new_block();
+
+        self.terminate(Terminator::Branch {
+            condition: value,
+            then,
+            otherwise,
+        });
+        for (block, s) in [(then, "true"), (otherwise, "false")] {
+            self.switch_to(block);
+            let index = self.intern_string(s);
+            self.push(Instruction::String { dest, index });
+            self.terminate(Terminator::Jump(end));
+        }
+
+        self.switch_to(end);
+        dest
+    }
+
+    /// Lowers a `break` or `continue` statement to a jump to the matching loop block.
+    fn lower_loop_jump(&mut self, keyword: &str, span: Span) -> Result<()> {
+        let context = match self.loops.last() {
+            Some(c) => c,
+            None => {
+                return Err(CompileError::JumpOutsideOfLoop {
+                    keyword: String::from(keyword),
+                }
+                .at(span))
+            }
+        };
+
+        let target = if keyword == "break" {
+            context.break_block
+        } else {
+            context.continue_block
+        };
+        self.terminate(Terminator::Jump(target));
+        Ok(())
+    }
+
+    /// Lowers the body of a loop in a new scope.
+    fn lower_loop_body(
+        &mut self,
+        block: &mut Block,
+        continue_block: BlockId,
+        break_block: BlockId,
+    ) -> Result<()> {
+        self.loops.push(LoopContext {
+            continue_block,
+            break_block,
+        });
+
+        self.scopes.push(HashMap::new());
+        block.accept(self)?;
+
+        self.loops.pop();
+        Ok(())
+    }
+
+    /// Lowers operands joined by `&&` or `||`.
+    ///
+    /// Operands are evaluated from left to right, and evaluation stops as soon as an operand
+    /// determines the result: the first `false` operand of a conjunction, or the first `true`
+    /// operand of a disjunction.
+    fn lower_logical<T: Visitable + Spanned>(
+        &mut self,
+        operator: LogicalOperator,
+        root: &mut T,
+        trail: &mut [T],
+    ) -> Result<Option<Temp>> {
+        if trail.is_empty() {
+            return self.lower_node(root);
+        }
+
+        let result = self.function.new_temp(Type::Boolean);
+        let short_circuit = self.function.new_block();
+        let end = self.function.new_block();
+
+        for operand in std::iter::once(root).chain(trail.iter_mut()) {
+            let value = self.lower_operand(operand)?;
+
+            // Skip the remaining operands if the result is known.
+            let next = self.function.new_block();
+            let (then, otherwise) = match operator {
+                LogicalOperator::And => (next, short_circuit),
+                LogicalOperator::Or => (short_circuit, next),
+            };
+            self.terminate(Terminator::Branch {
+                condition: value,
+                then,
+                otherwise,
+            });
+            self.switch_to(next);
+        }
+
+        // Every operand was evaluated: a conjunction is true and a disjunction is false.
+        let all_evaluated = (operator == LogicalOperator::And) as i32;
+        self.push(Instruction::Const {
+            dest: result,
+            value: all_evaluated,
+        });
+        self.terminate(Terminator::Jump(end));
+
+        self.switch_to(short_circuit);
+        self.push(Instruction::Const {
+            dest: result,
+            value: 1 - all_evaluated,
+        });
+        self.terminate(Terminator::Jump(end));
+
+        self.switch_to(end);
+        Ok(Some(result))
+    }
+}
+
+impl<'a> Visitor for LowerVisitor<'a> {
+    type Result = Result<Option<Temp>>;
+
+    fn visit_expression(&mut self, v: &mut Expression) -> Self::Result {
+        self.lower_logical(LogicalOperator::Or, &mut v.root, &mut v.trail)
+    }
+
+    fn visit_conjunction(&mut self, v: &mut Conjunction) -> Self::Result {
+        self.lower_logical(LogicalOperator::And, &mut v.root, &mut v.trail)
+    }
+
+    fn visit_comparison(&mut self, v: &mut Comparison) -> Self::Result {
+        let (operator, rhs) = match v.comparison.as_mut() {
+            Some(comparison) => comparison,
+            None => return self.lower_node(&mut v.root),
+        };
+
+        let left = self.lower_operand(&mut v.root)?;
+        let right = self.lower_operand(rhs)?;
+        let operator = comparison_operator(operator)?;
+        Ok(Some(self.binary(operator, left, right)))
+    }
+
+    fn visit_arithmetic_expression(&mut self, v: &mut ArithmeticExpression) -> Self::Result {
+        if v.trail.is_empty() {
+            return self.lower_node(&mut v.root_term);
+        }
+
+        let mut left = self.lower_operand(&mut v.root_term)?;
+
+        for (operator, term) in v.trail.iter_mut() {
+            let right = self.lower_operand(term)?;
+            left = self.binary(term_operator(operator)?, left, right);
+        }
+
+        Ok(Some(left))
+    }
+
+    fn visit_factor(&mut self, v: &mut Factor) -> Self::Result {
+        match v {
+            Factor::FunctionCall(fn_call) => self.lower_node(fn_call),
+            Factor::Atomic(atom) => self.lower_node(atom),
+            Factor::Expression(expr) => self.lower_node(expr.as_mut()),
+            Factor::Unary(unary_op, factor) => {
+                let operand = self.lower_operand(factor.as_mut())?;
+                let operator = match unary_op {
+                    UnaryOperator::Plus => return Ok(Some(operand)),
+                    UnaryOperator::Minus => ir::UnaryOperator::Negate,
+                    UnaryOperator::Not => ir::UnaryOperator::Not,
+                    UnaryOperator::Unknown => return Err(CompileError::SyntaxError),
+                };
+
+                let dest = self
+                    .function
+                    .new_temp(self.function.temp_type(operand).clone());
+                self.push(Instruction::Unary {
+                    dest,
+                    operator,
+                    operand,
+                });
+                Ok(Some(dest))
+            }
+            Factor::IfExpression(if_expr) => self.lower_node(if_expr),
+            Factor::Allocation(allocation) => self.lower_node(allocation),
+        }
+    }
+
+    // Operators are lowered along with their operands.
+
+    fn visit_factor_operator(&mut self, _v: &mut FactorOperator) -> Self::Result {
+        Ok(None)
+    }
+
+    fn visit_term(&mut self, v: &mut Term) -> Self::Result {
+        if v.trail.is_empty() {
+            return self.lower_node(&mut v.root_factor);
+        }
+
+        let mut left = self.lower_operand(&mut v.root_factor)?;
+
+        for (operator, factor) in v.trail.iter_mut() {
+            let right = self.lower_operand(factor)?;
+            left = self.binary(factor_operator(operator)?, left, right);
+        }
+
+        Ok(Some(left))
+    }
+
+    fn visit_term_operator(&mut self, _v: &mut TermOperator) -> Self::Result {
+        Ok(None)
+    }
+
+    fn visit_comparison_operator(&mut self, _v: &mut ComparisonOperator) -> Self::Result {
+        Ok(None)
+    }
+
+    fn visit_unary_operator(&mut self, _v: &mut UnaryOperator) -> Self::Result {
+        Ok(None)
+    }
+
+    fn visit_function_declaration(&mut self, v: &mut FunctionDeclaration) -> Self::Result {
+        let functions = self.functions;
+        let function = functions
+            .get(&v.name)
+            .ok_or(CompileError::UnknownFunction {
+                name: v.name.clone(),
+            })?;
+
+        let parameters = function
+            .arguments
+            .iter()
+            .map(|t| ir::Parameter {
+                param_type: t.clone(),
+                size: self.types.size_of(t),
+            })
+            .collect();
+        self.function = ir::Function::new(&v.name, parameters, function.return_type.clone());
+        self.abort_blocks.clear();
+        let entry = self.function.new_block();
+        self.switch_to(entry);
+
+        // Scalar arguments are loaded into temporaries, structs are used in place.
+        // Arguments live in the same scope as the variables declared by the function body.
+        self.scopes.push(HashMap::new());
+        for (index, (arg, t)) in v.args.arguments.iter().zip(&function.arguments).enumerate() {
+            let address = Address::new(Base::Argument(index));
+            let place = if t.is_struct() {
+                Place::Memory(address)
+            } else {
+                let dest = self.function.new_temp(t.clone());
+                self.push(Instruction::Load { dest, address });
+                Place::Temp(dest)
+            };
+
+            self.declare(
+                &arg.name,
+                Variable {
+                    place,
+                    var_type: t.clone(),
+                },
+            )?;
+        }
+
+        v.block.accept(self)?;
+
+        // Functions that do not return a value can end without a `return` statement.
+        if self.current.is_some() {
+            self.terminate(Terminator::Return(None));
+        }
+
+        let mut function = mem::replace(
+            &mut self.function,
+            ir::Function::new("", Vec::new(), Type::Void),
+        );
+        function.thread_jumps();
+        function.sort_blocks();
+        self.program.functions.push(function);
+        Ok(None)
+    }
+
+    fn visit_statement(&mut self, v: &mut Statement) -> Self::Result {
+        match v {
+            Statement::Expr(expr) => {
+                // The value of the expression is discarded.
+                self.lower_node(expr)?;
+            }
+            Statement::Return(value, _) => {
+                let value = match value {
+                    Some(expr) => Some(self.lower_operand(expr)?),
+                    None => None,
+                };
+                self.terminate(Terminator::Return(value));
+            }
+            Statement::VarAssign(assignment) => {
+                self.lower_node(assignment)?;
+            }
+            Statement::VarDecl(declaration) => {
+                self.lower_node(declaration)?;
+            }
+            Statement::IfExpression(if_expr) => {
+                self.lower_node(if_expr)?;
+            }
+            Statement::WhileLoop(while_loop) => {
+                self.lower_node(while_loop)?;
+            }
+            Statement::ForLoop(for_loop) => {
+                self.lower_node(for_loop)?;
+            }
+            Statement::Break(span) => self.lower_loop_jump("break", *span)?,
+            Statement::Continue(span) => self.lower_loop_jump("continue", *span)?,
+        }
+
+        Ok(None)
+    }
+
+    fn visit_variable_declaration(&mut self, v: &mut VariableDeclaration) -> Self::Result {
+        let var_type = self.types.resolve(&v.var_type)?;
+        let place = if var_type.is_struct() {
+            let slot = self.function.new_slot(self.types.size_of(&var_type));
+            Place::Memory(Address::new(Base::Slot(slot)))
+        } else {
+            Place::Temp(self.function.new_temp(var_type.clone()))
+        };
+        self.declare(
+            &v.name,
+            Variable {
+                place,
+                var_type: var_type.clone(),
+            },
+        )?;
+
+        if let Some(length) = v.array_length {
+            let element_type = var_type
+                .element_type()
+                .ok_or_else(|| CompileError::NotAnArray {
+                    t: var_type.clone(),
+                })?;
+            let length = self.constant(Type::Integer, length);
+            let array = self.allocate_array(length, element_type);
+            self.store(place, Place::Temp(array), &var_type)?;
+        } else if let Some(expr) = v.expression.as_mut() {
+            let value = self.lower_value(expr)?;
+            self.store(place, value, &var_type)?;
+        } else {
+            // Variables declared without a value are zeroed, every time they are declared.
+            self.store_zero(place, &var_type);
+        }
+
+        Ok(None)
+    }
+
+    fn visit_program(&mut self, v: &mut Program) -> Self::Result {
+        let mut function_keys: Vec<String> = v.functions.keys().cloned().collect();
+        function_keys.sort();
+
+        for fn_name in function_keys.into_iter() {
+            let decl = v.functions.get_mut(&fn_name).unwrap();
+            self.lower_node(decl)?;
+        }
+
+        Ok(None)
+    }
+
+    fn visit_atomic_expression(&mut self, v: &mut AtomicExpression) -> Self::Result {
+        let (place, t) = self.lower_place(&mut v.atom, &mut v.trailers)?;
+        Ok(Some(self.load(place, &t)))
+    }
+
+    fn visit_atom(&mut self, v: &mut Atom) -> Self::Result {
+        let temp = match v {
+            Atom::Boolean(b) => self.constant(Type::Boolean, *b as i32),
+            Atom::Integer(i) => self.constant(Type::Integer, *i),
+            Atom::String(s) => self.string(s),
+            Atom::Identifier(name) => {
+                let var = self.variable(name)?;
+                self.load(var.place, &var.var_type)
+            }
+        };
+        Ok(Some(temp))
+    }
+
+    fn visit_block(&mut self, v: &mut Block) -> Self::Result {
+        for statement in v.body.iter_mut() {
+            statement.accept(self)?;
+        }
+
+        self.scopes.pop().ok_or(CompileError::MissingScope)?;
+        Ok(None)
+    }
+
+    fn visit_variable_assignment(&mut self, v: &mut VariableAssignment) -> Self::Result {
+        let (destination, variable_type) =
+            self.lower_place(&mut Atom::Identifier(v.name.clone()), &mut v.trailers)?;
+        let value = self.lower_value(&mut v.expression)?;
+        self.store(destination, value, &variable_type)?;
+        Ok(None)
+    }
+
+    fn visit_function_call(&mut self, v: &mut FunctionCall) -> Self::Result {
+        if builtins::is_builtin(&v.name) {
+            return self.lower_builtin_call(v);
+        }
+
+        let functions = self.functions;
+        let function = functions
+            .get(&v.name)
+            .ok_or(CompileError::UnknownFunction {
+                name: v.name.clone(),
+            })?;
+
+        let mut arguments = Vec::with_capacity(v.arguments.len());
+        for (expr, arg_type) in v.arguments.iter_mut().zip(&function.arguments) {
+            let argument = match self.lower_value(expr)? {
+                Place::Temp(temp) => ir::Argument::Value(temp),
+                Place::Memory(address) => ir::Argument::Memory {
+                    address,
+                    size: self.types.size_of(arg_type),
+                },
+            };
+            arguments.push(argument);
+        }
+
+        let dest = match function.return_type {
+            Type::Void => None,
+            ref t => Some(self.function.new_temp(t.clone())),
+        };
+        self.push(Instruction::Call {
+            dest,
+            function: function.name.clone(),
+            arguments,
+        });
+        Ok(dest)
+    }
+
+    fn visit_if_expression(&mut self, v: &mut IfExpression) -> Self::Result {
+        let condition = self.lower_operand(v.condition.as_mut())?;
+
+        let then = self.function.new_block();
+        let end = self.function.new_block();
+        let otherwise = match v.else_block {
+            Some(_) => self.function.new_block(),
+            None => end,
+        };
+        self.terminate(Terminator::Branch {
+            condition,
+            then,
+            otherwise,
+        });
+
+        self.switch_to(then);
+        self.scopes.push(HashMap::new());
+        v.if_block.accept(self)?;
+        self.terminate(Terminator::Jump(end));
+
+        if let Some(else_block) = &mut v.else_block {
+            self.switch_to(otherwise);
+            self.scopes.push(HashMap::new());
+            else_block.accept(self)?;
+            self.terminate(Terminator::Jump(end));
+        }
+
+        self.switch_to(end);
+        Ok(None)
+    }
+
+    fn visit_while_loop(&mut self, v: &mut WhileLoop) -> Self::Result {
+        let condition_block = self.function.new_block();
+        let body = self.function.new_block();
+        let end = self.function.new_block();
+
+        self.terminate(Terminator::Jump(condition_block));
+        self.switch_to(condition_block);
+        let condition = self.lower_operand(&mut v.condition)?;
+        self.terminate(Terminator::Branch {
+            condition,
+            then: body,
+            otherwise: end,
+        });
+
+        self.switch_to(body);
+        self.lower_loop_body(&mut v.block, condition_block, end)?;
+        self.terminate(Terminator::Jump(condition_block));
+
+        self.switch_to(end);
+        Ok(None)
+    }
+
+    fn visit_allocation(&mut self, v: &mut Allocation) -> Self::Result {
+        let allocated_type = self.types.resolve(&v.allocated_type)?;
+
+        if let Some(length) = v.length.as_mut() {
+            let length = self.lower_operand(length.as_mut())?;
+            return Ok(Some(self.allocate_array(length, &allocated_type)));
+        }
+
+        let size = self.constant(Type::Integer, self.types.size_of(&allocated_type) as i32);
+        let pointer = self
+            .function
+            .new_temp(Type::Pointer(Box::new(allocated_type)));
+        self.push(Instruction::Syscall {
+            dest: Some(pointer),
+            call: SysCall::ALLOC,
+            argument: Some(size),
+        });
+        Ok(Some(pointer))
+    }
+
+    fn visit_for_loop(&mut self, v: &mut ForLoop) -> Self::Result {
+        // Variables declared by the init clause live in a scope wrapping the whole loop.
+        self.scopes.push(HashMap::new());
+
+        if let Some(init) = v.init.as_mut() {
+            init.accept(self)?;
+        }
+
+        let condition_block = self.function.new_block();
+        let body = self.function.new_block();
+        let step = self.function.new_block();
+        let end = self.function.new_block();
+
+        self.terminate(Terminator::Jump(condition_block));
+        self.switch_to(condition_block);
+        match v.condition.as_mut() {
+            Some(condition) => {
+                let condition = self.lower_operand(condition)?;
+                self.terminate(Terminator::Branch {
+                    condition,
+                    then: body,
+                    otherwise: end,
+                });
+            }
+            None => self.terminate(Terminator::Jump(body)),
+        }
+
+        self.switch_to(body);
+        self.lower_loop_body(&mut v.block, step, end)?;
+        self.terminate(Terminator::Jump(step));
+
+        self.switch_to(step);
+        if let Some(statement) = v.step.as_mut() {
+            statement.accept(self)?;
+        }
+        self.terminate(Terminator::Jump(condition_block));
+
+        self.switch_to(end);
+        self.scopes.pop().ok_or(CompileError::MissingScope)?;
+        Ok(None)
+    }
+}
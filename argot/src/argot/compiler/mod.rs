@@ -1,12 +1,20 @@
-mod emit;
+mod builtins;
+mod call_graph;
+mod codegen;
 mod error;
 mod first_pass;
 mod label;
+mod lower;
 mod operator;
+mod regalloc;
 mod root;
-mod scope;
-mod second_pass;
+mod type_check;
 mod typing;
 
-pub use error::CompileError;
-pub use root::{compile, compile_asm};
+pub use call_graph::CallGraph;
+pub use error::{CompileError, Location};
+pub use root::{
+    compile, compile_asm, compile_asm_with, compile_asm_with_report, compile_ir, compile_ir_with,
+    compile_ir_with_report, compile_with, Options, Report, DEFAULT_INLINE_THRESHOLD,
+};
+pub use typing::Type;
@@ -1,30 +1,53 @@
-use crate::syntax::types::{FactorOperator, TermOperator, UnaryOperator};
+use crate::compiler::typing::Type;
+use crate::syntax::types::{
+    ComparisonOperator, FactorOperator, LogicalOperator, TermOperator, UnaryOperator,
+};
 
 pub trait Operator {
-    fn defined_for(&self, t: &str) -> bool;
+    fn defined_for(&self, t: &Type) -> bool;
 }
 
 impl Operator for FactorOperator {
-    fn defined_for(&self, t: &str) -> bool {
-        t == "int"
+    fn defined_for(&self, t: &Type) -> bool {
+        *t == Type::Integer
     }
 }
 
 impl Operator for TermOperator {
-    fn defined_for(&self, t: &str) -> bool {
+    fn defined_for(&self, t: &Type) -> bool {
         match self {
-            TermOperator::Plus | TermOperator::Minus => t == "int",
-            TermOperator::And | TermOperator::Or => t == "bool",
+            TermOperator::Plus | TermOperator::Minus => *t == Type::Integer,
             TermOperator::Unknown => false,
         }
     }
 }
 
+impl Operator for LogicalOperator {
+    fn defined_for(&self, t: &Type) -> bool {
+        *t == Type::Boolean
+    }
+}
+
+impl Operator for ComparisonOperator {
+    fn defined_for(&self, t: &Type) -> bool {
+        match self {
+            ComparisonOperator::Equal | ComparisonOperator::NotEqual => {
+                *t == Type::Integer || *t == Type::Boolean
+            }
+            ComparisonOperator::Greater
+            | ComparisonOperator::Lower
+            | ComparisonOperator::GreaterOrEqual
+            | ComparisonOperator::LowerOrEqual => *t == Type::Integer,
+            ComparisonOperator::Unknown => false,
+        }
+    }
+}
+
 impl Operator for UnaryOperator {
-    fn defined_for(&self, t: &str) -> bool {
+    fn defined_for(&self, t: &Type) -> bool {
         match self {
-            UnaryOperator::Plus | UnaryOperator::Minus => t == "int",
-            UnaryOperator::Not => t == "bool",
+            UnaryOperator::Plus | UnaryOperator::Minus => *t == Type::Integer,
+            UnaryOperator::Not => *t == Type::Boolean,
             UnaryOperator::Unknown => false,
         }
     }
@@ -0,0 +1,375 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::ir::{BlockId, Function, Instruction, Temp};
+
+/// Registers available to the allocator, split by calling convention.
+///
+/// A function may overwrite the caller-saved registers, so their values are saved around calls
+/// by the caller. A function saves the callee-saved registers it uses and restores them before
+/// returning.
+#[derive(Clone, Debug)]
+pub struct Registers {
+    pub caller_saved: Vec<u8>,
+    pub callee_saved: Vec<u8>,
+}
+
+impl Default for Registers {
+    /// Registers `$8` to `$31`. The first eight registers are left to the code generator as
+    /// scratch registers.
+    fn default() -> Registers {
+        Registers {
+            caller_saved: (8..20).collect(),
+            callee_saved: (20..32).collect(),
+        }
+    }
+}
+
+/// Where a temporary is kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    Register(u8),
+
+    /// Spill slot of the stack frame, holding a word.
+    Spill(usize),
+}
+
+/// Locations of the temporaries of a function.
+#[derive(Clone, Debug)]
+pub struct Allocation {
+    /// Location of each temporary, indexed by temporary. Temporaries only used by unreachable
+    /// code have none.
+    pub locations: Vec<Option<Location>>,
+
+    /// Number of spill slots.
+    pub spills: usize,
+
+    /// Callee-saved registers used by the function.
+    pub callee_saved: Vec<u8>,
+
+    /// Caller-saved registers holding values live across each call, by block and instruction.
+    pub call_saves: HashMap<(BlockId, usize), Vec<u8>>,
+}
+
+impl Allocation {
+    pub fn location(&self, temp: Temp) -> Location {
+        self.locations[temp.0].expect("temporary is not allocated")
+    }
+}
+
+/// Range of positions where a temporary is live.
+///
+/// Instructions are numbered in order through the blocks of the function. An instruction
+/// numbered `n` reads its operands at position `2n` and assigns its result at position `2n + 1`,
+/// so that its result can reuse the register of an operand it last reads.
+#[derive(Clone, Copy, Debug)]
+struct Interval {
+    temp: Temp,
+    start: usize,
+    end: usize,
+}
+
+impl Interval {
+    /// Returns whether the temporary is live across the instruction numbered `n`.
+    fn crosses(&self, n: usize) -> bool {
+        self.start < 2 * n && self.end > 2 * n + 1
+    }
+}
+
+/// Call instruction, by block and index in the block.
+struct Call {
+    position: (BlockId, usize),
+
+    /// Number of the instruction.
+    n: usize,
+}
+
+/// Returns the live interval of each temporary, ordered by start, and the calls of the function.
+fn intervals(function: &Function) -> (Vec<Interval>, Vec<Call>) {
+    let (live_in, live_out) = function.liveness();
+
+    let mut ranges: Vec<Option<(usize, usize)>> = vec![None; function.temps.len()];
+    let mut extend = |temp: Temp, position: usize| {
+        let range = ranges[temp.0].get_or_insert((position, position));
+        range.0 = range.0.min(position);
+        range.1 = range.1.max(position);
+    };
+
+    let mut calls = Vec::new();
+    let mut n = 0;
+    for (id, block) in function.blocks.iter().enumerate() {
+        for temp in live_in[id].iter() {
+            extend(*temp, 2 * n);
+        }
+
+        for (index, instruction) in block.instructions.iter().enumerate() {
+            for temp in instruction.uses() {
+                extend(temp, 2 * n);
+            }
+            if let Some(dest) = instruction.dest() {
+                extend(dest, 2 * n + 1);
+            }
+            if let Instruction::Call { .. } = instruction {
+                calls.push(Call {
+                    position: (BlockId(id), index),
+                    n,
+                });
+            }
+            n += 1;
+        }
+
+        for temp in block.terminator.uses() {
+            extend(temp, 2 * n);
+        }
+        for temp in live_out[id].iter() {
+            extend(*temp, 2 * n + 1);
+        }
+        n += 1;
+    }
+
+    let mut intervals: Vec<Interval> = ranges
+        .into_iter()
+        .enumerate()
+        .filter_map(|(temp, range)| {
+            range.map(|(start, end)| Interval {
+                temp: Temp(temp),
+                start,
+                end,
+            })
+        })
+        .collect();
+    intervals.sort_by_key(|interval| (interval.start, interval.temp));
+
+    (intervals, calls)
+}
+
+/// Assigns a register or a spill slot to each temporary of a function, by linear scan of the
+/// live intervals of the temporaries.
+///
+/// Temporaries live across a call get callee-saved registers first, so that they are not saved
+/// at each call. When registers run out, the temporary whose interval ends last is spilled.
+pub fn allocate(function: &Function, registers: &Registers) -> Allocation {
+    let (intervals, calls) = intervals(function);
+
+    let mut locations = vec![None; function.temps.len()];
+    let mut spills = 0;
+    let mut free_caller_saved: BTreeSet<u8> = registers.caller_saved.iter().copied().collect();
+    let mut free_callee_saved: BTreeSet<u8> = registers.callee_saved.iter().copied().collect();
+
+    // Intervals holding a register, with the register.
+    let mut active: Vec<(Interval, u8)> = Vec::new();
+
+    for interval in intervals.iter() {
+        active.retain(|(other, register)| {
+            if other.end >= interval.start {
+                return true;
+            }
+            if registers.callee_saved.contains(register) {
+                free_callee_saved.insert(*register);
+            } else {
+                free_caller_saved.insert(*register);
+            }
+            false
+        });
+
+        let crosses_call = calls.iter().any(|call| interval.crosses(call.n));
+        let (preferred, fallback) = if crosses_call {
+            (&mut free_callee_saved, &mut free_caller_saved)
+        } else {
+            (&mut free_caller_saved, &mut free_callee_saved)
+        };
+        let register = preferred.pop_first().or_else(|| fallback.pop_first());
+
+        match register {
+            Some(register) => {
+                locations[interval.temp.0] = Some(Location::Register(register));
+                active.push((*interval, register));
+            }
+            None => {
+                // Some interval is active, since there are no free registers.
+                let (last, _) = active
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, (other, _))| (other.end, other.temp))
+                    .unwrap();
+                let (spilled, register) = active[last];
+
+                if spilled.end > interval.end {
+                    locations[spilled.temp.0] = Some(Location::Spill(spills));
+                    locations[interval.temp.0] = Some(Location::Register(register));
+                    active[last] = (*interval, register);
+                } else {
+                    locations[interval.temp.0] = Some(Location::Spill(spills));
+                }
+                spills += 1;
+            }
+        }
+    }
+
+    let callee_saved: BTreeSet<u8> = locations
+        .iter()
+        .filter_map(|location| match location {
+            Some(Location::Register(register)) if registers.callee_saved.contains(register) => {
+                Some(*register)
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut call_saves = HashMap::new();
+    for call in calls.iter() {
+        let saved: BTreeSet<u8> = intervals
+            .iter()
+            .filter(|interval| interval.crosses(call.n))
+            .filter_map(|interval| match locations[interval.temp.0] {
+                Some(Location::Register(register))
+                    if registers.caller_saved.contains(&register) =>
+                {
+                    Some(register)
+                }
+                _ => None,
+            })
+            .collect();
+        call_saves.insert(call.position, saved.into_iter().collect());
+    }
+
+    Allocation {
+        locations,
+        spills,
+        callee_saved: callee_saved.into_iter().collect(),
+        call_saves,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate, Location, Registers};
+    use crate::compiler::Type;
+    use crate::ir::{Argument, BinaryOperator, BlockId, Function, Instruction, Temp, Terminator};
+
+    fn constant(f: &mut Function, block: BlockId, value: i32) -> Temp {
+        let dest = f.new_temp(Type::Integer);
+        f.block_mut(block)
+            .instructions
+            .push(Instruction::Const { dest, value });
+        dest
+    }
+
+    fn add(f: &mut Function, block: BlockId, left: Temp, right: Temp) -> Temp {
+        let dest = f.new_temp(Type::Integer);
+        f.block_mut(block).instructions.push(Instruction::Binary {
+            dest,
+            operator: BinaryOperator::Add,
+            left,
+            right,
+        });
+        dest
+    }
+
+    fn registers(caller_saved: &[u8], callee_saved: &[u8]) -> Registers {
+        Registers {
+            caller_saved: caller_saved.to_vec(),
+            callee_saved: callee_saved.to_vec(),
+        }
+    }
+
+    #[test]
+    fn reuse_registers() {
+        let mut f = Function::new("f", Vec::new(), Type::Integer);
+        let b = f.new_block();
+        let one = constant(&mut f, b, 1);
+        let two = constant(&mut f, b, 2);
+        let three = add(&mut f, b, one, two);
+        let four = add(&mut f, b, three, one);
+        f.block_mut(b).terminator = Terminator::Return(Some(four));
+
+        let allocation = allocate(&f, &registers(&[8, 9], &[]));
+        assert_eq!(allocation.spills, 0);
+        assert_eq!(allocation.location(one), Location::Register(8));
+        assert_eq!(allocation.location(two), Location::Register(9));
+        // `two` is last read by the addition, which can write its register.
+        assert_eq!(allocation.location(three), Location::Register(9));
+        assert_eq!(allocation.location(four), Location::Register(8));
+    }
+
+    #[test]
+    fn spill_longest_interval() {
+        let mut f = Function::new("f", Vec::new(), Type::Integer);
+        let b = f.new_block();
+        let one = constant(&mut f, b, 1);
+        let two = constant(&mut f, b, 2);
+        let three = constant(&mut f, b, 3);
+        let sum = add(&mut f, b, two, three);
+        let total = add(&mut f, b, sum, one);
+        f.block_mut(b).terminator = Terminator::Return(Some(total));
+
+        let allocation = allocate(&f, &registers(&[8, 9], &[]));
+        assert_eq!(allocation.spills, 1);
+        assert_eq!(allocation.location(one), Location::Spill(0));
+        assert_eq!(allocation.location(two), Location::Register(9));
+        assert_eq!(allocation.location(three), Location::Register(8));
+    }
+
+    #[test]
+    fn live_across_loop() {
+        let mut f = Function::new("f", Vec::new(), Type::Integer);
+        let entry = f.new_block();
+        let header = f.new_block();
+        let body = f.new_block();
+        let exit = f.new_block();
+
+        let total = constant(&mut f, entry, 0);
+        f.block_mut(entry).terminator = Terminator::Jump(header);
+        let condition = f.new_temp(Type::Boolean);
+        f.block_mut(header).terminator = Terminator::Branch {
+            condition,
+            then: body,
+            otherwise: exit,
+        };
+        let one = constant(&mut f, body, 1);
+        let next = add(&mut f, body, total, one);
+        f.block_mut(body).instructions.push(Instruction::Copy {
+            dest: total,
+            source: next,
+        });
+        f.block_mut(body).terminator = Terminator::Jump(header);
+        f.block_mut(exit).terminator = Terminator::Return(Some(total));
+
+        let allocation = allocate(&f, &registers(&[8, 9, 10, 11], &[]));
+        let location = allocation.location(total);
+        for temp in [condition, one, next] {
+            assert_ne!(allocation.location(temp), location);
+        }
+    }
+
+    #[test]
+    fn save_registers_across_calls() {
+        let mut f = Function::new("f", Vec::new(), Type::Integer);
+        let b = f.new_block();
+        let x = constant(&mut f, b, 1);
+        let y = constant(&mut f, b, 2);
+        let result = f.new_temp(Type::Integer);
+        f.block_mut(b).instructions.push(Instruction::Call {
+            dest: Some(result),
+            function: String::from("g"),
+            arguments: vec![Argument::Value(x)],
+        });
+        let sum = add(&mut f, b, result, x);
+        let total = add(&mut f, b, sum, y);
+        f.block_mut(b).terminator = Terminator::Return(Some(total));
+
+        // Values live across the call prefer callee-saved registers.
+        let allocation = allocate(&f, &registers(&[8], &[20, 21]));
+        assert_eq!(allocation.location(x), Location::Register(20));
+        assert_eq!(allocation.location(y), Location::Register(21));
+        assert_eq!(allocation.location(result), Location::Register(8));
+        assert_eq!(allocation.callee_saved, vec![20, 21]);
+        assert_eq!(allocation.call_saves[&(b, 2)], Vec::<u8>::new());
+
+        // Caller-saved registers holding them are saved around the call.
+        let allocation = allocate(&f, &registers(&[8, 9, 10], &[]));
+        assert_eq!(allocation.location(x), Location::Register(8));
+        assert_eq!(allocation.location(y), Location::Register(9));
+        assert!(allocation.callee_saved.is_empty());
+        assert_eq!(allocation.call_saves[&(b, 2)], vec![8, 9]);
+    }
+}
@@ -1,29 +1,208 @@
-use assembler::Assembler;
+use assembler::{peephole, Assembler};
+
+use nom::Err as NErr;
 
 use snafu::ResultExt;
 
 use crate::{
-    compiler::{error::*, first_pass::FirstPassVisitor, second_pass::SecondPassVisitor},
-    syntax::program::program,
+    compiler::{
+        codegen::CodeGenerator, error::*, first_pass::FirstPassVisitor, lower::LowerVisitor,
+        type_check::TypeCheckVisitor,
+    },
+    ir,
+    syntax::{
+        common::spanned, function::function_declaration, program::program,
+        statement::simple_statement, Span,
+    },
 };
 
-pub fn compile_asm(source: &str) -> Result<String> {
-    let (rest, mut p) = program(source)
-        .map_err(|e| ParseError {
-            message: e.to_string(),
-        })
-        .context(IncompleteParseSnafu)?;
+/// Returns the source starting at the first word that begins like an identifier but contains
+/// characters identifiers cannot, such as `f1`. String literals are skipped.
+fn rejected_identifier(source: &str) -> Option<&str> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut in_string = false;
+    let mut previous = ' ';
+    for (i, c) in source.char_indices() {
+        if c == '"' {
+            in_string = !in_string;
+        } else if !in_string && c.is_alphabetic() && !is_word_char(previous) {
+            let word = &source[i..];
+            let length = word.find(|c| !is_word_char(c)).unwrap_or(word.len());
+            if !word[..length].chars().all(char::is_alphabetic) {
+                return Some(word);
+            }
+        }
+        previous = c;
+    }
+    None
+}
+
+/// Builds the error for the first part of the source that could not be parsed.
+fn syntax_error(rest: &str) -> CompileError {
+    // The program parser stops at the first function it fails to parse. Parsing that function
+    // again gets us closer to the actual error.
+    let error_input = match function_declaration(rest) {
+        Err(NErr::Error(e)) | Err(NErr::Failure(e)) => e.input,
+        _ => rest,
+    };
+
+    // Identifiers are only made of letters. The parser stops in the middle of one that is not,
+    // so the error is reported on the whole identifier.
+    let error_line_end = error_input.find('\n').unwrap_or(error_input.len());
+    let parsed = &rest[..rest.len() - error_input.len() + error_line_end];
+    if let Some(identifier) = rejected_identifier(parsed) {
+        let identifier = &rest[parsed.len() - identifier.len()..];
+        let length = identifier
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(identifier.len());
+        return CompileError::SyntaxError
+            .at(Span::new(identifier.len(), identifier.len() - length));
+    }
+
+    // When the start of a statement parses, the error is right after it. If the statement is
+    // followed by a new line or the end of the block, it is only missing its semicolon.
+    let mut error_input = error_input;
+    if let Ok((_, (_, span))) = spanned(simple_statement)(error_input) {
+        let after_statement = &error_input[span.range(error_input).end..];
+        let next = after_statement.trim_start_matches([' ', '\t']);
+        if next.is_empty() || next.starts_with(['\r', '\n', '}']) {
+            return CompileError::MissingSemicolon.at(Span::at(after_statement));
+        }
+        error_input = next;
+    }
+
+    let token = error_input.trim_start();
+    let token_length = token
+        .find(char::is_whitespace)
+        .unwrap_or(token.len())
+        .max(1)
+        .min(token.len());
+    CompileError::SyntaxError.at(Span::new(token.len(), token.len() - token_length))
+}
+
+/// Options of the compiler.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Optimization level, from 0 for no optimizations to 2. See [`ir::optimize`].
+    pub opt_level: u8,
+
+    /// Size of the largest functions inlined from level 1, 0 disabling inlining. See
+    /// [`ir::inline`].
+    pub inline_threshold: usize,
+
+    /// Whether to run the peephole optimizer on the generated assembly. See [`peephole`].
+    pub peephole: bool,
+}
+
+/// Size of the largest functions inlined by default.
+pub const DEFAULT_INLINE_THRESHOLD: usize = 20;
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            opt_level: 0,
+            inline_threshold: DEFAULT_INLINE_THRESHOLD,
+            peephole: false,
+        }
+    }
+}
+
+/// What the optimizations did to a program.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// Calls inlined, and functions removed because they cannot be reached from `main`.
+    pub inlining: ir::InlineReport,
+
+    /// Statistics of the peephole optimizer, when it is enabled.
+    pub peephole: Option<peephole::Stats>,
+}
 
-    assert_eq!(rest, "");
+/// Compiles a program to the intermediate representation.
+pub fn compile_ir(source: &str) -> Result<ir::Program> {
+    compile_ir_with(source, &Options::default())
+}
+
+/// Compiles a program to the intermediate representation, with options.
+pub fn compile_ir_with(source: &str, options: &Options) -> Result<ir::Program> {
+    compile_ir_with_report(source, options).map(|(program, _)| program)
+}
+
+/// Compiles a program to the intermediate representation, along with what the optimizations
+/// did to it.
+pub fn compile_ir_with_report(source: &str, options: &Options) -> Result<(ir::Program, Report)> {
+    let (rest, mut p) =
+        program(source).map_err(|_| CompileError::SyntaxError.at(Span::at(source)))?;
+
+    if !rest.is_empty() {
+        return Err(syntax_error(rest));
+    }
 
     let first_pass_output = FirstPassVisitor::new().apply(&mut p)?;
-    let asm_source = SecondPassVisitor::new(first_pass_output.functions).apply(&mut p)?;
+    TypeCheckVisitor::new(&first_pass_output.functions, &first_pass_output.types).apply(&mut p)?;
 
-    Ok(asm_source)
+    // Functions that cannot be reached from `main` are checked, but not compiled.
+    let mut report = Report::default();
+    let reachable = first_pass_output.calls.reachable("main");
+    let mut unreachable: Vec<String> = p
+        .functions
+        .keys()
+        .filter(|name| !reachable.contains(*name))
+        .cloned()
+        .collect();
+    unreachable.sort();
+    for name in unreachable.iter() {
+        p.functions.remove(name);
+    }
+
+    let mut program =
+        LowerVisitor::new(&first_pass_output.functions, &first_pass_output.types).apply(&mut p)?;
+
+    if options.opt_level > 0 && options.inline_threshold > 0 {
+        report.inlining = ir::inline(
+            &mut program,
+            &first_pass_output.calls,
+            options.inline_threshold,
+        );
+    }
+    report.inlining.removed.extend(unreachable);
+    report.inlining.removed.sort();
+
+    ir::optimize(&mut program, options.opt_level);
+
+    // Tail calls keep deep recursion from overflowing the stack, so they do not depend on the
+    // optimization level.
+    ir::mark_tail_calls(&mut program);
+    Ok((program, report))
+}
+
+pub fn compile_asm(source: &str) -> Result<String> {
+    compile_asm_with(source, &Options::default())
+}
+
+pub fn compile_asm_with(source: &str, options: &Options) -> Result<String> {
+    compile_asm_with_report(source, options).map(|(asm, _)| asm)
+}
+
+/// Compiles a program to assembly, along with what the optimizations did to it.
+pub fn compile_asm_with_report(source: &str, options: &Options) -> Result<(String, Report)> {
+    let (program, mut report) = compile_ir_with_report(source, options)?;
+    let asm = CodeGenerator::new().apply(&program);
+    if !options.peephole {
+        return Ok((asm, report));
+    }
+
+    let (asm, stats) = peephole::optimize_source(&asm).context(AssemblySnafu)?;
+    report.peephole = Some(stats);
+    Ok((asm, report))
 }
 
 pub fn compile(source: &str) -> Result<Vec<u8>> {
-    let assembly_source = compile_asm(source)?;
+    compile_with(source, &Options::default())
+}
+
+pub fn compile_with(source: &str, options: &Options) -> Result<Vec<u8>> {
+    let assembly_source = compile_asm_with(source, options)?;
     Assembler::new()
         .assemble(&assembly_source)
         .context(AssemblySnafu)
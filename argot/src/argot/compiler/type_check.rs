@@ -0,0 +1,553 @@
+use std::collections::HashMap;
+
+use snafu::ensure;
+
+use crate::{
+    compiler::{
+        builtins,
+        error::*,
+        first_pass::FunctionDecl,
+        typing::{self, Type, TypeTable},
+    },
+    syntax::types::*,
+    visitor::{Visitable, Visitor},
+};
+
+/// Returns whether every path through a block ends with a `return` statement.
+fn always_returns(block: &Block) -> bool {
+    block.body.iter().any(|statement| match statement {
+        Statement::Return(..) => true,
+        Statement::IfExpression(if_expr) => match &if_expr.else_block {
+            Some(else_block) => always_returns(&if_expr.if_block) && always_returns(else_block),
+            None => false,
+        },
+        _ => false,
+    })
+}
+
+/// Ensures a value has the type it is used as.
+fn expect(t: Type, expected: &Type) -> Result<()> {
+    ensure!(
+        t == *expected,
+        TypeMismatchSnafu {
+            t1: t,
+            t2: expected.clone()
+        }
+    );
+    Ok(())
+}
+
+/// Checks the types of a program, and annotates its expressions with the type of their value.
+///
+/// Runs after the first pass has resolved the signatures of the functions, so that the second
+/// pass only generates code for well-typed programs.
+pub struct TypeCheckVisitor<'a> {
+    functions: &'a HashMap<String, FunctionDecl>,
+    types: &'a TypeTable,
+
+    /// Types of the variables in scope, innermost scope last.
+    scopes: Vec<HashMap<String, Type>>,
+
+    /// Return type of the function being checked.
+    return_type: Type,
+}
+
+impl<'a> TypeCheckVisitor<'a> {
+    pub fn new(
+        functions: &'a HashMap<String, FunctionDecl>,
+        types: &'a TypeTable,
+    ) -> TypeCheckVisitor<'a> {
+        TypeCheckVisitor {
+            functions,
+            types,
+            scopes: Vec::new(),
+            return_type: Type::Void,
+        }
+    }
+
+    pub fn apply(&mut self, program: &mut Program) -> Result<()> {
+        program.accept(self)?;
+        debug_assert!(self.scopes.is_empty());
+        Ok(())
+    }
+
+    /// Checks a syntax node, attaching its span to the errors it raises.
+    fn check_node<T: Visitable + Spanned>(&mut self, node: &mut T) -> Result<Type> {
+        let span = node.span();
+        node.accept(self).map_err(|e| e.at(span))
+    }
+
+    /// Checks a block in a new scope.
+    fn check_block(&mut self, block: &mut Block) -> Result<()> {
+        self.scopes.push(HashMap::new());
+        block.accept(self)?;
+        Ok(())
+    }
+
+    fn declare(&mut self, name: &str, t: Type) -> Result<()> {
+        let scope = self.scopes.last_mut().ok_or(CompileError::MissingScope)?;
+        ensure!(
+            !scope.contains_key(name),
+            VariableAlreadyDefinedSnafu { name }
+        );
+        scope.insert(String::from(name), t);
+        Ok(())
+    }
+
+    /// Looks up the type of a variable, starting from the innermost scope.
+    fn variable(&self, name: &str) -> Result<Type> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.get(name))
+            .cloned()
+            .ok_or(CompileError::UnknownIdentifier {
+                name: String::from(name),
+            })
+    }
+
+    /// Checks an atom and its trailers, returning the type of the value they designate.
+    fn check_place(&mut self, atom: &mut Atom, trailers: &mut [Trailer]) -> Result<Type> {
+        let mut t = atom.accept(self)?;
+
+        for trailer in trailers.iter_mut() {
+            t = match trailer {
+                Trailer::Field(name) => {
+                    // Fields of structs on the heap are accessed through their pointer.
+                    let struct_name = t
+                        .struct_name()
+                        .ok_or_else(|| CompileError::NotAStruct { t: t.clone() })?;
+                    let structure = self
+                        .types
+                        .get_struct(struct_name)
+                        .ok_or_else(|| CompileError::NotAStruct { t: t.clone() })?;
+                    let field =
+                        structure
+                            .field(name)
+                            .ok_or_else(|| CompileError::UnknownField {
+                                t: Type::Struct(String::from(struct_name)),
+                                field: name.clone(),
+                            })?;
+                    field.field_type.clone()
+                }
+                Trailer::Index(index) => {
+                    let element_type = t
+                        .element_type()
+                        .cloned()
+                        .ok_or_else(|| CompileError::NotAnArray { t: t.clone() })?;
+                    let index_type = self.check_node(index.as_mut())?;
+                    expect(index_type, &Type::Integer).map_err(|e| e.at(index.span))?;
+                    element_type
+                }
+                Trailer::ArgumentList(_) => return Err(CompileError::SyntaxError),
+            };
+        }
+
+        Ok(t)
+    }
+
+    /// Checks an expression whose value may be a struct.
+    ///
+    /// Structs cannot be used in expressions, but can be copied to variables and arguments.
+    fn check_value(&mut self, expr: &mut Expression) -> Result<Type> {
+        let t = match expr.as_atomic_mut() {
+            Some(atomic) => {
+                let span = atomic.span;
+                self.check_place(&mut atomic.atom, &mut atomic.trailers)
+                    .map_err(|e| e.at(span))?
+            }
+            None => return self.check_node(expr),
+        };
+
+        expr.value_type = Some(t.clone());
+        Ok(t)
+    }
+
+    /// Checks operands joined by `&&` or `||`, which must all be booleans.
+    fn check_logical<T: Visitable + Spanned>(
+        &mut self,
+        operator: LogicalOperator,
+        root: &mut T,
+        trail: &mut [T],
+    ) -> Result<Type> {
+        if trail.is_empty() {
+            return self.check_node(root);
+        }
+
+        let start = root.span();
+        let mut previous_type = None;
+        for operand in std::iter::once(root).chain(trail.iter_mut()) {
+            let operand_type = self.check_node(operand)?;
+            let t2 = previous_type.as_ref().unwrap_or(&operand_type);
+            typing::typecheck_binary_operator(&operator, &operand_type, t2)
+                .map_err(|e| e.at(start.to(operand.span())))?;
+            previous_type = Some(operand_type);
+        }
+
+        Ok(Type::Boolean)
+    }
+
+    /// Checks a call to a builtin function.
+    fn check_builtin_call(&mut self, v: &mut FunctionCall) -> Result<Type> {
+        if v.name == "len" {
+            ensure!(v.arguments.len() == 1, InvalidArgumentsSnafu);
+
+            let expr = &mut v.arguments[0];
+            let t = self.check_node(expr)?;
+            if t.element_type().is_none() {
+                return Err(CompileError::NotAnArray { t }.at(expr.span));
+            }
+            return Ok(Type::Integer);
+        }
+
+        let newline = v.name == "println";
+        ensure!(
+            v.arguments.len() == 1 || (newline && v.arguments.is_empty()),
+            InvalidArgumentsSnafu
+        );
+
+        if let Some(expr) = v.arguments.first_mut() {
+            let t = self.check_node(expr)?;
+            match t {
+                Type::Integer | Type::Boolean | Type::String => {}
+                _ => return Err(CompileError::NotPrintable { t }.at(expr.span)),
+            }
+        }
+
+        Ok(Type::Void)
+    }
+
+    fn check_return(&mut self, value: &mut Option<Expression>, span: Span) -> Result<()> {
+        match value {
+            Some(expr) => {
+                let t = self.check_node(expr)?;
+                expect(t, &self.return_type).map_err(|e| e.at(expr.span))
+            }
+            None => expect(Type::Void, &self.return_type).map_err(|e| e.at(span)),
+        }
+    }
+}
+
+impl<'a> Visitor for TypeCheckVisitor<'a> {
+    type Result = Result<Type>;
+
+    fn visit_factor(&mut self, v: &mut Factor) -> Self::Result {
+        match v {
+            Factor::FunctionCall(fn_call) => self.check_node(fn_call),
+            Factor::Atomic(atom) => self.check_node(atom),
+            Factor::Expression(expr) => self.check_node(expr.as_mut()),
+            Factor::Unary(unary_op, factor) => {
+                let t = factor.accept(self)?;
+                typing::typecheck_unary_operator(unary_op, &t).map_err(|e| e.at(factor.span()))?;
+                Ok(t)
+            }
+            Factor::IfExpression(if_expr) => {
+                if if_expr.else_block.is_none() {
+                    return Err(CompileError::NotAllPathsReturnAValue.at(if_expr.span));
+                }
+                self.check_node(if_expr)
+            }
+            Factor::Allocation(allocation) => self.check_node(allocation),
+        }
+    }
+
+    // Operators are checked along with their operands.
+
+    fn visit_factor_operator(&mut self, _v: &mut FactorOperator) -> Self::Result {
+        Ok(Type::Void)
+    }
+
+    fn visit_term(&mut self, v: &mut Term) -> Self::Result {
+        let t = v.root_factor.accept(self)?;
+
+        let start = v.root_factor.span();
+        for (operator, factor) in v.trail.iter_mut() {
+            let factor_type = factor.accept(self)?;
+            typing::typecheck_binary_operator(operator, &factor_type, &t)
+                .map_err(|e| e.at(start.to(factor.span())))?;
+        }
+
+        Ok(t)
+    }
+
+    fn visit_term_operator(&mut self, _v: &mut TermOperator) -> Self::Result {
+        Ok(Type::Void)
+    }
+
+    fn visit_comparison_operator(&mut self, _v: &mut ComparisonOperator) -> Self::Result {
+        Ok(Type::Void)
+    }
+
+    fn visit_unary_operator(&mut self, _v: &mut UnaryOperator) -> Self::Result {
+        Ok(Type::Void)
+    }
+
+    fn visit_function_declaration(&mut self, v: &mut FunctionDeclaration) -> Self::Result {
+        let functions = self.functions;
+        let function = functions
+            .get(&v.name)
+            .ok_or(CompileError::UnknownFunction {
+                name: v.name.clone(),
+            })?;
+
+        if function.return_type != Type::Void {
+            ensure!(always_returns(&v.block), NotAllPathsReturnAValueSnafu);
+        }
+        self.return_type = function.return_type.clone();
+
+        // Arguments live in the same scope as the variables declared by the function body.
+        self.scopes.push(HashMap::new());
+        for (arg, t) in v.args.arguments.iter().zip(&function.arguments) {
+            self.declare(&arg.name, t.clone())
+                .map_err(|e| e.at(arg.span))?;
+        }
+
+        v.block.accept(self)?;
+        Ok(Type::Void)
+    }
+
+    fn visit_statement(&mut self, v: &mut Statement) -> Self::Result {
+        match v {
+            Statement::Expr(expr) => {
+                self.check_node(expr)?;
+            }
+            Statement::Return(value, span) => self.check_return(value, *span)?,
+            Statement::VarAssign(assignment) => {
+                self.check_node(assignment)?;
+            }
+            Statement::VarDecl(declaration) => {
+                self.check_node(declaration)?;
+            }
+            Statement::IfExpression(if_expr) => {
+                self.check_node(if_expr)?;
+            }
+            Statement::WhileLoop(while_loop) => {
+                self.check_node(while_loop)?;
+            }
+            Statement::ForLoop(for_loop) => {
+                self.check_node(for_loop)?;
+            }
+            Statement::Break(_) | Statement::Continue(_) => {}
+        }
+
+        Ok(Type::Void)
+    }
+
+    fn visit_variable_declaration(&mut self, v: &mut VariableDeclaration) -> Self::Result {
+        let var_type = self.types.resolve(&v.var_type)?;
+        self.declare(&v.name, var_type.clone())?;
+
+        if let Some(expr) = v.expression.as_mut() {
+            let expr_type = self.check_value(expr)?;
+            expect(expr_type, &var_type)?;
+        }
+
+        Ok(Type::Void)
+    }
+
+    fn visit_expression(&mut self, v: &mut Expression) -> Self::Result {
+        let t = self.check_logical(LogicalOperator::Or, &mut v.root, &mut v.trail)?;
+        v.value_type = Some(t.clone());
+        Ok(t)
+    }
+
+    fn visit_conjunction(&mut self, v: &mut Conjunction) -> Self::Result {
+        self.check_logical(LogicalOperator::And, &mut v.root, &mut v.trail)
+    }
+
+    fn visit_comparison(&mut self, v: &mut Comparison) -> Self::Result {
+        let t = self.check_node(&mut v.root)?;
+
+        let start = v.root.span();
+        match v.comparison.as_mut() {
+            Some((operator, rhs)) => {
+                let rhs_type = self.check_node(rhs)?;
+                typing::typecheck_binary_operator(operator, &rhs_type, &t)
+                    .map_err(|e| e.at(start.to(rhs.span())))?;
+                Ok(Type::Boolean)
+            }
+            None => Ok(t),
+        }
+    }
+
+    fn visit_arithmetic_expression(&mut self, v: &mut ArithmeticExpression) -> Self::Result {
+        let t = self.check_node(&mut v.root_term)?;
+
+        let start = v.root_term.span();
+        for (operator, term) in v.trail.iter_mut() {
+            let term_type = self.check_node(term)?;
+            typing::typecheck_binary_operator(operator, &term_type, &t)
+                .map_err(|e| e.at(start.to(term.span())))?;
+        }
+
+        Ok(t)
+    }
+
+    fn visit_program(&mut self, v: &mut Program) -> Self::Result {
+        let mut function_keys: Vec<String> = v.functions.keys().cloned().collect();
+        function_keys.sort();
+
+        for fn_name in function_keys.into_iter() {
+            let decl = v.functions.get_mut(&fn_name).unwrap();
+            self.check_node(decl)?;
+        }
+
+        Ok(Type::Void)
+    }
+
+    fn visit_atomic_expression(&mut self, v: &mut AtomicExpression) -> Self::Result {
+        let t = self.check_place(&mut v.atom, &mut v.trailers)?;
+        if t.is_struct() {
+            return Err(CompileError::NotAValue { t });
+        }
+        Ok(t)
+    }
+
+    fn visit_atom(&mut self, v: &mut Atom) -> Self::Result {
+        match v {
+            Atom::Identifier(name) => self.variable(name),
+            _ => typing::literal_type(v).ok_or(CompileError::MissingType),
+        }
+    }
+
+    fn visit_block(&mut self, v: &mut Block) -> Self::Result {
+        for statement in v.body.iter_mut() {
+            statement.accept(self)?;
+        }
+
+        self.scopes.pop().ok_or(CompileError::MissingScope)?;
+        Ok(Type::Void)
+    }
+
+    fn visit_variable_assignment(&mut self, v: &mut VariableAssignment) -> Self::Result {
+        let variable_type =
+            self.check_place(&mut Atom::Identifier(v.name.clone()), &mut v.trailers)?;
+        let expr_type = self.check_value(&mut v.expression)?;
+        expect(expr_type, &variable_type)?;
+        Ok(Type::Void)
+    }
+
+    fn visit_function_call(&mut self, v: &mut FunctionCall) -> Self::Result {
+        if builtins::is_builtin(&v.name) {
+            return self.check_builtin_call(v);
+        }
+
+        let functions = self.functions;
+        let function = functions
+            .get(&v.name)
+            .ok_or(CompileError::UnknownFunction {
+                name: v.name.clone(),
+            })?;
+
+        ensure!(
+            v.arguments.len() == function.arguments.len(),
+            InvalidCallSnafu {
+                name: v.name.clone(),
+                t: function.signature()
+            }
+        );
+
+        for (expr, arg_type) in v.arguments.iter_mut().zip(&function.arguments) {
+            let expr_type = self.check_value(expr)?;
+            expect(expr_type, arg_type).map_err(|e| e.at(expr.span))?;
+        }
+
+        Ok(function.return_type.clone())
+    }
+
+    fn visit_if_expression(&mut self, v: &mut IfExpression) -> Self::Result {
+        self.check_node(v.condition.as_mut())?;
+
+        self.check_block(&mut v.if_block)?;
+        if let Some(else_block) = &mut v.else_block {
+            self.check_block(else_block)?;
+        }
+
+        Ok(Type::Void)
+    }
+
+    fn visit_while_loop(&mut self, v: &mut WhileLoop) -> Self::Result {
+        self.check_node(&mut v.condition)?;
+        self.check_block(&mut v.block)?;
+        Ok(Type::Void)
+    }
+
+    fn visit_for_loop(&mut self, v: &mut ForLoop) -> Self::Result {
+        // Variables declared by the init clause live in a scope wrapping the whole loop.
+        self.scopes.push(HashMap::new());
+
+        if let Some(init) = v.init.as_mut() {
+            init.accept(self)?;
+        }
+        if let Some(condition) = v.condition.as_mut() {
+            self.check_node(condition)?;
+        }
+
+        self.check_block(&mut v.block)?;
+
+        if let Some(step) = v.step.as_mut() {
+            step.accept(self)?;
+        }
+
+        self.scopes.pop().ok_or(CompileError::MissingScope)?;
+        Ok(Type::Void)
+    }
+
+    fn visit_allocation(&mut self, v: &mut Allocation) -> Self::Result {
+        let allocated_type = self.types.resolve(&v.allocated_type)?;
+
+        match v.length.as_mut() {
+            Some(length) => {
+                let length_type = self.check_node(length.as_mut())?;
+                expect(length_type, &Type::Integer).map_err(|e| e.at(length.span))?;
+                Ok(Type::Array(Box::new(allocated_type)))
+            }
+            None => Ok(Type::Pointer(Box::new(allocated_type))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeCheckVisitor;
+    use crate::compiler::{first_pass::FirstPassVisitor, typing::Type};
+    use crate::syntax::{program::program, Statement};
+
+    /// Type checks a program, returning the annotated types of the initializers of `main`.
+    fn initializer_types(source: &str) -> Vec<Option<Type>> {
+        let (_, mut p) = program(source).unwrap();
+        let output = FirstPassVisitor::new().apply(&mut p).unwrap();
+        TypeCheckVisitor::new(&output.functions, &output.types)
+            .apply(&mut p)
+            .unwrap();
+
+        p.functions["main"]
+            .block
+            .body
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::VarDecl(decl) => decl.expression.as_ref(),
+                _ => None,
+            })
+            .map(|expr| expr.value_type.clone())
+            .collect()
+    }
+
+    #[test]
+    fn annotations() {
+        let types = initializer_types(
+            "struct Point { int x; }\n\
+             fn main() {\n    Point p;\n    Point q = p;\n    bool b = p.x > 2;\n    Point*[] r = new Point*[3];\n}",
+        );
+
+        let point = Type::Struct(String::from("Point"));
+        assert_eq!(
+            types,
+            vec![
+                Some(point.clone()),
+                Some(Type::Boolean),
+                Some(Type::Array(Box::new(Type::Pointer(Box::new(point))))),
+            ]
+        );
+    }
+}
@@ -1,57 +1,388 @@
-use std::convert::TryFrom;
+use std::collections::HashMap;
 use std::fmt;
 use std::mem;
 
 use snafu::ensure;
 
 use crate::compiler::{error::*, operator::Operator};
+use crate::syntax::{
+    function::VOID_TYPE,
+    types::{Atom, StructDeclaration},
+};
 
-#[derive(Debug)]
-pub struct UnknownType {
-    type_name: String,
+/// Size of the header stored before the elements of an array: its length, as an `i32`.
+pub const ARRAY_HEADER_SIZE: usize = mem::size_of::<i32>();
+
+/// Type of an Argot value.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    /// Type of the calls to functions that do not return a value.
+    Void,
+    Integer,
+    Boolean,
+
+    /// Offset of a null-terminated string in the read-only block.
+    String,
+
+    /// Pointer to an array on the heap, stored as its length followed by its elements.
+    Array(Box<Type>),
+
+    /// Struct declared by the program, stored by value.
+    Struct(String),
+
+    /// Pointer to a single value on the heap.
+    Pointer(Box<Type>),
+
+    /// Signature of a function.
+    Function {
+        arguments: Vec<Type>,
+        return_type: Box<Type>,
+    },
 }
 
-impl fmt::Display for UnknownType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Unknown type: {}", self.type_name)
+impl Type {
+    /// Returns the type of the elements of an array type, such as `int` for `int[]`.
+    pub fn element_type(&self) -> Option<&Type> {
+        match self {
+            Type::Array(element) => Some(element),
+            _ => None,
+        }
     }
-}
 
-impl std::error::Error for UnknownType {}
+    /// Returns the struct a value of this type gives access to, dereferencing pointers.
+    pub fn struct_name(&self) -> Option<&str> {
+        match self {
+            Type::Struct(name) => Some(name),
+            Type::Pointer(t) => match t.as_ref() {
+                Type::Struct(name) => Some(name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 
-pub enum BuiltInType {
-    Integer,
-    Boolean,
+    /// Returns whether values of this type are too large for a register, and are copied instead.
+    pub fn is_struct(&self) -> bool {
+        matches!(self, Type::Struct(_))
+    }
 }
 
-impl BuiltInType {
-    pub fn alloc_size(&self) -> usize {
+impl fmt::Display for Type {
+    /// Formats a type the way it is written in Argot.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            BuiltInType::Integer => mem::size_of::<i32>(),
-            BuiltInType::Boolean => 1,
+            Type::Void => write!(f, "{}", VOID_TYPE),
+            Type::Integer => write!(f, "int"),
+            Type::Boolean => write!(f, "bool"),
+            Type::String => write!(f, "string"),
+            Type::Array(element) => write!(f, "{}[]", element),
+            Type::Struct(name) => write!(f, "{}", name),
+            Type::Pointer(t) => write!(f, "{}*", t),
+            Type::Function {
+                arguments,
+                return_type,
+            } => {
+                let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+                write!(f, "fn({})", arguments.join(", "))?;
+                if **return_type != Type::Void {
+                    write!(f, " -> {}", return_type)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
-impl TryFrom<String> for BuiltInType {
-    type Error = UnknownType;
+/// Returns the type of a literal, or `None` for identifiers.
+pub fn literal_type(atom: &Atom) -> Option<Type> {
+    match atom {
+        Atom::Boolean(_) => Some(Type::Boolean),
+        Atom::Integer(_) => Some(Type::Integer),
+        Atom::String(_) => Some(Type::String),
+        Atom::Identifier(_) => None,
+    }
+}
 
-    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
-        match value.as_ref() {
-            "int" => Ok(BuiltInType::Integer),
-            "bool" => Ok(BuiltInType::Boolean),
-            _ => Err(UnknownType { type_name: value }),
+/// Field of a struct, along with its position in the struct.
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub name: String,
+    pub field_type: Type,
+    pub offset: usize,
+}
+
+/// Layout of a struct.
+///
+/// Fields are stored in declaration order, without padding.
+#[derive(Clone, Debug)]
+pub struct StructType {
+    pub fields: Vec<Field>,
+    pub size: usize,
+}
+
+impl StructType {
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// Types a program can use: builtin types, the structs it declares, and arrays of and pointers
+/// to such types.
+///
+/// Arrays and pointers point to the heap, so their size does not depend on what they point to.
+/// Structs are stored by value, so a struct cannot contain itself.
+#[derive(Default)]
+pub struct TypeTable {
+    structs: HashMap<String, StructType>,
+}
+
+impl TypeTable {
+    pub fn new() -> TypeTable {
+        TypeTable {
+            structs: HashMap::new(),
+        }
+    }
+
+    /// Computes the layout of the structs declared by a program.
+    pub fn from_declarations(declarations: &[StructDeclaration]) -> Result<TypeTable> {
+        let mut table = TypeTable::new();
+
+        // Structs are registered before their layout is computed, so that fields can refer to
+        // structs declared later.
+        let mut by_name = HashMap::new();
+        for decl in declarations.iter() {
+            if table.resolve(&decl.name).is_ok() {
+                return Err(CompileError::TypeAlreadyDefined {
+                    name: decl.name.clone(),
+                }
+                .at(decl.span));
+            }
+
+            by_name.insert(decl.name.as_str(), decl);
+            table.structs.insert(
+                decl.name.clone(),
+                StructType {
+                    fields: Vec::new(),
+                    size: 0,
+                },
+            );
+        }
+
+        let mut laid_out = Vec::new();
+        for decl in declarations.iter() {
+            table.layout(&decl.name, &by_name, &mut laid_out, &mut Vec::new())?;
+        }
+        Ok(table)
+    }
+
+    /// Computes the layout of a struct and of the structs it contains.
+    fn layout(
+        &mut self,
+        name: &str,
+        declarations: &HashMap<&str, &StructDeclaration>,
+        laid_out: &mut Vec<String>,
+        pending: &mut Vec<String>,
+    ) -> Result<()> {
+        if laid_out.iter().any(|l| l == name) {
+            return Ok(());
+        }
+
+        let decl = declarations[name];
+        if pending.iter().any(|p| p == name) {
+            return Err(CompileError::RecursiveStruct {
+                name: String::from(name),
+            }
+            .at(decl.span));
+        }
+        pending.push(String::from(name));
+
+        let mut fields: Vec<Field> = Vec::with_capacity(decl.fields.len());
+        let mut offset = 0;
+        for field in decl.fields.iter() {
+            if fields.iter().any(|f| f.name == field.name) {
+                return Err(CompileError::DuplicateField {
+                    name: field.name.clone(),
+                }
+                .at(field.span));
+            }
+
+            let field_type = self
+                .resolve(&field.field_type)
+                .map_err(|e| e.at(field.span))?;
+            if let Type::Struct(s) = &field_type {
+                self.layout(s, declarations, laid_out, pending)
+                    .map_err(|e| e.at(field.span))?;
+            }
+
+            let size = self.size_of(&field_type);
+            fields.push(Field {
+                name: field.name.clone(),
+                field_type,
+                offset,
+            });
+            offset += size;
+        }
+
+        pending.pop();
+        laid_out.push(String::from(name));
+        self.structs.insert(
+            String::from(name),
+            StructType {
+                fields,
+                size: offset,
+            },
+        );
+        Ok(())
+    }
+
+    /// Resolves the name of a type, such as `int[]` or `Point*`.
+    ///
+    /// `void` is not the type of any value, so it is not resolved.
+    pub fn resolve(&self, name: &str) -> Result<Type> {
+        if let Some(element) = name.strip_suffix("[]") {
+            return Ok(Type::Array(Box::new(self.resolve(element)?)));
+        }
+        if let Some(pointee) = name.strip_suffix('*') {
+            return Ok(Type::Pointer(Box::new(self.resolve(pointee)?)));
+        }
+
+        match name {
+            "int" => Ok(Type::Integer),
+            "bool" => Ok(Type::Boolean),
+            "string" => Ok(Type::String),
+            _ if self.structs.contains_key(name) => Ok(Type::Struct(String::from(name))),
+            _ => Err(CompileError::UnknownType {
+                name: String::from(name),
+            }),
+        }
+    }
+
+    /// Resolves the return type of a function, which may be `void`.
+    pub fn resolve_return_type(&self, name: &str) -> Result<Type> {
+        if name == VOID_TYPE {
+            return Ok(Type::Void);
+        }
+
+        let t = self.resolve(name)?;
+        ensure!(!t.is_struct(), InvalidReturnTypeSnafu { t });
+        Ok(t)
+    }
+
+    pub fn get_struct(&self, name: &str) -> Option<&StructType> {
+        self.structs.get(name)
+    }
+
+    /// Returns the number of bytes taken by a value of a type.
+    pub fn size_of(&self, t: &Type) -> usize {
+        match t {
+            Type::Void => 0,
+            Type::Boolean => 1,
+            Type::Integer | Type::String | Type::Array(_) | Type::Pointer(_) => {
+                mem::size_of::<i32>()
+            }
+            Type::Struct(name) => self.structs.get(name).map(|s| s.size).unwrap_or(0),
+
+            // Functions are not values: only their address could be stored.
+            Type::Function { .. } => mem::size_of::<i32>(),
         }
     }
 }
 
-pub fn typecheck_unary_operator<T: Operator>(op: &T, t: &str) -> Result<()> {
-    ensure!(op.defined_for(t), InvalidOperatorSnafu { t });
+pub fn typecheck_unary_operator<T: Operator>(op: &T, t: &Type) -> Result<()> {
+    ensure!(op.defined_for(t), InvalidOperatorSnafu { t: t.clone() });
     Ok(())
 }
 
-pub fn typecheck_binary_operator<T: Operator>(op: &T, t1: &str, t2: &str) -> Result<()> {
-    ensure!(t1 == t2, TypeMismatchSnafu { t1, t2 });
-    ensure!(op.defined_for(t1), InvalidOperatorSnafu { t: t1 });
+pub fn typecheck_binary_operator<T: Operator>(op: &T, t1: &Type, t2: &Type) -> Result<()> {
+    ensure!(
+        t1 == t2,
+        TypeMismatchSnafu {
+            t1: t1.clone(),
+            t2: t2.clone()
+        }
+    );
+    ensure!(op.defined_for(t1), InvalidOperatorSnafu { t: t1.clone() });
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Type, TypeTable};
+    use crate::syntax::struct_decl::struct_declaration;
+
+    fn table(source: &[&str]) -> TypeTable {
+        let declarations: Vec<_> = source
+            .iter()
+            .map(|s| struct_declaration(s).unwrap().1)
+            .collect();
+        TypeTable::from_declarations(&declarations).unwrap()
+    }
+
+    fn size_of(types: &TypeTable, name: &str) -> usize {
+        types.size_of(&types.resolve(name).unwrap())
+    }
+
+    #[test]
+    fn struct_layout() {
+        let types = table(&[
+            "struct Line { Point a; bool visible; Point b; }",
+            "struct Point { int x; int y; }",
+        ]);
+
+        assert_eq!(size_of(&types, "Point"), 8);
+        assert_eq!(size_of(&types, "Line"), 17);
+        assert_eq!(size_of(&types, "Line[]"), 4);
+
+        let line = types.get_struct("Line").unwrap();
+        assert_eq!(line.field("visible").unwrap().offset, 8);
+        assert_eq!(line.field("b").unwrap().offset, 9);
+        assert_eq!(
+            line.field("b").unwrap().field_type,
+            Type::Struct(String::from("Point"))
+        );
+        assert!(line.field("x").is_none());
+    }
+
+    #[test]
+    fn self_reference_through_pointers() {
+        let types = table(&["struct Node { int value; Node[] children; Node* parent; }"]);
+        assert_eq!(size_of(&types, "Node"), 12);
+    }
+
+    #[test]
+    fn unknown_type() {
+        assert!(TypeTable::new().resolve("Point").is_err());
+        assert!(TypeTable::new().resolve("Point[]").is_err());
+        assert!(TypeTable::new().resolve("void").is_err());
+        assert_eq!(size_of(&TypeTable::new(), "bool"), 1);
+    }
+
+    #[test]
+    fn resolve_nested() {
+        let types = TypeTable::new();
+        assert_eq!(
+            types.resolve("int*[]").unwrap(),
+            Type::Array(Box::new(Type::Pointer(Box::new(Type::Integer))))
+        );
+    }
+
+    #[test]
+    fn display() {
+        let types = table(&["struct Point { int x; int y; }"]);
+        for name in ["int", "bool[][]", "string", "Point*", "Point*[]"] {
+            assert_eq!(types.resolve(name).unwrap().to_string(), name);
+        }
+
+        let f = Type::Function {
+            arguments: vec![Type::Integer, Type::Array(Box::new(Type::Boolean))],
+            return_type: Box::new(Type::Integer),
+        };
+        assert_eq!(f.to_string(), "fn(int, bool[]) -> int");
+
+        let f = Type::Function {
+            arguments: Vec::new(),
+            return_type: Box::new(Type::Void),
+        };
+        assert_eq!(f.to_string(), "fn()");
+    }
+}
@@ -0,0 +1,225 @@
+//! Textual dump of the intermediate representation.
+//!
+//! ```text
+//! fn sub(int, int) -> int {
+//! b0:
+//!     %0: int = load [arg0]
+//!     %1: int = load [arg1]
+//!     %2: int = sub %0, %1
+//!     return %2
+//! }
+//! ```
+use std::fmt;
+
+use crate::compiler::Type;
+use crate::ir::*;
+
+impl fmt::Display for Temp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "b{}", self.0)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.base {
+            Base::Slot(slot) => write!(f, "[slot{}", slot)?,
+            Base::Argument(index) => write!(f, "[arg{}", index)?,
+            Base::Heap(temp) => write!(f, "[{}", temp)?,
+        }
+        if self.offset != 0 {
+            write!(f, "+{}", self.offset)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            UnaryOperator::Negate => "neg",
+            UnaryOperator::Not => "not",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BinaryOperator::Add => "add",
+            BinaryOperator::Sub => "sub",
+            BinaryOperator::Mul => "mul",
+            BinaryOperator::Div => "div",
+            BinaryOperator::ShiftLeft => "shl",
+            BinaryOperator::Equal => "eq",
+            BinaryOperator::NotEqual => "neq",
+            BinaryOperator::Greater => "gt",
+            BinaryOperator::Lower => "lt",
+            BinaryOperator::GreaterOrEqual => "gtq",
+            BinaryOperator::LowerOrEqual => "ltq",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Argument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Argument::Value(temp) => write!(f, "{}", temp),
+            Argument::Memory { address, size } => write!(f, "{}:{}", address, size),
+        }
+    }
+}
+
+impl fmt::Display for Terminator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Terminator::Jump(target) => write!(f, "jump {}", target),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => write!(f, "branch {}, {}, {}", condition, then, otherwise),
+            Terminator::Return(Some(value)) => write!(f, "return {}", value),
+            Terminator::Return(None) => write!(f, "return"),
+            Terminator::TailCall {
+                function,
+                arguments,
+            } => {
+                let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+                write!(f, "tail call {}({})", function, arguments.join(", "))
+            }
+            Terminator::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+/// Writes the temporary an instruction assigns, along with its type.
+fn write_dest(f: &mut fmt::Formatter<'_>, function: &Function, dest: Temp) -> fmt::Result {
+    write!(f, "{}: {} = ", dest, function.temp_type(dest))
+}
+
+fn write_instruction(
+    f: &mut fmt::Formatter<'_>,
+    function: &Function,
+    instruction: &Instruction,
+) -> fmt::Result {
+    match instruction {
+        Instruction::Const { dest, value } => {
+            write_dest(f, function, *dest)?;
+            write!(f, "const {}", value)
+        }
+        Instruction::String { dest, index } => {
+            write_dest(f, function, *dest)?;
+            write!(f, "string s{}", index)
+        }
+        Instruction::Copy { dest, source } => {
+            write_dest(f, function, *dest)?;
+            write!(f, "{}", source)
+        }
+        Instruction::Unary {
+            dest,
+            operator,
+            operand,
+        } => {
+            write_dest(f, function, *dest)?;
+            write!(f, "{} {}", operator, operand)
+        }
+        Instruction::Binary {
+            dest,
+            operator,
+            left,
+            right,
+        } => {
+            write_dest(f, function, *dest)?;
+            write!(f, "{} {}, {}", operator, left, right)
+        }
+        Instruction::Load { dest, address } => {
+            write_dest(f, function, *dest)?;
+            write!(f, "load {}", address)
+        }
+        Instruction::Store { address, value } => write!(f, "store {}, {}", address, value),
+        Instruction::CopyMemory { dest, source, size } => {
+            write!(f, "copy {}, {}, {}", dest, source, size)
+        }
+        Instruction::Call {
+            dest,
+            function: name,
+            arguments,
+        } => {
+            if let Some(dest) = dest {
+                write_dest(f, function, *dest)?;
+            }
+            let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+            write!(f, "call {}({})", name, arguments.join(", "))
+        }
+        Instruction::Syscall {
+            dest,
+            call,
+            argument,
+        } => {
+            if let Some(dest) = dest {
+                write_dest(f, function, *dest)?;
+            }
+            write!(f, "syscall {:?}", call)?;
+            if let Some(argument) = argument {
+                write!(f, " {}", argument)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parameters: Vec<String> = self
+            .parameters
+            .iter()
+            .map(|p| p.param_type.to_string())
+            .collect();
+        write!(f, "fn {}({})", self.name, parameters.join(", "))?;
+        if self.return_type != Type::Void {
+            write!(f, " -> {}", self.return_type)?;
+        }
+        writeln!(f, " {{")?;
+
+        for (slot, size) in self.slots.iter().enumerate() {
+            writeln!(f, "    slot{}: {} bytes", slot, size)?;
+        }
+
+        for (id, block) in self.blocks.iter().enumerate() {
+            writeln!(f, "{}:", BlockId(id))?;
+            for instruction in block.instructions.iter() {
+                write!(f, "    ")?;
+                write_instruction(f, self, instruction)?;
+                writeln!(f)?;
+            }
+            writeln!(f, "    {}", block.terminator)?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, s) in self.strings.iter().enumerate() {
+            writeln!(f, "s{} = \"{}\"", index, s)?;
+        }
+
+        for (i, function) in self.functions.iter().enumerate() {
+            if i > 0 || !self.strings.is_empty() {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}", function)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+//! Inlining of calls to small functions.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem;
+
+use crate::compiler::CallGraph;
+use crate::ir::*;
+
+/// Calls inlined into each function, and functions removed because they are never called.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InlineReport {
+    /// Number of calls inlined, by caller and callee.
+    pub inlined: BTreeMap<(String, String), usize>,
+
+    /// Functions removed because they cannot be reached from `main`.
+    pub removed: Vec<String>,
+}
+
+/// Formats the report with a line per inlined callee and per removed function.
+impl fmt::Display for InlineReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((caller, callee), count) in self.inlined.iter() {
+            let calls = if *count == 1 { "call" } else { "calls" };
+            writeln!(f, "inlined {} {} to {} in {}", count, calls, callee, caller)?;
+        }
+        for name in self.removed.iter() {
+            writeln!(f, "removed {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the size of a function, as its number of instructions and terminators.
+pub fn size(function: &Function) -> usize {
+    function
+        .blocks
+        .iter()
+        .map(|block| block.instructions.len() + 1)
+        .sum()
+}
+
+/// Returns whether the scalar parameters of a function are only loaded, so that loading them can
+/// be replaced by the arguments of a call.
+fn loads_parameters(function: &Function) -> bool {
+    let scalar = |base: Base| match base {
+        Base::Argument(index) => !function.parameters[index].param_type.is_struct(),
+        Base::Slot(_) | Base::Heap(_) => false,
+    };
+
+    let passes_memory = |arguments: &[Argument]| {
+        arguments.iter().all(|argument| match argument {
+            Argument::Memory { address, .. } => !scalar(address.base),
+            Argument::Value(_) => true,
+        })
+    };
+
+    function.blocks.iter().all(|block| {
+        let tail_call = match &block.terminator {
+            Terminator::TailCall { arguments, .. } => passes_memory(arguments),
+            _ => true,
+        };
+        tail_call
+            && block
+                .instructions
+                .iter()
+                .all(|instruction| match instruction {
+                    Instruction::Load { address, .. } => {
+                        !scalar(address.base) || address.offset == 0
+                    }
+                    Instruction::Store { address, .. } => !scalar(address.base),
+                    Instruction::CopyMemory { dest, source, .. } => {
+                        !scalar(dest.base) && !scalar(source.base)
+                    }
+                    Instruction::Call { arguments, .. } => passes_memory(arguments),
+                    _ => true,
+                })
+    })
+}
+
+/// Renames the temporaries, slots, parameters and blocks of a function being inlined into
+/// another one.
+struct Renaming {
+    temps: usize,
+    slots: usize,
+    blocks: usize,
+
+    /// Values of the scalar parameters.
+    values: Vec<Option<Temp>>,
+
+    /// Slots holding copies of the struct parameters.
+    copies: Vec<Option<usize>>,
+}
+
+impl Renaming {
+    fn temp(&self, temp: Temp) -> Temp {
+        Temp(temp.0 + self.temps)
+    }
+
+    fn block(&self, block: BlockId) -> BlockId {
+        BlockId(block.0 + self.blocks)
+    }
+
+    fn address(&self, address: Address) -> Address {
+        let base = match address.base {
+            Base::Slot(slot) => Base::Slot(slot + self.slots),
+            Base::Argument(index) => {
+                Base::Slot(self.copies[index].expect("scalar parameter used as memory"))
+            }
+            Base::Heap(temp) => Base::Heap(self.temp(temp)),
+        };
+        Address {
+            base,
+            offset: address.offset,
+        }
+    }
+
+    fn arguments(&self, arguments: Vec<Argument>) -> Vec<Argument> {
+        arguments
+            .into_iter()
+            .map(|argument| match argument {
+                Argument::Value(temp) => Argument::Value(self.temp(temp)),
+                Argument::Memory { address, size } => Argument::Memory {
+                    address: self.address(address),
+                    size,
+                },
+            })
+            .collect()
+    }
+
+    fn instruction(&self, instruction: &Instruction) -> Instruction {
+        match instruction.clone() {
+            Instruction::Const { dest, value } => Instruction::Const {
+                dest: self.temp(dest),
+                value,
+            },
+            Instruction::String { dest, index } => Instruction::String {
+                dest: self.temp(dest),
+                index,
+            },
+            Instruction::Copy { dest, source } => Instruction::Copy {
+                dest: self.temp(dest),
+                source: self.temp(source),
+            },
+            Instruction::Unary {
+                dest,
+                operator,
+                operand,
+            } => Instruction::Unary {
+                dest: self.temp(dest),
+                operator,
+                operand: self.temp(operand),
+            },
+            Instruction::Binary {
+                dest,
+                operator,
+                left,
+                right,
+            } => Instruction::Binary {
+                dest: self.temp(dest),
+                operator,
+                left: self.temp(left),
+                right: self.temp(right),
+            },
+            Instruction::Load { dest, address } => match address.base {
+                Base::Argument(index) if self.values[index].is_some() => Instruction::Copy {
+                    dest: self.temp(dest),
+                    source: self.values[index].unwrap(),
+                },
+                _ => Instruction::Load {
+                    dest: self.temp(dest),
+                    address: self.address(address),
+                },
+            },
+            Instruction::Store { address, value } => Instruction::Store {
+                address: self.address(address),
+                value: self.temp(value),
+            },
+            Instruction::CopyMemory { dest, source, size } => Instruction::CopyMemory {
+                dest: self.address(dest),
+                source: self.address(source),
+                size,
+            },
+            Instruction::Call {
+                dest,
+                function,
+                arguments,
+            } => Instruction::Call {
+                dest: dest.map(|dest| self.temp(dest)),
+                function,
+                arguments: self.arguments(arguments),
+            },
+            Instruction::Syscall {
+                dest,
+                call,
+                argument,
+            } => Instruction::Syscall {
+                dest: dest.map(|dest| self.temp(dest)),
+                call,
+                argument: argument.map(|argument| self.temp(argument)),
+            },
+        }
+    }
+}
+
+/// Replaces a call of a function by its body. The instructions following the call are moved to
+/// a new block, which the inlined returns jump to.
+fn inline_call(function: &mut Function, block: BlockId, index: usize, callee: &Function) {
+    let rest = function.block_mut(block).instructions.split_off(index + 1);
+    let (dest, arguments) = match function.block_mut(block).instructions.pop() {
+        Some(Instruction::Call {
+            dest, arguments, ..
+        }) => (dest, arguments),
+        _ => panic!("inlined instruction is not a call"),
+    };
+
+    // Structs are passed by value, so the callee works on copies.
+    let mut values = Vec::with_capacity(arguments.len());
+    let mut copies = Vec::with_capacity(arguments.len());
+    for argument in arguments.into_iter() {
+        match argument {
+            Argument::Value(temp) => {
+                values.push(Some(temp));
+                copies.push(None);
+            }
+            Argument::Memory { address, size } => {
+                let slot = function.new_slot(size);
+                function
+                    .block_mut(block)
+                    .instructions
+                    .push(Instruction::CopyMemory {
+                        dest: Address::new(Base::Slot(slot)),
+                        source: address,
+                        size,
+                    });
+                values.push(None);
+                copies.push(Some(slot));
+            }
+        }
+    }
+
+    let renaming = Renaming {
+        temps: function.temps.len(),
+        slots: function.slots.len(),
+        blocks: function.blocks.len(),
+        values,
+        copies,
+    };
+    function.temps.extend(callee.temps.iter().cloned());
+    function.slots.extend(callee.slots.iter().copied());
+
+    let continuation = BlockId(renaming.blocks + callee.blocks.len());
+    for callee_block in callee.blocks.iter() {
+        let mut instructions: Vec<Instruction> = callee_block
+            .instructions
+            .iter()
+            .map(|instruction| renaming.instruction(instruction))
+            .collect();
+
+        let terminator = match callee_block.terminator.clone() {
+            Terminator::Jump(target) => Terminator::Jump(renaming.block(target)),
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => Terminator::Branch {
+                condition: renaming.temp(condition),
+                then: renaming.block(then),
+                otherwise: renaming.block(otherwise),
+            },
+            Terminator::Return(value) => {
+                if let (Some(dest), Some(value)) = (dest, value) {
+                    instructions.push(Instruction::Copy {
+                        dest,
+                        source: renaming.temp(value),
+                    });
+                }
+                Terminator::Jump(continuation)
+            }
+            // The inlined function has no frame of its own to reuse.
+            Terminator::TailCall {
+                function,
+                arguments,
+            } => {
+                instructions.push(Instruction::Call {
+                    dest,
+                    function,
+                    arguments: renaming.arguments(arguments),
+                });
+                Terminator::Jump(continuation)
+            }
+            Terminator::Unreachable => Terminator::Unreachable,
+        };
+
+        function.blocks.push(BasicBlock {
+            instructions,
+            terminator,
+        });
+    }
+
+    let terminator = mem::replace(
+        &mut function.block_mut(block).terminator,
+        Terminator::Jump(renaming.block(BlockId(0))),
+    );
+    function.blocks.push(BasicBlock {
+        instructions: rest,
+        terminator,
+    });
+}
+
+/// Returns the position of the first call of a function that can be inlined in a block.
+fn find_call(
+    function: &Function,
+    block: BlockId,
+    inlinable: &BTreeMap<String, Function>,
+) -> Option<(usize, String)> {
+    function
+        .block(block)
+        .instructions
+        .iter()
+        .enumerate()
+        .find_map(|(index, instruction)| match instruction {
+            Instruction::Call { function: name, .. }
+                if *name != function.name && inlinable.contains_key(name) =>
+            {
+                Some((index, name.clone()))
+            }
+            _ => None,
+        })
+}
+
+/// Inlines the calls of functions whose size is at most `threshold`, unless they are recursive,
+/// then removes the functions that are not called anymore.
+///
+/// Functions are processed after the functions they call, so that the calls inlined into a
+/// function are inlined along with it.
+pub fn inline(program: &mut Program, calls: &CallGraph, threshold: usize) -> InlineReport {
+    let mut report = InlineReport::default();
+
+    // Functions that can be inlined so far, by name.
+    let mut inlinable = BTreeMap::new();
+    for name in calls.postorder("main") {
+        let index = match program.functions.iter().position(|f| f.name == name) {
+            Some(index) => index,
+            None => continue,
+        };
+        let function = &mut program.functions[index];
+
+        // Blocks added by inlining are scanned as well, and only hold calls that cannot be
+        // inlined anymore.
+        let mut block = BlockId(0);
+        let mut inlined = false;
+        while block.0 < function.blocks.len() {
+            match find_call(function, block, &inlinable) {
+                Some((index, callee)) => {
+                    inline_call(function, block, index, &inlinable[&callee]);
+                    *report
+                        .inlined
+                        .entry((function.name.clone(), callee))
+                        .or_default() += 1;
+                    inlined = true;
+                }
+                None => block.0 += 1,
+            }
+        }
+
+        if inlined {
+            function.thread_jumps();
+            function.sort_blocks();
+            function.merge_blocks();
+            function.sort_blocks();
+        }
+
+        if name != "main"
+            && !calls.is_recursive(&name)
+            && size(function) <= threshold
+            && loads_parameters(function)
+        {
+            inlinable.insert(name, function.clone());
+        }
+    }
+
+    let reachable = CallGraph::from_ir(program).reachable("main");
+    program.functions.retain(|function| {
+        let keep = reachable.contains(&function.name);
+        if !keep {
+            report.removed.push(function.name.clone());
+        }
+        keep
+    });
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::size;
+    use crate::{compile_ir_with, ir::Program, Options};
+
+    fn compiled(source: &str, inline_threshold: usize) -> Program {
+        compile_ir_with(
+            source,
+            &Options {
+                opt_level: 1,
+                inline_threshold,
+                ..Options::default()
+            },
+        )
+        .unwrap()
+    }
+
+    fn names(program: &Program) -> Vec<&str> {
+        program.functions.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    #[test]
+    fn inline_small_functions() {
+        let source = "fn main() {\n    println(twice(add(1, 2)));\n}\n\
+                      fn add(int a, int b) -> int {\n    return a + b;\n}\n\
+                      fn twice(int x) -> int {\n    return add(x, x);\n}";
+
+        let program = compiled(source, 20);
+        assert_eq!(names(&program), vec!["main"]);
+        // The calls are inlined, then folded.
+        let ir = program.to_string();
+        assert!(ir.contains("const 6"), "{}", ir);
+        assert!(!ir.contains("add") && !ir.contains("twice"), "{}", ir);
+
+        let program = compiled(source, 0);
+        assert_eq!(names(&program), vec!["add", "main", "twice"]);
+    }
+
+    #[test]
+    fn threshold() {
+        let source = "fn main() {\n    println(small(1) + big(2));\n}\n\
+                      fn small(int x) -> int {\n    return x + 1;\n}\n\
+                      fn big(int x) -> int {\n    int y = x * x;\n    if (y > 10) {\n        y = y - 10;\n    }\n    return y * x + 3;\n}";
+
+        let full = compiled(source, 0);
+        let big = &full.functions[0];
+        assert_eq!(big.name, "big");
+        let small = &full.functions[2];
+        assert_eq!(small.name, "small");
+        assert!(size(small) < size(big));
+
+        let program = compiled(source, size(small));
+        assert_eq!(names(&program), vec!["big", "main"]);
+        let program = compiled(source, size(big));
+        assert_eq!(names(&program), vec!["main"]);
+    }
+
+    #[test]
+    fn keep_recursive_functions() {
+        let source = "fn main() {\n    println(fact(5));\n    println(even(3));\n}\n\
+                      fn fact(int n) -> int {\n    if (n < 2) {\n        return 1;\n    }\n    return n * fact(n - 1);\n}\n\
+                      fn even(int n) -> bool {\n    if (n == 0) {\n        return true;\n    }\n    return odd(n - 1);\n}\n\
+                      fn odd(int n) -> bool {\n    if (n == 0) {\n        return false;\n    }\n    return even(n - 1);\n}";
+
+        let program = compiled(source, 100);
+        assert_eq!(names(&program), vec!["even", "fact", "main", "odd"]);
+    }
+
+    #[test]
+    fn remove_unreachable_functions() {
+        let source = "fn main() {\n    used();\n}\n\
+                      fn used() {\n    println(1);\n}\n\
+                      fn unused() {\n    unused();\n    println(2);\n}";
+
+        let program = compiled(source, 0);
+        assert_eq!(names(&program), vec!["main", "used"]);
+    }
+}
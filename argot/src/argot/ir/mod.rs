@@ -0,0 +1,590 @@
+//! Intermediate representation of Argot programs, between the syntax tree and assembly.
+//!
+//! Functions are made of basic blocks of three-address instructions operating on typed
+//! temporaries. Temporaries can be assigned more than once: a variable is lowered to a single
+//! temporary, assigned by each assignment to the variable.
+//!
+//! Temporaries only hold values that fit in a register. Structs are stored in the slots of the
+//! stack frame of a function, and copied between memory locations.
+mod display;
+mod inline;
+mod optimize;
+mod tail_call;
+
+use std::collections::HashSet;
+use std::mem;
+
+use instructor::SysCall;
+
+use crate::compiler::Type;
+
+pub use inline::{inline, InlineReport};
+pub use optimize::optimize;
+pub use tail_call::mark_tail_calls;
+
+/// Temporary holding a value that fits in a register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Temp(pub usize);
+
+/// Index of a basic block in its function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub usize);
+
+/// Memory a value is loaded from or stored to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base {
+    /// Slot of the stack frame of the function.
+    Slot(usize),
+
+    /// Argument of the function, pushed on the stack by the caller.
+    Argument(usize),
+
+    /// Heap memory, at the address held by a temporary.
+    Heap(Temp),
+}
+
+/// Address of a value, as an offset from the start of some memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Address {
+    pub base: Base,
+    pub offset: i32,
+}
+
+impl Address {
+    pub fn new(base: Base) -> Address {
+        Address { base, offset: 0 }
+    }
+
+    /// Returns the address `delta` bytes after this one.
+    pub fn offset(self, delta: i32) -> Address {
+        Address {
+            base: self.base,
+            offset: self.offset + delta,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Negate,
+
+    /// Logical negation of a boolean.
+    Not,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+
+    /// Shifts the bits of the left operand to the left.
+    ShiftLeft,
+
+    Equal,
+    NotEqual,
+    Greater,
+    Lower,
+    GreaterOrEqual,
+    LowerOrEqual,
+}
+
+impl BinaryOperator {
+    /// Returns whether the operator compares its operands, producing a boolean.
+    pub fn is_comparison(&self) -> bool {
+        !matches!(
+            self,
+            BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Mul
+                | BinaryOperator::Div
+                | BinaryOperator::ShiftLeft
+        )
+    }
+}
+
+impl Base {
+    /// Returns the temporary holding the address of the memory, if any.
+    pub fn temp(&self) -> Option<Temp> {
+        match self {
+            Base::Heap(temp) => Some(*temp),
+            Base::Slot(_) | Base::Argument(_) => None,
+        }
+    }
+}
+
+/// Returns the temporaries read by passing arguments to a function.
+fn argument_uses(arguments: &[Argument]) -> Vec<Temp> {
+    arguments
+        .iter()
+        .filter_map(|argument| match argument {
+            Argument::Value(temp) => Some(*temp),
+            Argument::Memory { address, .. } => address.base.temp(),
+        })
+        .collect()
+}
+
+/// Value passed to a function.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Argument {
+    Value(Temp),
+
+    /// Struct, copied from memory to the stack.
+    Memory {
+        address: Address,
+        size: usize,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum Instruction {
+    Const {
+        dest: Temp,
+        value: i32,
+    },
+
+    /// Loads the address of a string of the program.
+    String {
+        dest: Temp,
+        index: usize,
+    },
+
+    Copy {
+        dest: Temp,
+        source: Temp,
+    },
+
+    Unary {
+        dest: Temp,
+        operator: UnaryOperator,
+        operand: Temp,
+    },
+
+    Binary {
+        dest: Temp,
+        operator: BinaryOperator,
+        left: Temp,
+        right: Temp,
+    },
+
+    /// Loads a value from memory. The number of bytes read depends on the type of `dest`.
+    Load {
+        dest: Temp,
+        address: Address,
+    },
+
+    /// Stores a value to memory. The number of bytes written depends on the type of `value`.
+    Store {
+        address: Address,
+        value: Temp,
+    },
+
+    /// Copies `size` bytes of memory, such as a struct.
+    CopyMemory {
+        dest: Address,
+        source: Address,
+        size: usize,
+    },
+
+    Call {
+        dest: Option<Temp>,
+        function: String,
+        arguments: Vec<Argument>,
+    },
+
+    Syscall {
+        dest: Option<Temp>,
+        call: SysCall,
+        argument: Option<Temp>,
+    },
+}
+
+impl Instruction {
+    /// Returns the temporary assigned by the instruction, if any.
+    pub fn dest(&self) -> Option<Temp> {
+        match self {
+            Instruction::Const { dest, .. }
+            | Instruction::String { dest, .. }
+            | Instruction::Copy { dest, .. }
+            | Instruction::Unary { dest, .. }
+            | Instruction::Binary { dest, .. }
+            | Instruction::Load { dest, .. } => Some(*dest),
+            Instruction::Call { dest, .. } | Instruction::Syscall { dest, .. } => *dest,
+            Instruction::Store { .. } | Instruction::CopyMemory { .. } => None,
+        }
+    }
+
+    /// Returns the temporaries read by the instruction.
+    pub fn uses(&self) -> Vec<Temp> {
+        match self {
+            Instruction::Const { .. } | Instruction::String { .. } => Vec::new(),
+            Instruction::Copy { source, .. } => vec![*source],
+            Instruction::Unary { operand, .. } => vec![*operand],
+            Instruction::Binary { left, right, .. } => vec![*left, *right],
+            Instruction::Load { address, .. } => address.base.temp().into_iter().collect(),
+            Instruction::Store { address, value } => {
+                address.base.temp().into_iter().chain([*value]).collect()
+            }
+            Instruction::CopyMemory { dest, source, .. } => dest
+                .base
+                .temp()
+                .into_iter()
+                .chain(source.base.temp())
+                .collect(),
+            Instruction::Call { arguments, .. } => argument_uses(arguments),
+            Instruction::Syscall { argument, .. } => argument.iter().copied().collect(),
+        }
+    }
+}
+
+/// Instruction ending a basic block.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Terminator {
+    Jump(BlockId),
+
+    /// Jumps to `then` if `condition` is true, and to `otherwise` if it is false.
+    Branch {
+        condition: Temp,
+        then: BlockId,
+        otherwise: BlockId,
+    },
+
+    Return(Option<Temp>),
+
+    /// Returns the result of a call, reusing the stack frame of the function for the called
+    /// function. The arguments of both functions take the same space on the stack.
+    TailCall {
+        function: String,
+        arguments: Vec<Argument>,
+    },
+
+    /// End of a block that never completes, such as one aborting the program.
+    Unreachable,
+}
+
+impl Terminator {
+    /// Returns the blocks execution can continue to.
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![*then, *otherwise],
+            Terminator::Return(_) | Terminator::TailCall { .. } | Terminator::Unreachable => {
+                Vec::new()
+            }
+        }
+    }
+
+    /// Returns the temporaries read by the terminator.
+    pub fn uses(&self) -> Vec<Temp> {
+        match self {
+            Terminator::Branch { condition, .. } => vec![*condition],
+            Terminator::Return(value) => value.iter().copied().collect(),
+            Terminator::TailCall { arguments, .. } => argument_uses(arguments),
+            Terminator::Jump(_) | Terminator::Unreachable => Vec::new(),
+        }
+    }
+
+    fn successors_mut(&mut self) -> Vec<&mut BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![then, otherwise],
+            Terminator::Return(_) | Terminator::TailCall { .. } | Terminator::Unreachable => {
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BasicBlock {
+    pub instructions: Vec<Instruction>,
+    pub terminator: Terminator,
+}
+
+/// Parameter of a function, passed on the stack.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    pub param_type: Type,
+    pub size: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Type,
+
+    /// Type of each temporary, indexed by temporary.
+    pub temps: Vec<Type>,
+
+    /// Size of each slot of the stack frame, in bytes.
+    pub slots: Vec<usize>,
+
+    /// Blocks of the function. The first block is its entry point.
+    pub blocks: Vec<BasicBlock>,
+}
+
+impl Function {
+    pub fn new(name: &str, parameters: Vec<Parameter>, return_type: Type) -> Function {
+        Function {
+            name: String::from(name),
+            parameters,
+            return_type,
+            temps: Vec::new(),
+            slots: Vec::new(),
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn new_temp(&mut self, t: Type) -> Temp {
+        self.temps.push(t);
+        Temp(self.temps.len() - 1)
+    }
+
+    pub fn new_slot(&mut self, size: usize) -> usize {
+        self.slots.push(size);
+        self.slots.len() - 1
+    }
+
+    /// Adds an empty block, which is unreachable until it gets another terminator.
+    pub fn new_block(&mut self) -> BlockId {
+        self.blocks.push(BasicBlock {
+            instructions: Vec::new(),
+            terminator: Terminator::Unreachable,
+        });
+        BlockId(self.blocks.len() - 1)
+    }
+
+    pub fn temp_type(&self, temp: Temp) -> &Type {
+        &self.temps[temp.0]
+    }
+
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id.0]
+    }
+
+    pub fn block_mut(&mut self, id: BlockId) -> &mut BasicBlock {
+        &mut self.blocks[id.0]
+    }
+
+    /// Returns the temporaries live at the start and at the end of each block.
+    pub fn liveness(&self) -> (Vec<HashSet<Temp>>, Vec<HashSet<Temp>>) {
+        // Temporaries read by each block before being assigned, and assigned by each block.
+        let mut used = Vec::with_capacity(self.blocks.len());
+        let mut assigned = Vec::with_capacity(self.blocks.len());
+        for block in self.blocks.iter() {
+            let mut block_used = HashSet::new();
+            let mut block_assigned = HashSet::new();
+            for instruction in block.instructions.iter() {
+                for temp in instruction.uses() {
+                    if !block_assigned.contains(&temp) {
+                        block_used.insert(temp);
+                    }
+                }
+                block_assigned.extend(instruction.dest());
+            }
+            for temp in block.terminator.uses() {
+                if !block_assigned.contains(&temp) {
+                    block_used.insert(temp);
+                }
+            }
+            used.push(block_used);
+            assigned.push(block_assigned);
+        }
+
+        let mut live_in = vec![HashSet::new(); self.blocks.len()];
+        let mut live_out = vec![HashSet::new(); self.blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (id, block) in self.blocks.iter().enumerate().rev() {
+                let out: HashSet<Temp> = block
+                    .terminator
+                    .successors()
+                    .iter()
+                    .flat_map(|successor| live_in[successor.0].iter().copied())
+                    .collect();
+                let mut live: HashSet<Temp> = out.difference(&assigned[id]).copied().collect();
+                live.extend(used[id].iter().copied());
+
+                if live != live_in[id] || out != live_out[id] {
+                    live_in[id] = live;
+                    live_out[id] = out;
+                    changed = true;
+                }
+            }
+        }
+
+        (live_in, live_out)
+    }
+
+    /// Redirects jumps to empty blocks that only jump to another block, so that they become
+    /// unreachable.
+    pub fn thread_jumps(&mut self) {
+        let forward: Vec<Option<BlockId>> = self
+            .blocks
+            .iter()
+            .map(|block| match block.terminator {
+                Terminator::Jump(target) if block.instructions.is_empty() => Some(target),
+                _ => None,
+            })
+            .collect();
+
+        // Chains of empty blocks are followed to their end, unless they loop.
+        let resolve = |mut id: BlockId| {
+            let mut steps = 0;
+            while let Some(target) = forward[id.0] {
+                if steps == forward.len() {
+                    break;
+                }
+                id = target;
+                steps += 1;
+            }
+            id
+        };
+
+        for block in self.blocks.iter_mut() {
+            for successor in block.terminator.successors_mut() {
+                *successor = resolve(*successor);
+            }
+        }
+    }
+
+    /// Appends each block to its predecessor, when the predecessor is its only one and jumps to
+    /// it. Merged blocks become unreachable.
+    pub fn merge_blocks(&mut self) {
+        let mut predecessors = vec![0; self.blocks.len()];
+        for block in self.blocks.iter() {
+            for successor in block.terminator.successors() {
+                predecessors[successor.0] += 1;
+            }
+        }
+
+        for id in 0..self.blocks.len() {
+            while let Terminator::Jump(target) = self.blocks[id].terminator {
+                if target.0 == id || target.0 == 0 || predecessors[target.0] != 1 {
+                    break;
+                }
+
+                let merged = mem::replace(
+                    self.block_mut(target),
+                    BasicBlock {
+                        instructions: Vec::new(),
+                        terminator: Terminator::Unreachable,
+                    },
+                );
+                let block = &mut self.blocks[id];
+                block.instructions.extend(merged.instructions);
+                block.terminator = merged.terminator;
+            }
+        }
+    }
+
+    /// Removes the blocks that cannot be reached from the entry point, and sorts the others in
+    /// reverse postorder.
+    ///
+    /// Blocks then come before their successors, except for loops. The `then` successor of a
+    /// branch is placed right after it when possible.
+    pub fn sort_blocks(&mut self) {
+        if self.blocks.is_empty() {
+            return;
+        }
+
+        let mut visited = vec![false; self.blocks.len()];
+        let mut postorder = Vec::with_capacity(self.blocks.len());
+
+        // Successors are visited in reverse order, so that the first one ends up first.
+        let mut stack = vec![(BlockId(0), self.block(BlockId(0)).terminator.successors())];
+        visited[0] = true;
+        while let Some((block, successors)) = stack.last_mut() {
+            match successors.pop() {
+                Some(successor) => {
+                    if !visited[successor.0] {
+                        visited[successor.0] = true;
+                        let next = self.block(successor).terminator.successors();
+                        stack.push((successor, next));
+                    }
+                }
+                None => {
+                    postorder.push(*block);
+                    stack.pop();
+                }
+            }
+        }
+
+        let mut new_ids = vec![None; self.blocks.len()];
+        for (new_id, old_id) in postorder.iter().rev().enumerate() {
+            new_ids[old_id.0] = Some(BlockId(new_id));
+        }
+
+        let mut blocks: Vec<Option<BasicBlock>> = self.blocks.drain(..).map(Some).collect();
+        for old_id in postorder.into_iter().rev() {
+            let mut block = blocks[old_id.0].take().unwrap();
+            for successor in block.terminator.successors_mut() {
+                // Successors of a reachable block are reachable.
+                *successor = new_ids[successor.0].unwrap();
+            }
+            self.blocks.push(block);
+        }
+    }
+}
+
+/// Splits `size` bytes of memory into the words and bytes they are copied as, returning their
+/// offsets and sizes.
+pub fn chunks(size: usize) -> impl Iterator<Item = (i32, usize)> {
+    let words = (0..size / 4).map(|word| (4 * word as i32, 4));
+    let bytes = (size - size % 4..size).map(|byte| (byte as i32, 1));
+    words.chain(bytes)
+}
+
+/// Program lowered to the intermediate representation.
+#[derive(Clone, Debug, Default)]
+pub struct Program {
+    pub functions: Vec<Function>,
+
+    /// Strings used by the program, stored in its read-only block.
+    pub strings: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockId, Function, Temp, Terminator};
+    use crate::compiler::Type;
+
+    #[test]
+    fn sort_blocks() {
+        let mut f = Function::new("f", Vec::new(), Type::Void);
+        let entry = f.new_block();
+        let end = f.new_block();
+        let dead = f.new_block();
+        let then = f.new_block();
+        let condition = f.new_temp(Type::Boolean);
+
+        f.block_mut(entry).terminator = Terminator::Branch {
+            condition,
+            then,
+            otherwise: end,
+        };
+        f.block_mut(then).terminator = Terminator::Jump(end);
+        f.block_mut(end).terminator = Terminator::Return(None);
+        f.block_mut(dead).terminator = Terminator::Jump(entry);
+
+        f.sort_blocks();
+        assert_eq!(f.blocks.len(), 3);
+        assert_eq!(
+            f.blocks[0].terminator,
+            Terminator::Branch {
+                condition: Temp(0),
+                then: BlockId(1),
+                otherwise: BlockId(2)
+            }
+        );
+        assert_eq!(f.blocks[1].terminator, Terminator::Jump(BlockId(2)));
+        assert_eq!(f.blocks[2].terminator, Terminator::Return(None));
+    }
+}
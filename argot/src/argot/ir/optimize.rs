@@ -0,0 +1,525 @@
+//! Optimizations of the intermediate representation.
+//!
+//! | Level | Optimizations                                                                   |
+//! |-------|---------------------------------------------------------------------------------|
+//! | 0     | None                                                                            |
+//! | 1     | Constant folding, algebraic and boolean simplification, dead branch elimination |
+//! | 2     | Level 1, and strength reduction of multiplications                              |
+use std::collections::HashMap;
+
+use crate::compiler::Type;
+use crate::ir::*;
+
+/// Value of a temporary, as far as the constant propagation knows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Value {
+    Constant(i32),
+
+    /// Value that is not known at compile time.
+    Varying,
+}
+
+/// Values of the temporaries at some point of a function. Temporaries that are not assigned yet
+/// are missing.
+type State = HashMap<Temp, Value>;
+
+/// Merges the values of the temporaries coming from another block into a state, returning
+/// whether the state changed.
+fn merge(state: &mut State, incoming: &State) -> bool {
+    let mut changed = false;
+    for (temp, value) in incoming.iter() {
+        match state.get(temp) {
+            None => {
+                state.insert(*temp, *value);
+                changed = true;
+            }
+            Some(current) if current != value && *current != Value::Varying => {
+                state.insert(*temp, Value::Varying);
+                changed = true;
+            }
+            Some(_) => {}
+        }
+    }
+    changed
+}
+
+fn fold_unary(operator: UnaryOperator, operand: i32) -> i32 {
+    match operator {
+        UnaryOperator::Negate => operand.wrapping_neg(),
+        UnaryOperator::Not => (operand == 0) as i32,
+    }
+}
+
+/// Computes a binary operation the way the VM does. Divisions by zero are left to the VM, which
+/// traps on them.
+fn fold_binary(operator: BinaryOperator, left: i32, right: i32) -> Option<i32> {
+    let value = match operator {
+        BinaryOperator::Add => left.wrapping_add(right),
+        BinaryOperator::Sub => left.wrapping_sub(right),
+        BinaryOperator::Mul => left.wrapping_mul(right),
+        BinaryOperator::Div if right == 0 => return None,
+        BinaryOperator::Div => left.wrapping_div(right),
+        BinaryOperator::ShiftLeft => left.checked_shl(right as u32).unwrap_or(0),
+        BinaryOperator::Equal => (left == right) as i32,
+        BinaryOperator::NotEqual => (left != right) as i32,
+        BinaryOperator::Greater => (left > right) as i32,
+        BinaryOperator::Lower => (left < right) as i32,
+        BinaryOperator::GreaterOrEqual => (left >= right) as i32,
+        BinaryOperator::LowerOrEqual => (left <= right) as i32,
+    };
+    Some(value)
+}
+
+/// Returns the value an instruction assigns, or `None` if it reads a temporary that is not
+/// assigned yet.
+fn evaluate(instruction: &Instruction, state: &State) -> Option<Value> {
+    let value = |temp: &Temp| state.get(temp).copied();
+
+    match instruction {
+        Instruction::Const { value, .. } => Some(Value::Constant(*value)),
+        Instruction::Copy { source, .. } => value(source),
+        Instruction::Unary {
+            operator, operand, ..
+        } => match value(operand)? {
+            Value::Constant(operand) => Some(Value::Constant(fold_unary(*operator, operand))),
+            Value::Varying => Some(Value::Varying),
+        },
+        Instruction::Binary {
+            operator,
+            left,
+            right,
+            ..
+        } => match (value(left)?, value(right)?) {
+            (Value::Constant(left), Value::Constant(right)) => {
+                Some(fold_binary(*operator, left, right).map_or(Value::Varying, Value::Constant))
+            }
+            (Value::Constant(0), _) | (_, Value::Constant(0))
+                if *operator == BinaryOperator::Mul =>
+            {
+                Some(Value::Constant(0))
+            }
+            _ => Some(Value::Varying),
+        },
+        _ => Some(Value::Varying),
+    }
+}
+
+/// Updates a state with the value assigned by an instruction.
+fn transfer(instruction: &Instruction, state: &mut State) {
+    if let Some(dest) = instruction.dest() {
+        match evaluate(instruction, state) {
+            Some(value) => state.insert(dest, value),
+            None => state.remove(&dest),
+        };
+    }
+}
+
+/// Returns the constant value of a temporary in a state.
+fn constant(state: &State, temp: Temp) -> Option<i32> {
+    match state.get(&temp) {
+        Some(Value::Constant(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Returns the blocks a terminator can continue to, leaving out the branch not taken when the
+/// condition is constant.
+fn taken_successors(terminator: &Terminator, state: &State) -> Vec<BlockId> {
+    match terminator {
+        Terminator::Branch {
+            condition,
+            then,
+            otherwise,
+        } => match constant(state, *condition) {
+            Some(0) => vec![*otherwise],
+            Some(_) => vec![*then],
+            None => vec![*then, *otherwise],
+        },
+        terminator => terminator.successors(),
+    }
+}
+
+/// Computes the values of the temporaries at the start of each block. Blocks that are never
+/// reached have no state.
+fn propagate_constants(function: &Function) -> Vec<Option<State>> {
+    let mut states: Vec<Option<State>> = vec![None; function.blocks.len()];
+    states[0] = Some(State::new());
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (id, block) in function.blocks.iter().enumerate() {
+            let mut state = match &states[id] {
+                Some(state) => state.clone(),
+                None => continue,
+            };
+            for instruction in block.instructions.iter() {
+                transfer(instruction, &mut state);
+            }
+
+            for successor in taken_successors(&block.terminator, &state) {
+                match &mut states[successor.0] {
+                    Some(successor_state) => changed |= merge(successor_state, &state),
+                    None => {
+                        states[successor.0] = Some(state.clone());
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    states
+}
+
+/// Rewrites instructions and terminators using the constants known at each point of a function.
+struct Simplifier<'a> {
+    function: &'a mut Function,
+    strength_reduction: bool,
+}
+
+impl<'a> Simplifier<'a> {
+    /// Simplifies an instruction, appending the result to `out`.
+    fn instruction(&mut self, instruction: Instruction, state: &State, out: &mut Vec<Instruction>) {
+        if let Some(dest) = instruction.dest() {
+            if !matches!(instruction, Instruction::Const { .. }) {
+                if let Some(Value::Constant(value)) = evaluate(&instruction, state) {
+                    out.push(Instruction::Const { dest, value });
+                    return;
+                }
+            }
+        }
+
+        match instruction {
+            Instruction::Binary {
+                dest,
+                operator,
+                left,
+                right,
+            } => out.extend(self.binary(dest, operator, left, right, state)),
+            instruction => out.push(instruction),
+        }
+    }
+
+    /// Simplifies a binary operation with a constant operand.
+    fn binary(
+        &mut self,
+        dest: Temp,
+        operator: BinaryOperator,
+        left: Temp,
+        right: Temp,
+        state: &State,
+    ) -> Vec<Instruction> {
+        let copy = |source| vec![Instruction::Copy { dest, source }];
+        let not = |operand| {
+            vec![Instruction::Unary {
+                dest,
+                operator: UnaryOperator::Not,
+                operand,
+            }]
+        };
+        let left_boolean = *self.function.temp_type(left) == Type::Boolean;
+        let right_boolean = *self.function.temp_type(right) == Type::Boolean;
+
+        let (left_value, right_value) = (constant(state, left), constant(state, right));
+        match (operator, left_value, right_value) {
+            (BinaryOperator::Add, _, Some(0)) | (BinaryOperator::Sub, _, Some(0)) => copy(left),
+            (BinaryOperator::Add, Some(0), _) => copy(right),
+            (BinaryOperator::Mul, _, Some(1)) | (BinaryOperator::Div, _, Some(1)) => copy(left),
+            (BinaryOperator::Mul, Some(1), _) => copy(right),
+
+            // Comparisons of booleans with `true` or `false`.
+            (BinaryOperator::Equal, _, Some(1)) | (BinaryOperator::NotEqual, _, Some(0))
+                if left_boolean =>
+            {
+                copy(left)
+            }
+            (BinaryOperator::Equal, Some(1), _) | (BinaryOperator::NotEqual, Some(0), _)
+                if right_boolean =>
+            {
+                copy(right)
+            }
+            (BinaryOperator::Equal, _, Some(0)) | (BinaryOperator::NotEqual, _, Some(1))
+                if left_boolean =>
+            {
+                not(left)
+            }
+            (BinaryOperator::Equal, Some(0), _) | (BinaryOperator::NotEqual, Some(1), _)
+                if right_boolean =>
+            {
+                not(right)
+            }
+
+            (BinaryOperator::Mul, _, Some(factor)) if self.reduces(factor) => {
+                self.shift(dest, left, factor)
+            }
+            (BinaryOperator::Mul, Some(factor), _) if self.reduces(factor) => {
+                self.shift(dest, right, factor)
+            }
+
+            _ => vec![Instruction::Binary {
+                dest,
+                operator,
+                left,
+                right,
+            }],
+        }
+    }
+
+    /// Returns whether a multiplication by `factor` is replaced by a shift.
+    fn reduces(&self, factor: i32) -> bool {
+        self.strength_reduction && factor > 1 && (factor as u32).is_power_of_two()
+    }
+
+    /// Multiplies a temporary by a power of two by shifting it.
+    fn shift(&mut self, dest: Temp, operand: Temp, factor: i32) -> Vec<Instruction> {
+        let amount = self.function.new_temp(Type::Integer);
+        vec![
+            Instruction::Const {
+                dest: amount,
+                value: factor.trailing_zeros() as i32,
+            },
+            Instruction::Binary {
+                dest,
+                operator: BinaryOperator::ShiftLeft,
+                left: operand,
+                right: amount,
+            },
+        ]
+    }
+
+    /// Simplifies the terminator of a block, given the instructions of the block.
+    fn terminator(
+        &self,
+        terminator: Terminator,
+        instructions: &[Instruction],
+        state: &State,
+    ) -> Terminator {
+        let (condition, then, otherwise) = match terminator {
+            Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            } => (condition, then, otherwise),
+            terminator => return terminator,
+        };
+
+        match constant(state, condition) {
+            Some(0) => return Terminator::Jump(otherwise),
+            Some(_) => return Terminator::Jump(then),
+            None => {}
+        }
+
+        // Branching on a negation is branching the other way on its operand.
+        let negated = instructions
+            .iter()
+            .rposition(|instruction| instruction.dest() == Some(condition))
+            .and_then(|position| match &instructions[position] {
+                Instruction::Unary {
+                    operator: UnaryOperator::Not,
+                    operand,
+                    ..
+                } if instructions[position + 1..]
+                    .iter()
+                    .all(|instruction| instruction.dest() != Some(*operand)) =>
+                {
+                    Some(*operand)
+                }
+                _ => None,
+            });
+        match negated {
+            Some(operand) => Terminator::Branch {
+                condition: operand,
+                then: otherwise,
+                otherwise: then,
+            },
+            None => Terminator::Branch {
+                condition,
+                then,
+                otherwise,
+            },
+        }
+    }
+
+    fn apply(&mut self) {
+        let states = propagate_constants(self.function);
+
+        for (id, state) in states.into_iter().enumerate() {
+            // Unreachable blocks are removed once their predecessors are simplified.
+            let mut state = match state {
+                Some(state) => state,
+                None => continue,
+            };
+
+            let block = BlockId(id);
+            let instructions = std::mem::take(&mut self.function.block_mut(block).instructions);
+            let mut simplified = Vec::with_capacity(instructions.len());
+            for instruction in instructions {
+                // The simplified instruction assigns the same value as the original one.
+                self.instruction(instruction.clone(), &state, &mut simplified);
+                transfer(&instruction, &mut state);
+            }
+
+            let terminator = std::mem::replace(
+                &mut self.function.block_mut(block).terminator,
+                Terminator::Unreachable,
+            );
+            let terminator = self.terminator(terminator, &simplified, &state);
+            let block = self.function.block_mut(block);
+            block.instructions = simplified;
+            block.terminator = terminator;
+        }
+    }
+}
+
+/// Returns whether an instruction only assigns its destination, so that it can be removed when
+/// its result is not used.
+fn is_pure(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Const { .. }
+            | Instruction::String { .. }
+            | Instruction::Copy { .. }
+            | Instruction::Unary { .. }
+    ) || matches!(instruction, Instruction::Binary { operator, .. } if *operator != BinaryOperator::Div)
+}
+
+/// Removes the pure instructions whose result is never used, returning whether any was removed.
+fn eliminate_dead_code(function: &mut Function) -> bool {
+    let (_, live_out) = function.liveness();
+
+    let mut changed = false;
+    for (block, mut live) in function.blocks.iter_mut().zip(live_out) {
+        live.extend(block.terminator.uses());
+
+        let mut kept = Vec::with_capacity(block.instructions.len());
+        for instruction in block.instructions.drain(..).rev() {
+            let dest = instruction.dest();
+            if is_pure(&instruction) && dest.is_some_and(|dest| !live.contains(&dest)) {
+                changed = true;
+                continue;
+            }
+
+            if let Some(dest) = dest {
+                live.remove(&dest);
+            }
+            live.extend(instruction.uses());
+            kept.push(instruction);
+        }
+        kept.reverse();
+        block.instructions = kept;
+    }
+    changed
+}
+
+/// Optimizes a function.
+fn optimize_function(function: &mut Function, level: u8) {
+    let mut simplifier = Simplifier {
+        function,
+        strength_reduction: level >= 2,
+    };
+    simplifier.apply();
+
+    while eliminate_dead_code(function) {}
+    // Unreachable blocks are removed first, so that they do not count as predecessors.
+    function.thread_jumps();
+    function.sort_blocks();
+    function.merge_blocks();
+    function.sort_blocks();
+}
+
+/// Optimizes a program at an optimization level, from 0 for no optimizations to 2.
+pub fn optimize(program: &mut Program, level: u8) {
+    if level == 0 {
+        return;
+    }
+
+    for function in program.functions.iter_mut() {
+        optimize_function(function, level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compile_ir_with, Options};
+
+    /// Optimizes a program made of a function `f` and of a `main` function making a call to
+    /// it. Calls are not inlined.
+    fn optimized(source: &str, call: &str, opt_level: u8) -> String {
+        let source = format!("fn main() {{\n    {};\n}}\n{}", call, source);
+        compile_ir_with(
+            &source,
+            &Options {
+                opt_level,
+                inline_threshold: 0,
+                ..Options::default()
+            },
+        )
+        .unwrap()
+        .to_string()
+    }
+
+    #[test]
+    fn constant_folding() {
+        let ir = optimized(
+            "fn f() {\n    int a = 4 - 3;\n    println(-a * (2 + a));\n}",
+            "f()",
+            1,
+        );
+        assert!(ir.contains("const -3\n    syscall PRINTI"), "{}", ir);
+        assert!(!ir.contains("sub") && !ir.contains("mul") && !ir.contains("neg"));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let ir = optimized("fn f() {\n    println(1 / 0);\n}", "f()", 1);
+        assert!(ir.contains("div"), "{}", ir);
+    }
+
+    #[test]
+    fn algebraic_simplification() {
+        let ir = optimized(
+            "fn f(int x) -> int {\n    return (x + 0) * 1 - 0 + 0 * x;\n}",
+            "f(1)",
+            1,
+        );
+        assert!(!ir.contains("add") && !ir.contains("sub"), "{}", ir);
+        assert!(!ir.contains("mul"), "{}", ir);
+    }
+
+    #[test]
+    fn boolean_simplification() {
+        let ir = optimized(
+            "fn f(bool b) -> bool {\n    if (!b) {\n        return b == false;\n    }\n    return b == true;\n}",
+            "f(true)",
+            1,
+        );
+        assert!(!ir.contains("eq"), "{}", ir);
+        assert!(
+            ir.contains("branch %0, ") && !ir.contains("not %0\n    branch"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn dead_branches() {
+        let ir = optimized(
+            "fn f() {\n    if (1 > 2 || false) {\n        println(1);\n    } else {\n        println(2);\n    }\n    while (false) {\n        println(3);\n    }\n}",
+            "f()",
+            1,
+        );
+        assert!(!ir.contains("branch") && !ir.contains("jump"), "{}", ir);
+        assert!(ir.contains("const 2") && !ir.contains("const 3"), "{}", ir);
+    }
+
+    #[test]
+    fn strength_reduction() {
+        let source = "fn f(int x) -> int {\n    return 8 * x;\n}";
+        assert!(optimized(source, "f(1)", 1).contains("mul"));
+
+        let ir = optimized(source, "f(1)", 2);
+        assert!(ir.contains("const 3") && ir.contains("shl %0, "), "{}", ir);
+        assert!(!ir.contains("mul"));
+    }
+}
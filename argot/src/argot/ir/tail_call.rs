@@ -0,0 +1,133 @@
+//! Detection of tail calls, calls whose result is returned right away.
+use std::collections::HashMap;
+
+use crate::ir::*;
+
+/// Returns the space taken by the arguments of a function on the stack.
+fn arguments_size(function: &Function) -> usize {
+    function.parameters.iter().map(|p| p.size).sum()
+}
+
+/// Returns whether a block returns without doing anything else.
+fn only_returns(block: &BasicBlock) -> bool {
+    block.instructions.is_empty() && block.terminator == Terminator::Return(None)
+}
+
+/// Returns whether the last instruction of a block is a call whose result is returned by the
+/// block.
+fn ends_with_tail_call(function: &Function, block: &BasicBlock) -> bool {
+    let dest = match block.instructions.last() {
+        Some(Instruction::Call { dest, .. }) => *dest,
+        _ => return false,
+    };
+
+    match block.terminator {
+        Terminator::Return(value) => value == dest,
+        Terminator::Jump(target) => dest.is_none() && only_returns(function.block(target)),
+        _ => false,
+    }
+}
+
+/// Turns the calls of a function whose result is returned right away into tail calls.
+fn mark_function(function: &mut Function, arguments_sizes: &HashMap<String, usize>) -> usize {
+    let size = arguments_sizes[&function.name];
+    let mut marked = 0;
+    for id in 0..function.blocks.len() {
+        if !ends_with_tail_call(function, &function.blocks[id]) {
+            continue;
+        }
+
+        let block = &mut function.blocks[id];
+        let (callee, arguments) = match block.instructions.last() {
+            Some(Instruction::Call {
+                function,
+                arguments,
+                ..
+            }) => (function.clone(), arguments.clone()),
+            _ => unreachable!(),
+        };
+
+        // The frame of the function can only be reused by a function taking arguments of the
+        // same size, since the arguments are overwritten in place.
+        if arguments_sizes.get(&callee) != Some(&size) {
+            continue;
+        }
+
+        block.instructions.pop();
+        block.terminator = Terminator::TailCall {
+            function: callee,
+            arguments,
+        };
+        marked += 1;
+    }
+    marked
+}
+
+/// Turns the calls whose result is returned right away into tail calls, which reuse the stack
+/// frame of the calling function. Returns the number of tail calls.
+///
+/// `main` has no caller to return to, so its calls are kept.
+pub fn mark_tail_calls(program: &mut Program) -> usize {
+    let arguments_sizes: HashMap<String, usize> = program
+        .functions
+        .iter()
+        .map(|function| (function.name.clone(), arguments_size(function)))
+        .collect();
+
+    program
+        .functions
+        .iter_mut()
+        .filter(|function| function.name != "main")
+        .map(|function| mark_function(function, &arguments_sizes))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compile_ir_with, Options};
+
+    fn compiled(source: &str) -> String {
+        compile_ir_with(source, &Options::default())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn self_tail_calls() {
+        let ir = compiled(
+            "fn main() {\n    println(count(10, 0));\n    down(3);\n}\n\
+             fn count(int n, int acc) -> int {\n    if (n == 0) {\n        return acc;\n    }\n    return count(n - 1, acc + 1);\n}\n\
+             fn down(int n) {\n    if (n > 0) {\n        println(n);\n        down(n - 1);\n    }\n}",
+        );
+        assert!(ir.contains("    tail call count(%5, %7)\n"));
+        assert!(ir.contains("    tail call down(%5)\n"));
+        assert!(!ir.contains("call count(%5, %7)\n    return"));
+
+        // Calls of main are kept.
+        assert!(ir.contains("%2: int = call count(%0, %1)"));
+        assert!(ir.contains("    call down(%4)\n"));
+    }
+
+    #[test]
+    fn mutual_tail_calls() {
+        let ir = compiled(
+            "fn main() {\n    println(even(4));\n}\n\
+             fn even(int n) -> bool {\n    if (n == 0) {\n        return true;\n    }\n    return odd(n - 1);\n}\n\
+             fn odd(int n) -> bool {\n    if (n == 0) {\n        return false;\n    }\n    return even(n - 1);\n}",
+        );
+        assert!(ir.contains("tail call odd("));
+        assert!(ir.contains("tail call even("));
+    }
+
+    #[test]
+    fn keep_other_calls() {
+        let ir = compiled(
+            "fn main() {\n    println(fact(5));\n    println(f(1));\n}\n\
+             fn fact(int n) -> int {\n    if (n < 2) {\n        return 1;\n    }\n    return n * fact(n - 1);\n}\n\
+             fn f(int x) -> int {\n    return g(x, 2);\n}\n\
+             fn g(int x, int y) -> int {\n    return x + y;\n}",
+        );
+        // The result of `fact` is used, and `g` takes more arguments than `f`.
+        assert!(!ir.contains("tail call"));
+    }
+}
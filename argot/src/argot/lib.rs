@@ -1,5 +1,9 @@
 pub mod compiler;
+pub mod ir;
 pub mod syntax;
 pub mod visitor;
 
-pub use compiler::{compile, compile_asm};
+pub use compiler::{
+    compile, compile_asm, compile_asm_with, compile_asm_with_report, compile_ir, compile_ir_with,
+    compile_ir_with_report, compile_with, Options, Report, DEFAULT_INLINE_THRESHOLD,
+};
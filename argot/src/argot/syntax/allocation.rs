@@ -0,0 +1,83 @@
+use nom::{
+    combinator::{map, opt},
+    sequence::tuple,
+    IResult,
+};
+
+use crate::syntax::{
+    common::{keyword, spanned},
+    expression::Expression,
+    span::{Span, Spanned},
+    trailer::index,
+    var_decl::type_name,
+};
+
+use crate::visitor::{Visitable, Visitor};
+
+/// Allocation on the heap: an array such as `new int[n]`, or a single value such as `new Point`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allocation {
+    /// Type of the allocated value, or of the elements of the allocated array.
+    pub allocated_type: String,
+
+    /// Length of the allocated array, if an array is allocated.
+    pub length: Option<Box<Expression>>,
+    pub span: Span,
+}
+
+impl Spanned for Allocation {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Visitable for Allocation {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V) -> V::Result {
+        visitor.visit_allocation(self)
+    }
+}
+
+pub fn allocation(i: &str) -> IResult<&str, Allocation> {
+    map(
+        spanned(tuple((keyword("new"), type_name, opt(index)))),
+        |((_, allocated_type, length), span)| Allocation {
+            allocated_type: String::from(allocated_type),
+            length: length.map(Box::new),
+            span,
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::allocation;
+    use crate::syntax::expression::expression;
+
+    #[test]
+    fn new_array_length() {
+        let (rest, array) = allocation("new int[n + 1]").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(array.allocated_type, "int");
+        assert_eq!(*array.length.unwrap(), expression("n + 1").unwrap().1);
+    }
+
+    #[test]
+    fn new_nested_array() {
+        let (rest, array) = allocation("new bool[][4]").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(array.allocated_type, "bool[]");
+    }
+
+    #[test]
+    fn new_value() {
+        let (rest, value) = allocation("new Point").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(value.allocated_type, "Point");
+        assert!(value.length.is_none());
+    }
+
+    #[test]
+    fn new_prefix() {
+        assert!(allocation("newint[4]").is_err());
+    }
+}
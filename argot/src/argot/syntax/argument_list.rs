@@ -6,12 +6,23 @@ use nom::{
     IResult,
 };
 
-use crate::syntax::{common::whitespace, var_decl::identifier};
+use crate::syntax::{
+    common::{spanned, whitespace},
+    span::{Span, Spanned},
+    var_decl::{identifier, type_name},
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Argument {
     pub arg_type: String,
     pub name: String,
+    pub span: Span,
+}
+
+impl Spanned for Argument {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -25,16 +36,21 @@ pub fn argument_list(i: &str) -> IResult<&str, ArgumentList> {
             char('('),
             separated_list0(
                 char(','),
-                delimited(whitespace, tuple((identifier, identifier)), whitespace),
+                delimited(
+                    whitespace,
+                    spanned(tuple((type_name, identifier))),
+                    whitespace,
+                ),
             ),
             char(')'),
         ),
         |args| ArgumentList {
             arguments: args
                 .into_iter()
-                .map(|(arg_type, name)| Argument {
+                .map(|((arg_type, name), span)| Argument {
                     arg_type: String::from(arg_type),
                     name: String::from(name),
+                    span,
                 })
                 .collect(),
         },
@@ -43,7 +59,7 @@ pub fn argument_list(i: &str) -> IResult<&str, ArgumentList> {
 
 #[cfg(test)]
 mod tests {
-    use super::{argument_list, Argument, ArgumentList};
+    use super::{argument_list, Argument, ArgumentList, Span};
 
     #[test]
     fn empty_list() {
@@ -66,9 +82,18 @@ mod tests {
             ArgumentList {
                 arguments: vec![Argument {
                     name: String::from("b"),
-                    arg_type: String::from("bool")
+                    arg_type: String::from("bool"),
+                    span: Span::default()
                 }]
             }
         )
     }
+
+    #[test]
+    fn array_arg() {
+        let (rest, arglist) = argument_list("(int[] values, int n)").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(arglist.arguments[0].arg_type, "int[]");
+        assert_eq!(arglist.arguments[1].arg_type, "int");
+    }
 }
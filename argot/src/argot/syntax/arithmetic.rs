@@ -0,0 +1,145 @@
+use nom::{
+    combinator::map,
+    multi::many0,
+    sequence::{delimited, tuple},
+    IResult,
+};
+
+use crate::syntax::{
+    common::{spanned, whitespace},
+    operator::{term_operator, TermOperator},
+    span::{Span, Spanned},
+    term::{term, Term},
+};
+use crate::visitor::{Visitable, Visitor};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArithmeticExpression {
+    pub root_term: Term,
+    pub trail: Vec<(TermOperator, Term)>,
+    pub span: Span,
+}
+
+impl Spanned for ArithmeticExpression {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Visitable for ArithmeticExpression {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V) -> V::Result {
+        visitor.visit_arithmetic_expression(self)
+    }
+}
+
+pub fn arithmetic_expression(i: &str) -> IResult<&str, ArithmeticExpression> {
+    let t = delimited(
+        whitespace,
+        spanned(tuple((term, many0(tuple((term_operator, term)))))),
+        whitespace,
+    );
+    map(t, |((root_trm, lst), span)| ArithmeticExpression {
+        root_term: root_trm,
+        trail: lst,
+        span,
+    })(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::syntax::Span;
+
+    use super::{arithmetic_expression, ArithmeticExpression, Term, TermOperator};
+    use crate::syntax::types::{
+        Atom, AtomicExpression, Expression, Factor, FactorOperator, UnaryOperator,
+    };
+
+    #[test]
+    fn arithm_expression() {
+        let expected_expression = ArithmeticExpression {
+            span: Span::default(),
+            root_term: Term {
+                span: Span::default(),
+                root_factor: Factor::Atomic(AtomicExpression {
+                    span: Span::default(),
+                    atom: Atom::Integer(18),
+                    trailers: Vec::new(),
+                }),
+                trail: Vec::new(),
+            },
+            trail: vec![(
+                TermOperator::Plus,
+                Term {
+                    span: Span::default(),
+                    root_factor: Factor::Atomic(AtomicExpression {
+                        span: Span::default(),
+                        atom: Atom::Integer(15),
+                        trailers: Vec::new(),
+                    }),
+                    trail: vec![(
+                        FactorOperator::Mult,
+                        Factor::Unary(
+                            UnaryOperator::Minus,
+                            Box::new(Factor::Atomic(AtomicExpression {
+                                span: Span::default(),
+                                atom: Atom::Integer(4),
+                                trailers: Vec::new(),
+                            })),
+                        ),
+                    )],
+                },
+            )],
+        };
+
+        let (rest, expr) = arithmetic_expression("18 + 15 * -4").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(expr, expected_expression);
+    }
+
+    #[test]
+    fn nested_expression() {
+        let expected_expression = ArithmeticExpression {
+            span: Span::default(),
+            root_term: Term {
+                span: Span::default(),
+                root_factor: Factor::Expression(Box::new(Expression::from(ArithmeticExpression {
+                    span: Span::default(),
+                    root_term: Term {
+                        span: Span::default(),
+                        root_factor: Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
+                            atom: Atom::Integer(2),
+                            trailers: Vec::new(),
+                        }),
+                        trail: vec![(
+                            FactorOperator::Mult,
+                            Factor::Atomic(AtomicExpression {
+                                span: Span::default(),
+                                atom: Atom::Integer(3),
+                                trailers: Vec::new(),
+                            }),
+                        )],
+                    },
+                    trail: Vec::new(),
+                }))),
+                trail: Vec::new(),
+            },
+            trail: vec![(
+                TermOperator::Plus,
+                Term {
+                    span: Span::default(),
+                    root_factor: Factor::Atomic(AtomicExpression {
+                        span: Span::default(),
+                        atom: Atom::Integer(4),
+                        trailers: Vec::new(),
+                    }),
+                    trail: Vec::new(),
+                },
+            )],
+        };
+
+        let (rest, expr) = arithmetic_expression("(2 * 3) + 4").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(expr, expected_expression);
+    }
+}
@@ -1,4 +1,11 @@
-use nom::{branch::alt, bytes::complete::tag, combinator::map, sequence::delimited, IResult};
+use nom::{
+    branch::alt,
+    bytes::complete::{escaped_transform, is_not, tag},
+    character::complete::char,
+    combinator::{map, opt, value},
+    sequence::delimited,
+    IResult,
+};
 
 use crate::syntax::{common::whitespace, number::integer, var_decl::identifier};
 use crate::visitor::{Visitable, Visitor};
@@ -8,6 +15,7 @@ pub enum Atom {
     Boolean(bool),
     Identifier(String),
     Integer(i32),
+    String(String),
 }
 
 impl Visitable for Atom {
@@ -17,7 +25,7 @@ impl Visitable for Atom {
 }
 
 pub fn atom(i: &str) -> IResult<&str, Atom> {
-    alt((bool_atom, identifier_atom, int_atom))(i)
+    alt((bool_atom, identifier_atom, int_atom, string_atom))(i)
 }
 
 fn bool_atom(i: &str) -> IResult<&str, Atom> {
@@ -39,6 +47,29 @@ fn int_atom(i: &str) -> IResult<&str, Atom> {
     map(integer, Atom::Integer)(i)
 }
 
+/// Parses a string literal.
+///
+/// `\n`, `\t` and `\\` are the only escape sequences, since string literals cannot contain quotes.
+fn string_atom(i: &str) -> IResult<&str, Atom> {
+    let contents = escaped_transform(
+        is_not("\\\""),
+        '\\',
+        alt((
+            value("\\", char('\\')),
+            value("\n", char('n')),
+            value("\t", char('t')),
+        )),
+    );
+
+    delimited(
+        whitespace,
+        map(delimited(char('"'), opt(contents), char('"')), |s| {
+            Atom::String(s.unwrap_or_default())
+        }),
+        whitespace,
+    )(i)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{atom, Atom};
@@ -63,4 +94,21 @@ mod tests {
         assert_eq!(rest, "");
         assert_eq!(atm, Atom::Integer(83712));
     }
+
+    #[test]
+    fn string_atom() {
+        let (rest, atm) = atom(r#" "hello, world\n" "#).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(atm, Atom::String(String::from("hello, world\n")));
+
+        let (rest, atm) = atom(r#""""#).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(atm, Atom::String(String::new()));
+    }
+
+    #[test]
+    fn string_atom_invalid() {
+        assert!(atom(r#""unterminated"#).is_err());
+        assert!(atom(r#""\q""#).is_err());
+    }
 }
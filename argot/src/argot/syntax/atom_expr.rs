@@ -1,5 +1,8 @@
 use nom::{combinator::map, multi::many0, sequence::tuple, IResult};
 
+use crate::syntax::common::spanned;
+use crate::syntax::span::{Span, Spanned};
+
 use crate::syntax::types::{Atom, Trailer};
 use crate::syntax::{atom::atom, trailer::trailer};
 use crate::visitor::{Visitable, Visitor};
@@ -8,6 +11,13 @@ use crate::visitor::{Visitable, Visitor};
 pub struct AtomicExpression {
     pub atom: Atom,
     pub trailers: Vec<Trailer>,
+    pub span: Span,
+}
+
+impl Spanned for AtomicExpression {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for AtomicExpression {
@@ -17,7 +27,12 @@ impl Visitable for AtomicExpression {
 }
 
 pub fn atomic_expression(i: &str) -> IResult<&str, AtomicExpression> {
-    map(tuple((atom, many0(trailer))), |(atom, trailers)| {
-        AtomicExpression { atom, trailers }
-    })(i)
+    map(
+        spanned(tuple((atom, many0(trailer)))),
+        |((atom, trailers), span)| AtomicExpression {
+            atom,
+            trailers,
+            span,
+        },
+    )(i)
 }
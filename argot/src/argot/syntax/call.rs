@@ -6,13 +6,26 @@ use nom::{
     IResult,
 };
 
-use crate::syntax::{expression::expression, types::Expression, var_decl::identifier};
+use crate::syntax::{
+    common::spanned,
+    expression::expression,
+    span::{Span, Spanned},
+    types::Expression,
+    var_decl::identifier,
+};
 use crate::visitor::{Visitable, Visitor};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FunctionCall {
     pub name: String,
     pub arguments: Vec<Expression>,
+    pub span: Span,
+}
+
+impl Spanned for FunctionCall {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for FunctionCall {
@@ -23,13 +36,14 @@ impl Visitable for FunctionCall {
 
 pub fn function_call(i: &str) -> IResult<&str, FunctionCall> {
     map(
-        tuple((
+        spanned(tuple((
             identifier,
             delimited(char('('), separated_list0(char(','), expression), char(')')),
-        )),
-        |(fn_name, args)| FunctionCall {
+        ))),
+        |((fn_name, args), span)| FunctionCall {
             name: String::from(fn_name),
             arguments: args,
+            span,
         },
     )(i)
 }
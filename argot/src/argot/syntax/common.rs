@@ -1,7 +1,47 @@
-use nom::{bytes::complete::take_while, IResult};
+use nom::{
+    bytes::complete::{tag, take_while},
+    character::complete::satisfy,
+    combinator::not,
+    sequence::terminated,
+    IResult,
+};
+
+use crate::syntax::span::Span;
 
 /// Eats {0-n} whitespace characters.
 pub fn whitespace(i: &str) -> IResult<&str, &str> {
     let chars = " \t\r\n";
     take_while(move |c| chars.contains(c))(i)
 }
+
+/// Parses a keyword that is not the prefix of a longer identifier.
+pub fn keyword<'a>(kw: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(kw), not(satisfy(|c: char| c.is_alphanumeric())))
+}
+
+/// Runs a parser and returns its output along with the span of the input it consumed.
+///
+/// Leading and trailing whitespace is not part of the span.
+pub fn spanned<'a, O, F>(mut parser: F) -> impl FnMut(&'a str) -> IResult<&'a str, (O, Span)>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    move |i: &'a str| {
+        let (i, _) = whitespace(i)?;
+        let (rest, output) = parser(i)?;
+
+        let consumed = i[..i.len() - rest.len()].trim_end();
+        Ok((rest, (output, Span::new(i.len(), i.len() - consumed.len()))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::keyword;
+
+    #[test]
+    fn keyword_prefix() {
+        assert_eq!(keyword("break")("break;").unwrap(), (";", "break"));
+        assert!(keyword("break")("breaks;").is_err());
+    }
+}
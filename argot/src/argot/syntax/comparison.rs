@@ -0,0 +1,73 @@
+use nom::{
+    combinator::{map, opt},
+    sequence::{delimited, tuple},
+    IResult,
+};
+
+use crate::syntax::{
+    arithmetic::{arithmetic_expression, ArithmeticExpression},
+    common::{spanned, whitespace},
+    operator::{comparison_operator, ComparisonOperator},
+    span::{Span, Spanned},
+};
+use crate::visitor::{Visitable, Visitor};
+
+/// An arithmetic expression, optionally compared to another one.
+///
+/// Comparisons do not chain: `a < b < c` is not an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub root: ArithmeticExpression,
+    pub comparison: Option<(ComparisonOperator, ArithmeticExpression)>,
+    pub span: Span,
+}
+
+impl Spanned for Comparison {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Visitable for Comparison {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V) -> V::Result {
+        visitor.visit_comparison(self)
+    }
+}
+
+pub fn comparison(i: &str) -> IResult<&str, Comparison> {
+    let t = delimited(
+        whitespace,
+        spanned(tuple((
+            arithmetic_expression,
+            opt(tuple((comparison_operator, arithmetic_expression))),
+        ))),
+        whitespace,
+    );
+    map(t, |((root, comparison), span)| Comparison {
+        root,
+        comparison,
+        span,
+    })(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{comparison, ComparisonOperator};
+
+    #[test]
+    fn comparison_expression() {
+        let (rest, cmp) = comparison("a + 1 <= 3 * b").unwrap();
+        assert_eq!(rest, "");
+
+        let (operator, rhs) = cmp.comparison.unwrap();
+        assert_eq!(operator, ComparisonOperator::LowerOrEqual);
+        assert_eq!(cmp.root.trail.len(), 1);
+        assert_eq!(rhs.root_term.trail.len(), 1);
+    }
+
+    #[test]
+    fn comparison_does_not_chain() {
+        let (rest, _) = comparison("1 < 2 < 3").unwrap();
+        assert_eq!(rest, "< 3");
+    }
+}
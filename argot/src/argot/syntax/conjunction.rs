@@ -0,0 +1,70 @@
+use nom::{
+    bytes::complete::tag,
+    combinator::map,
+    multi::many0,
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::syntax::{
+    common::{spanned, whitespace},
+    comparison::{comparison, Comparison},
+    span::{Span, Spanned},
+};
+use crate::visitor::{Visitable, Visitor};
+
+/// Comparisons joined by `&&`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conjunction {
+    pub root: Comparison,
+    pub trail: Vec<Comparison>,
+    pub span: Span,
+}
+
+impl Spanned for Conjunction {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Visitable for Conjunction {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V) -> V::Result {
+        visitor.visit_conjunction(self)
+    }
+}
+
+pub fn conjunction(i: &str) -> IResult<&str, Conjunction> {
+    let t = delimited(
+        whitespace,
+        spanned(tuple((
+            comparison,
+            many0(preceded(
+                delimited(whitespace, tag("&&"), whitespace),
+                comparison,
+            )),
+        ))),
+        whitespace,
+    );
+    map(t, |((root, trail), span)| Conjunction { root, trail, span })(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::conjunction;
+
+    #[test]
+    fn conjunction_precedence() {
+        let (rest, conj) = conjunction("a == 1 && b + 2 < c && d").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(conj.trail.len(), 2);
+        assert!(conj.root.comparison.is_some());
+        assert!(conj.trail[1].comparison.is_none());
+    }
+
+    #[test]
+    fn conjunction_stops_at_or() {
+        let (rest, conj) = conjunction("a && b || c").unwrap();
+        assert_eq!(rest, "|| c");
+        assert_eq!(conj.trail.len(), 1);
+    }
+}
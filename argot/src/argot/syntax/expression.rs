@@ -1,21 +1,38 @@
 use nom::{
+    bytes::complete::tag,
     combinator::map,
     multi::many0,
-    sequence::{delimited, tuple},
+    sequence::{delimited, preceded, tuple},
     IResult,
 };
 
+use crate::compiler::Type;
 use crate::syntax::{
-    common::whitespace,
-    operator::{term_operator, TermOperator},
-    term::{term, Term},
+    arithmetic::ArithmeticExpression,
+    common::{spanned, whitespace},
+    comparison::Comparison,
+    conjunction::{conjunction, Conjunction},
+    factor::Factor,
+    span::{Span, Spanned},
+    types::AtomicExpression,
 };
 use crate::visitor::{Visitable, Visitor};
 
+/// Conjunctions joined by `||`.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Expression {
-    pub root_term: Term,
-    pub trail: Vec<(TermOperator, Term)>,
+    pub root: Conjunction,
+    pub trail: Vec<Conjunction>,
+    pub span: Span,
+
+    /// Type of the value of the expression, set by the type checker.
+    pub value_type: Option<Type>,
+}
+
+impl Spanned for Expression {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for Expression {
@@ -24,96 +41,101 @@ impl Visitable for Expression {
     }
 }
 
+impl From<ArithmeticExpression> for Expression {
+    /// Wraps an arithmetic expression that is not compared or joined by logical operators.
+    fn from(root: ArithmeticExpression) -> Expression {
+        let span = root.span;
+        let root = Conjunction {
+            root: Comparison {
+                root,
+                comparison: None,
+                span,
+            },
+            trail: Vec::new(),
+            span,
+        };
+
+        Expression {
+            root,
+            trail: Vec::new(),
+            span,
+            value_type: None,
+        }
+    }
+}
+
+impl Expression {
+    /// Returns the atomic expression this expression is made of, if it has no operators.
+    pub fn as_atomic_mut(&mut self) -> Option<&mut AtomicExpression> {
+        if !self.trail.is_empty() || !self.root.trail.is_empty() {
+            return None;
+        }
+
+        let comparison = &mut self.root.root;
+        if comparison.comparison.is_some() || !comparison.root.trail.is_empty() {
+            return None;
+        }
+
+        let term = &mut comparison.root.root_term;
+        if !term.trail.is_empty() {
+            return None;
+        }
+
+        match &mut term.root_factor {
+            Factor::Atomic(atomic) => Some(atomic),
+            Factor::Expression(expr) => expr.as_atomic_mut(),
+            _ => None,
+        }
+    }
+}
+
 pub fn expression(i: &str) -> IResult<&str, Expression> {
     let t = delimited(
         whitespace,
-        tuple((term, many0(tuple((term_operator, term))))),
+        spanned(tuple((
+            conjunction,
+            many0(preceded(
+                delimited(whitespace, tag("||"), whitespace),
+                conjunction,
+            )),
+        ))),
         whitespace,
     );
-    map(t, |(root_trm, lst)| Expression {
-        root_term: root_trm,
-        trail: lst,
+    map(t, |((root, trail), span)| Expression {
+        root,
+        trail,
+        span,
+        value_type: None,
     })(i)
 }
 
 #[cfg(test)]
 mod tests {
-
-    use super::{expression, Expression, Term, TermOperator};
-    use crate::syntax::types::{Atom, AtomicExpression, Factor, FactorOperator, UnaryOperator};
+    use super::expression;
 
     #[test]
-    fn arithm_expression() {
-        let expected_expression = Expression {
-            root_term: Term {
-                root_factor: Factor::Atomic(AtomicExpression {
-                    atom: Atom::Integer(18),
-                    trailers: Vec::new(),
-                }),
-                trail: Vec::new(),
-            },
-            trail: vec![(
-                TermOperator::Plus,
-                Term {
-                    root_factor: Factor::Atomic(AtomicExpression {
-                        atom: Atom::Integer(15),
-                        trailers: Vec::new(),
-                    }),
-                    trail: vec![(
-                        FactorOperator::Mult,
-                        Factor::Unary(
-                            UnaryOperator::Minus,
-                            Box::new(Factor::Atomic(AtomicExpression {
-                                atom: Atom::Integer(4),
-                                trailers: Vec::new(),
-                            })),
-                        ),
-                    )],
-                },
-            )],
-        };
+    fn or_precedence() {
+        let (rest, expr) = expression("a || b && c || !d").unwrap();
+        assert_eq!(rest, "");
+        assert!(expr.root.trail.is_empty());
+        assert_eq!(expr.trail.len(), 2);
+        assert_eq!(expr.trail[0].trail.len(), 1);
+    }
 
-        let (rest, expr) = expression("18 + 15 * -4").unwrap();
+    #[test]
+    fn parenthesized_or() {
+        let (rest, expr) = expression("(a || b) && c").unwrap();
         assert_eq!(rest, "");
-        assert_eq!(expr, expected_expression);
+        assert!(expr.trail.is_empty());
+        assert_eq!(expr.root.trail.len(), 1);
     }
 
     #[test]
-    fn nested_expression() {
-        let expected_expression = Expression {
-            root_term: Term {
-                root_factor: Factor::Expression(Box::new(Expression {
-                    root_term: Term {
-                        root_factor: Factor::Atomic(AtomicExpression {
-                            atom: Atom::Integer(2),
-                            trailers: Vec::new(),
-                        }),
-                        trail: vec![(
-                            FactorOperator::Mult,
-                            Factor::Atomic(AtomicExpression {
-                                atom: Atom::Integer(3),
-                                trailers: Vec::new(),
-                            }),
-                        )],
-                    },
-                    trail: Vec::new(),
-                })),
-                trail: Vec::new(),
-            },
-            trail: vec![(
-                TermOperator::Plus,
-                Term {
-                    root_factor: Factor::Atomic(AtomicExpression {
-                        atom: Atom::Integer(4),
-                        trailers: Vec::new(),
-                    }),
-                    trail: Vec::new(),
-                },
-            )],
-        };
+    fn atomic() {
+        let (_, mut expr) = expression("(p.x)").unwrap();
+        assert_eq!(expr.as_atomic_mut().unwrap().trailers.len(), 1);
 
-        let (rest, expr) = expression("(2 * 3) + 4").unwrap();
-        assert_eq!(rest, "");
-        assert_eq!(expr, expected_expression);
+        let (_, mut expr) = expression("p.x + 1").unwrap();
+        assert!(expr.as_atomic_mut().is_none());
     }
 }
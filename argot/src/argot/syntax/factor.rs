@@ -7,12 +7,14 @@ use nom::{
 };
 
 use crate::syntax::{
+    allocation::{allocation, Allocation},
     atom_expr::{atomic_expression, AtomicExpression},
     call::{function_call, FunctionCall},
     common::whitespace,
     expression::{expression, Expression},
     if_expr::{if_expression, IfExpression},
     operator::{unary_operator, UnaryOperator},
+    span::{Span, Spanned},
 };
 use crate::visitor::{Visitable, Visitor};
 
@@ -23,6 +25,7 @@ pub enum Factor {
     Expression(Box<Expression>),
     FunctionCall(FunctionCall),
     IfExpression(IfExpression),
+    Allocation(Allocation),
 }
 
 impl Visitable for Factor {
@@ -31,11 +34,25 @@ impl Visitable for Factor {
     }
 }
 
+impl Spanned for Factor {
+    fn span(&self) -> Span {
+        match self {
+            Factor::Atomic(atom) => atom.span(),
+            Factor::Unary(_, factor) => factor.span(),
+            Factor::Expression(expr) => expr.span(),
+            Factor::FunctionCall(call) => call.span(),
+            Factor::IfExpression(if_expr) => if_expr.span(),
+            Factor::Allocation(allocation) => allocation.span(),
+        }
+    }
+}
+
 pub fn factor(i: &str) -> IResult<&str, Factor> {
     delimited(
         whitespace,
         alt((
             if_expression_factor,
+            allocation_factor,
             fn_call_factor,
             unary_factor,
             expr_factor,
@@ -49,6 +66,10 @@ fn if_expression_factor(i: &str) -> IResult<&str, Factor> {
     map(if_expression, Factor::IfExpression)(i)
 }
 
+fn allocation_factor(i: &str) -> IResult<&str, Factor> {
+    map(allocation, Factor::Allocation)(i)
+}
+
 fn fn_call_factor(i: &str) -> IResult<&str, Factor> {
     map(function_call, Factor::FunctionCall)(i)
 }
@@ -78,6 +99,7 @@ fn expr_factor(i: &str) -> IResult<&str, Factor> {
 mod tests {
     use super::{factor, AtomicExpression, Factor, UnaryOperator};
     use crate::syntax::types::Atom;
+    use crate::syntax::Span;
 
     #[test]
     fn test_atom_factor() {
@@ -86,6 +108,7 @@ mod tests {
         assert_eq!(
             f,
             Factor::Atomic(AtomicExpression {
+                span: Span::default(),
                 atom: Atom::Integer(48),
                 trailers: Vec::new()
             })
@@ -101,6 +124,7 @@ mod tests {
             Factor::Unary(
                 UnaryOperator::Minus,
                 Box::new(Factor::Atomic(AtomicExpression {
+                    span: Span::default(),
                     atom: Atom::Integer(42),
                     trailers: Vec::new()
                 }))
@@ -0,0 +1,122 @@
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::{map, opt},
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::syntax::{
+    block::{block, Block},
+    common::{keyword, spanned, whitespace},
+    expression::{expression, Expression},
+    span::{Span, Spanned},
+    statement::Statement,
+    var_decl::{variable_assignment, variable_declaration},
+};
+
+use crate::visitor::{Visitable, Visitor};
+
+/// A C-style `for (init; condition; step) { ... }` loop.
+///
+/// Every clause is optional, and a missing condition loops forever.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForLoop {
+    pub init: Option<Box<Statement>>,
+    pub condition: Option<Expression>,
+    pub step: Option<Box<Statement>>,
+    pub block: Block,
+    pub span: Span,
+}
+
+impl Spanned for ForLoop {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Visitable for ForLoop {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V) -> V::Result {
+        visitor.visit_for_loop(self)
+    }
+}
+
+fn init_clause(i: &str) -> IResult<&str, Statement> {
+    alt((map(variable_declaration, Statement::VarDecl), step_clause))(i)
+}
+
+fn step_clause(i: &str) -> IResult<&str, Statement> {
+    alt((
+        map(variable_assignment, Statement::VarAssign),
+        map(expression, Statement::Expr),
+    ))(i)
+}
+
+pub fn for_loop(i: &str) -> IResult<&str, ForLoop> {
+    map(
+        spanned(tuple((
+            preceded(
+                keyword("for"),
+                delimited(
+                    whitespace,
+                    delimited(
+                        char('('),
+                        tuple((
+                            delimited(whitespace, opt(init_clause), char(';')),
+                            delimited(whitespace, opt(expression), char(';')),
+                            delimited(whitespace, opt(step_clause), whitespace),
+                        )),
+                        char(')'),
+                    ),
+                    whitespace,
+                ),
+            ),
+            block,
+        ))),
+        |(((init, condition, step), block), span)| ForLoop {
+            init: init.map(Box::new),
+            condition,
+            step: step.map(Box::new),
+            block,
+            span,
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::for_loop;
+    use crate::syntax::types::Statement;
+
+    #[test]
+    fn for_all_clauses() {
+        let (rest, for_expr) = for_loop("for (int i = 3; i; i = i - 1) { continue; }").unwrap();
+        assert_eq!(rest, "");
+
+        assert!(matches!(
+            for_expr.init.as_deref(),
+            Some(Statement::VarDecl(_))
+        ));
+        assert!(for_expr.condition.is_some());
+        assert!(matches!(
+            for_expr.step.as_deref(),
+            Some(Statement::VarAssign(_))
+        ));
+        assert!(matches!(for_expr.block.body[0], Statement::Continue(_)));
+    }
+
+    #[test]
+    fn for_no_clauses() {
+        let (rest, for_expr) = for_loop("for (;;) {}").unwrap();
+        assert_eq!(rest, "");
+
+        assert!(for_expr.init.is_none());
+        assert!(for_expr.condition.is_none());
+        assert!(for_expr.step.is_none());
+    }
+
+    #[test]
+    fn for_missing_clause() {
+        assert!(for_loop("for (int i = 0; i) {}").is_err());
+    }
+}
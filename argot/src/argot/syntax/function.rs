@@ -1,8 +1,8 @@
 use nom::{
     bytes::complete::tag,
     character::complete::alpha1,
-    combinator::map,
-    sequence::{delimited, tuple},
+    combinator::{map, opt},
+    sequence::{delimited, preceded, tuple},
     IResult,
 };
 
@@ -10,17 +10,29 @@ use crate::{
     syntax::{
         argument_list::{argument_list, ArgumentList},
         block::{block, Block},
-        common::whitespace,
+        common::{spanned, whitespace},
+        span::{Span, Spanned},
+        var_decl::type_name,
     },
     visitor::{Visitable, Visitor},
 };
 
+/// Return type of functions that do not declare one.
+pub const VOID_TYPE: &str = "void";
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct FunctionDeclaration {
     pub return_type: String,
     pub name: String,
     pub block: Block,
     pub args: ArgumentList,
+    pub span: Span,
+}
+
+impl Spanned for FunctionDeclaration {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for FunctionDeclaration {
@@ -32,22 +44,30 @@ impl Visitable for FunctionDeclaration {
 pub fn function_declaration(i: &str) -> IResult<&str, FunctionDeclaration> {
     map(
         tuple((
-            tag("fn"),
-            delimited(whitespace, alpha1, whitespace),
-            argument_list,
+            spanned(tuple((
+                tag("fn"),
+                delimited(whitespace, alpha1, whitespace),
+                argument_list,
+                opt(preceded(
+                    delimited(whitespace, tag("->"), whitespace),
+                    type_name,
+                )),
+            ))),
             block,
         )),
-        |(_f, name, args, block)| FunctionDeclaration {
-            return_type: String::from("int"),
+        |(((_f, name, args, return_type), span), block)| FunctionDeclaration {
+            return_type: String::from(return_type.unwrap_or(VOID_TYPE)),
             name: String::from(name),
             block,
             args,
+            span,
         },
     )(i)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::syntax::Span;
 
     use super::function_declaration;
 
@@ -62,7 +82,8 @@ mod tests {
         assert_eq!(
             decl,
             FunctionDeclaration {
-                return_type: String::from("int"),
+                span: Span::default(),
+                return_type: String::from("void"),
                 name: String::from("hello"),
                 block: Block::new(),
                 args: ArgumentList::default()
@@ -77,10 +98,13 @@ mod tests {
         assert_eq!(
             decl,
             FunctionDeclaration {
-                return_type: String::from("int"),
+                span: Span::default(),
+                return_type: String::from("void"),
                 name: String::from("hello"),
                 block: Block {
                     body: vec![Statement::VarDecl(VariableDeclaration {
+                        array_length: None,
+                        span: Span::default(),
                         name: String::from("a"),
                         var_type: String::from("int"),
                         expression: None
@@ -90,4 +114,12 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn fn_decl_return_type() {
+        let (rest, decl) = function_declaration("fn answer() -> int { return 42; }").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(decl.return_type, "int");
+        assert_eq!(decl.block.body.len(), 1);
+    }
 }
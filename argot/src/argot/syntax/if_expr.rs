@@ -8,8 +8,9 @@ use nom::{
 
 use crate::syntax::{
     block::{block, Block},
-    common::whitespace,
+    common::{spanned, whitespace},
     expression::{expression, Expression},
+    span::{Span, Spanned},
 };
 
 use crate::visitor::{Visitable, Visitor};
@@ -19,6 +20,13 @@ pub struct IfExpression {
     pub condition: Box<Expression>,
     pub if_block: Block,
     pub else_block: Option<Block>,
+    pub span: Span,
+}
+
+impl Spanned for IfExpression {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for IfExpression {
@@ -29,7 +37,7 @@ impl Visitable for IfExpression {
 
 pub fn if_expression(i: &str) -> IResult<&str, IfExpression> {
     map(
-        tuple((
+        spanned(tuple((
             preceded(
                 tag("if"),
                 delimited(
@@ -40,11 +48,12 @@ pub fn if_expression(i: &str) -> IResult<&str, IfExpression> {
             ),
             block,
             opt(preceded(tag("else"), block)),
-        )),
-        |(condition, if_block, else_block)| IfExpression {
+        ))),
+        |((condition, if_block, else_block), span)| IfExpression {
             condition: Box::new(condition),
             if_block,
             else_block,
+            span,
         },
     )(i)
 }
@@ -53,8 +62,10 @@ pub fn if_expression(i: &str) -> IResult<&str, IfExpression> {
 mod tests {
     use super::{if_expression, IfExpression};
     use crate::syntax::types::{
-        Atom, AtomicExpression, Block, Expression, Factor, Statement, Term, VariableDeclaration,
+        ArithmeticExpression, Atom, AtomicExpression, Block, Expression, Factor, Statement, Term,
+        VariableDeclaration,
     };
+    use crate::syntax::Span;
 
     #[test]
     fn if_empty_condition() {
@@ -69,16 +80,20 @@ mod tests {
         assert_eq!(
             if_expr,
             IfExpression {
-                condition: Box::new(Expression {
+                span: Span::default(),
+                condition: Box::new(Expression::from(ArithmeticExpression {
+                    span: Span::default(),
                     root_term: Term {
+                        span: Span::default(),
                         root_factor: Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
                             atom: Atom::Integer(18),
                             trailers: Vec::new()
                         }),
                         trail: Vec::new()
                     },
                     trail: Vec::new()
-                }),
+                })),
                 if_block: Block::new(),
                 else_block: None,
             }
@@ -92,30 +107,39 @@ mod tests {
         assert_eq!(
             if_expr,
             IfExpression {
-                condition: Box::new(Expression {
+                span: Span::default(),
+                condition: Box::new(Expression::from(ArithmeticExpression {
+                    span: Span::default(),
                     root_term: Term {
+                        span: Span::default(),
                         root_factor: Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
                             atom: Atom::Integer(18),
                             trailers: Vec::new()
                         }),
                         trail: Vec::new()
                     },
                     trail: Vec::new()
-                }),
+                })),
                 if_block: Block {
                     body: vec![Statement::VarDecl(VariableDeclaration {
+                        array_length: None,
+                        span: Span::default(),
                         var_type: String::from("int"),
                         name: String::from("a"),
-                        expression: Some(Expression {
+                        expression: Some(Expression::from(ArithmeticExpression {
+                            span: Span::default(),
                             root_term: Term {
+                                span: Span::default(),
                                 root_factor: Factor::Atomic(AtomicExpression {
+                                    span: Span::default(),
                                     atom: Atom::Integer(3),
                                     trailers: Vec::new()
                                 }),
                                 trail: Vec::new()
                             },
                             trail: Vec::new()
-                        })
+                        }))
                     })]
                 },
                 else_block: None,
@@ -134,30 +158,39 @@ mod tests {
         assert_eq!(
             if_expr,
             IfExpression {
-                condition: Box::new(Expression {
+                span: Span::default(),
+                condition: Box::new(Expression::from(ArithmeticExpression {
+                    span: Span::default(),
                     root_term: Term {
+                        span: Span::default(),
                         root_factor: Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
                             atom: Atom::Integer(18),
                             trailers: Vec::new()
                         }),
                         trail: Vec::new()
                     },
                     trail: Vec::new()
-                }),
+                })),
                 if_block: Block {
                     body: vec![Statement::VarDecl(VariableDeclaration {
+                        array_length: None,
+                        span: Span::default(),
                         var_type: String::from("int"),
                         name: String::from("a"),
-                        expression: Some(Expression {
+                        expression: Some(Expression::from(ArithmeticExpression {
+                            span: Span::default(),
                             root_term: Term {
+                                span: Span::default(),
                                 root_factor: Factor::Atomic(AtomicExpression {
+                                    span: Span::default(),
                                     atom: Atom::Integer(3),
                                     trailers: Vec::new()
                                 }),
                                 trail: Vec::new()
                             },
                             trail: Vec::new()
-                        })
+                        }))
                     })]
                 },
                 else_block: Some(Block::new()),
@@ -176,46 +209,60 @@ mod tests {
         assert_eq!(
             if_expr,
             IfExpression {
-                condition: Box::new(Expression {
+                span: Span::default(),
+                condition: Box::new(Expression::from(ArithmeticExpression {
+                    span: Span::default(),
                     root_term: Term {
+                        span: Span::default(),
                         root_factor: Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
                             atom: Atom::Integer(18),
                             trailers: Vec::new()
                         }),
                         trail: Vec::new()
                     },
                     trail: Vec::new()
-                }),
+                })),
                 if_block: Block {
                     body: vec![Statement::VarDecl(VariableDeclaration {
+                        array_length: None,
+                        span: Span::default(),
                         var_type: String::from("int"),
                         name: String::from("a"),
-                        expression: Some(Expression {
+                        expression: Some(Expression::from(ArithmeticExpression {
+                            span: Span::default(),
                             root_term: Term {
+                                span: Span::default(),
                                 root_factor: Factor::Atomic(AtomicExpression {
+                                    span: Span::default(),
                                     atom: Atom::Integer(3),
                                     trailers: Vec::new()
                                 }),
                                 trail: Vec::new()
                             },
                             trail: Vec::new()
-                        })
+                        }))
                     })]
                 },
                 else_block: Some(Block {
                     body: vec![Statement::VarDecl(VariableDeclaration {
+                        array_length: None,
+                        span: Span::default(),
                         var_type: String::from("int"),
                         name: String::from("a"),
-                        expression: Some(Expression {
+                        expression: Some(Expression::from(ArithmeticExpression {
+                            span: Span::default(),
                             root_term: Term {
+                                span: Span::default(),
                                 root_factor: Factor::Atomic(AtomicExpression {
+                                    span: Span::default(),
                                     atom: Atom::Integer(5),
                                     trailers: Vec::new()
                                 }),
                                 trail: Vec::new()
                             },
                             trail: Vec::new()
-                        })
+                        }))
                     })]
                 }),
             }
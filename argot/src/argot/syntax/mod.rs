@@ -1,61 +1,91 @@
 /*
 Grammar.
 
+Expression: Conjunction ( || Conjunction )*
+
+Conjunction: Comparison ( && Comparison )*
+
+Comparison: ArithmeticExpression ( ComparisonOperator ArithmeticExpression )?
+
 ArithmeticExpression: Term ( (+ | -) term)*
 
 Term: Factor ( (* | /) Factor )*
 
 Factor:   UnaryOperator Factor
+        | new Type ( [ Expression ] )?
+        | Atom ( [ Expression ] | . Identifier )*
         | Integer
         | Float
 
+ComparisonOperator: == | != | > | < | >= | <=
 TermOperator:   + | -
 FactorOperator: * | /
 UnaryOperator:  -
 */
 
+pub mod allocation;
 pub mod argument_list;
+pub mod arithmetic;
 pub mod atom;
 pub mod atom_expr;
 pub mod block;
 pub mod call;
 pub mod common;
+pub mod comparison;
+pub mod conjunction;
 pub mod expression;
 pub mod factor;
+pub mod for_loop;
 pub mod function;
 pub mod if_expr;
 pub mod number;
 pub mod operator;
 pub mod program;
+pub mod span;
 pub mod statement;
+pub mod struct_decl;
 pub mod term;
 pub mod trailer;
 pub mod var_decl;
+pub mod while_loop;
 
 pub mod types {
+    pub use super::allocation::Allocation;
     pub use super::argument_list::{Argument, ArgumentList};
+    pub use super::arithmetic::ArithmeticExpression;
     pub use super::atom::Atom;
     pub use super::atom_expr::AtomicExpression;
     pub use super::block::Block;
     pub use super::call::FunctionCall;
+    pub use super::comparison::Comparison;
+    pub use super::conjunction::Conjunction;
     pub use super::expression::Expression;
     pub use super::factor::Factor;
+    pub use super::for_loop::ForLoop;
     pub use super::function::FunctionDeclaration;
     pub use super::if_expr::IfExpression;
-    pub use super::operator::{FactorOperator, TermOperator, UnaryOperator};
+    pub use super::operator::{
+        ComparisonOperator, FactorOperator, LogicalOperator, TermOperator, UnaryOperator,
+    };
     pub use super::program::Program;
+    pub use super::span::{Span, Spanned};
     pub use super::statement::Statement;
+    pub use super::struct_decl::{StructDeclaration, StructField};
     pub use super::term::Term;
     pub use super::trailer::Trailer;
     pub use super::var_decl::{VariableAssignment, VariableDeclaration};
+    pub use super::while_loop::WhileLoop;
 }
 
 pub use atom::Atom;
 pub use block::Block;
 pub use factor::Factor;
 pub use function::FunctionDeclaration;
-pub use operator::{FactorOperator, TermOperator, UnaryOperator};
+pub use operator::{
+    ComparisonOperator, FactorOperator, LogicalOperator, TermOperator, UnaryOperator,
+};
 pub use program::Program;
+pub use span::{Span, Spanned};
 pub use statement::Statement;
 pub use term::Term;
 pub use var_decl::{VariableAssignment, VariableDeclaration};
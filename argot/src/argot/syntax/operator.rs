@@ -1,3 +1,5 @@
+use std::fmt;
+
 use nom::{
     branch::alt, bytes::complete::tag, character::complete::char, combinator::map,
     sequence::delimited, IResult,
@@ -37,9 +39,6 @@ pub enum TermOperator {
     Plus,
     Minus,
 
-    And,
-    Or,
-
     Unknown,
 }
 
@@ -54,8 +53,6 @@ impl From<String> for TermOperator {
         match c.as_ref() {
             "+" => TermOperator::Plus,
             "-" => TermOperator::Minus,
-            "&&" => TermOperator::And,
-            "||" => TermOperator::Or,
             _ => TermOperator::Unknown,
         }
     }
@@ -69,12 +66,12 @@ pub enum FactorOperator {
     Unknown, // TODO: Get more detail.
 }
 
-impl ToString for FactorOperator {
-    fn to_string(&self) -> String {
+impl fmt::Display for FactorOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            FactorOperator::Div => String::from("/"),
-            FactorOperator::Mult => String::from("*"),
-            FactorOperator::Unknown => String::from("IGL"),
+            FactorOperator::Div => write!(f, "/"),
+            FactorOperator::Mult => write!(f, "*"),
+            FactorOperator::Unknown => write!(f, "IGL"),
         }
     }
 }
@@ -95,6 +92,48 @@ impl From<String> for FactorOperator {
     }
 }
 
+/// Operator joining the operands of a [`Conjunction`] or an [`Expression`].
+///
+/// [`Conjunction`]: crate::syntax::types::Conjunction
+/// [`Expression`]: crate::syntax::types::Expression
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LogicalOperator {
+    And,
+    Or,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ComparisonOperator {
+    Equal,
+    NotEqual,
+    Greater,
+    Lower,
+    GreaterOrEqual,
+    LowerOrEqual,
+
+    Unknown,
+}
+
+impl Visitable for ComparisonOperator {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V) -> V::Result {
+        visitor.visit_comparison_operator(self)
+    }
+}
+
+impl From<String> for ComparisonOperator {
+    fn from(c: String) -> ComparisonOperator {
+        match c.as_ref() {
+            "==" => ComparisonOperator::Equal,
+            "!=" => ComparisonOperator::NotEqual,
+            ">" => ComparisonOperator::Greater,
+            "<" => ComparisonOperator::Lower,
+            ">=" => ComparisonOperator::GreaterOrEqual,
+            "<=" => ComparisonOperator::LowerOrEqual,
+            _ => ComparisonOperator::Unknown,
+        }
+    }
+}
+
 pub fn unary_operator(i: &str) -> IResult<&str, UnaryOperator> {
     map(
         delimited(
@@ -108,11 +147,7 @@ pub fn unary_operator(i: &str) -> IResult<&str, UnaryOperator> {
 
 pub fn term_operator(i: &str) -> IResult<&str, TermOperator> {
     map(
-        delimited(
-            whitespace,
-            alt((tag("+"), tag("-"), tag("&&"), tag("||"))),
-            whitespace,
-        ),
+        delimited(whitespace, alt((char('+'), char('-'))), whitespace),
         |c| TermOperator::from(c.to_string()),
     )(i)
 }
@@ -124,10 +159,29 @@ pub fn factor_operator(i: &str) -> IResult<&str, FactorOperator> {
     )(i)
 }
 
+pub fn comparison_operator(i: &str) -> IResult<&str, ComparisonOperator> {
+    map(
+        delimited(
+            whitespace,
+            alt((
+                tag("=="),
+                tag("!="),
+                tag(">="),
+                tag("<="),
+                tag(">"),
+                tag("<"),
+            )),
+            whitespace,
+        ),
+        |c: &str| ComparisonOperator::from(c.to_string()),
+    )(i)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        factor_operator, term_operator, unary_operator, FactorOperator, TermOperator, UnaryOperator,
+        comparison_operator, factor_operator, term_operator, unary_operator, ComparisonOperator,
+        FactorOperator, TermOperator, UnaryOperator,
     };
 
     #[test]
@@ -171,17 +225,9 @@ mod tests {
     }
 
     #[test]
-    fn term_op_and() {
-        let (rest, op) = term_operator("  && ").unwrap();
-        assert_eq!(rest, "");
-        assert_eq!(op, TermOperator::And);
-    }
-
-    #[test]
-    fn term_op_or() {
-        let (rest, op) = term_operator(" || ").unwrap();
-        assert_eq!(rest, "");
-        assert_eq!(op, TermOperator::Or);
+    fn term_op_logical() {
+        assert!(term_operator("  && ").is_err());
+        assert!(term_operator(" || ").is_err());
     }
 
     #[test]
@@ -207,4 +253,28 @@ mod tests {
     fn factor_op_invalid() {
         assert!(factor_operator("  ? ").is_err());
     }
+
+    #[test]
+    fn comparison_ops() {
+        let cases = [
+            ("==", ComparisonOperator::Equal),
+            ("!=", ComparisonOperator::NotEqual),
+            (">", ComparisonOperator::Greater),
+            ("<", ComparisonOperator::Lower),
+            (" >= ", ComparisonOperator::GreaterOrEqual),
+            (" <= ", ComparisonOperator::LowerOrEqual),
+        ];
+
+        for (src, expected) in cases.iter() {
+            let (rest, op) = comparison_operator(src).unwrap();
+            assert_eq!(rest, "");
+            assert_eq!(op, *expected);
+        }
+    }
+
+    #[test]
+    fn comparison_op_invalid() {
+        assert!(comparison_operator(" = ").is_err());
+        assert!(comparison_operator(" ! ").is_err());
+    }
 }
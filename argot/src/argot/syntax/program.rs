@@ -1,16 +1,24 @@
 use std::collections::HashMap;
 
-use nom::{combinator::map, multi::many0, sequence::delimited, IResult};
+use nom::{branch::alt, combinator::map, multi::many0, sequence::delimited, IResult};
 
 use crate::syntax::{
     common::whitespace,
     function::{function_declaration, FunctionDeclaration},
+    struct_decl::{struct_declaration, StructDeclaration},
 };
 use crate::visitor::{Visitable, Visitor};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Program {
     pub functions: HashMap<String, FunctionDeclaration>,
+
+    /// Functions declared with the name of an earlier function, in source order. Only the first
+    /// declaration of a name is part of `functions`.
+    pub redefinitions: Vec<FunctionDeclaration>,
+
+    /// Struct declarations, in source order.
+    pub structs: Vec<StructDeclaration>,
 }
 
 impl Visitable for Program {
@@ -19,21 +27,46 @@ impl Visitable for Program {
     }
 }
 
+enum Declaration {
+    Function(FunctionDeclaration),
+    Struct(StructDeclaration),
+}
+
 pub fn program(i: &str) -> IResult<&str, Program> {
     map(
-        many0(delimited(whitespace, function_declaration, whitespace)),
+        many0(delimited(
+            whitespace,
+            alt((
+                map(function_declaration, Declaration::Function),
+                map(struct_declaration, Declaration::Struct),
+            )),
+            whitespace,
+        )),
         |decls| {
-            let mut hsh = HashMap::new();
+            let mut hsh: HashMap<String, FunctionDeclaration> = HashMap::new();
+            let mut redefinitions = Vec::new();
+            let mut structs = Vec::new();
             for decl in decls.into_iter() {
-                hsh.insert(String::from(&decl.name), decl);
+                match decl {
+                    Declaration::Function(f) if hsh.contains_key(&f.name) => redefinitions.push(f),
+                    Declaration::Function(f) => {
+                        hsh.insert(String::from(&f.name), f);
+                    }
+                    Declaration::Struct(s) => structs.push(s),
+                }
+            }
+            Program {
+                functions: hsh,
+                redefinitions,
+                structs,
             }
-            Program { functions: hsh }
         },
     )(i)
 }
 
 #[cfg(test)]
 mod test {
+    use crate::syntax::Span;
     use std::collections::HashMap;
 
     use crate::syntax::{
@@ -48,7 +81,9 @@ mod test {
         assert_eq!(
             prg,
             Program {
-                functions: HashMap::new()
+                functions: HashMap::new(),
+                redefinitions: Vec::new(),
+                structs: Vec::new()
             }
         );
     }
@@ -62,13 +97,21 @@ mod test {
         fn_hash.insert(
             String::from("main"),
             FunctionDeclaration {
+                span: Span::default(),
                 name: String::from("main"),
-                return_type: String::from("int"),
+                return_type: String::from("void"),
                 block: Block::new(),
                 args: ArgumentList::default(),
             },
         );
-        assert_eq!(prg, Program { functions: fn_hash })
+        assert_eq!(
+            prg,
+            Program {
+                functions: fn_hash,
+                redefinitions: Vec::new(),
+                structs: Vec::new()
+            }
+        )
     }
 
     #[test]
@@ -80,8 +123,9 @@ mod test {
         fn_hash.insert(
             String::from("main"),
             FunctionDeclaration {
+                span: Span::default(),
                 name: String::from("main"),
-                return_type: String::from("int"),
+                return_type: String::from("void"),
                 block: Block::new(),
                 args: ArgumentList::default(),
             },
@@ -89,12 +133,38 @@ mod test {
         fn_hash.insert(
             String::from("hello"),
             FunctionDeclaration {
+                span: Span::default(),
                 name: String::from("hello"),
-                return_type: String::from("int"),
+                return_type: String::from("void"),
                 block: Block::new(),
                 args: ArgumentList::default(),
             },
         );
-        assert_eq!(prg, Program { functions: fn_hash })
+        assert_eq!(
+            prg,
+            Program {
+                functions: fn_hash,
+                redefinitions: Vec::new(),
+                structs: Vec::new()
+            }
+        )
+    }
+
+    #[test]
+    fn program_struct() {
+        let (rest, prg) = program("struct Point { int x; int y; }\nfn main() {}").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(prg.functions.len(), 1);
+        assert_eq!(prg.structs.len(), 1);
+        assert_eq!(prg.structs[0].name, "Point");
+    }
+
+    #[test]
+    fn program_redefined_function() {
+        let (rest, prg) = program("fn main() {}\nfn main() -> int {\n    return 1;\n}").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(prg.functions["main"].return_type, "void");
+        assert_eq!(prg.redefinitions.len(), 1);
+        assert_eq!(prg.redefinitions[0].return_type, "int");
     }
 }
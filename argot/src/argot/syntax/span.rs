@@ -0,0 +1,77 @@
+use std::ops::Range;
+
+/// Region of the source a syntax node was parsed from.
+///
+/// Parsers only ever see the input that remains to be parsed, so a span records the length of
+/// the remaining input at the start and at the end of the node. Those lengths are turned into
+/// byte offsets with [`Span::range`] once the full source is known.
+///
+/// Spans always compare equal, so that syntax trees can be compared structurally.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Span {
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    /// Creates a span from the length of the remaining input before and after a node.
+    pub fn new(remaining_before: usize, remaining_after: usize) -> Span {
+        debug_assert!(remaining_before >= remaining_after);
+        Span {
+            start: remaining_before,
+            end: remaining_after,
+        }
+    }
+
+    /// Creates an empty span at the start of some remaining input.
+    pub fn at(remaining: &str) -> Span {
+        Span::new(remaining.len(), remaining.len())
+    }
+
+    /// Returns a span covering both spans and everything in between.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// Returns the byte range of the span in the source it was parsed from.
+    ///
+    /// # Examples
+    /// ```
+    /// use argot::syntax::Span;
+    ///
+    /// let source = "int a = 3;";
+    /// let span = Span::new("a = 3;".len(), " = 3;".len());
+    /// assert_eq!(&source[span.range(source)], "a");
+    /// ```
+    pub fn range(&self, source: &str) -> Range<usize> {
+        let start = source.len().saturating_sub(self.start);
+        let end = source.len().saturating_sub(self.end);
+        start..end
+    }
+}
+
+impl PartialEq for Span {
+    fn eq(&self, _other: &Span) -> bool {
+        true
+    }
+}
+
+/// Syntax nodes that know where they were parsed from.
+pub trait Spanned {
+    fn span(&self) -> Span;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Span;
+
+    #[test]
+    fn span_join() {
+        let source = "1 + 2 * 3";
+        let lhs = Span::new(source.len(), source.len() - 1);
+        let rhs = Span::new(5, 0);
+
+        assert_eq!(&source[lhs.to(rhs).range(source)], source);
+        assert_eq!(&source[rhs.range(source)], "2 * 3");
+    }
+}
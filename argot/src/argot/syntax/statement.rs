@@ -9,11 +9,14 @@ use nom::{
 
 use crate::{
     syntax::{
-        common::whitespace,
+        common::{keyword, spanned, whitespace},
         expression::expression,
+        for_loop::{for_loop, ForLoop},
         if_expr::{if_expression, IfExpression},
+        span::Span,
         types::{Expression, VariableAssignment, VariableDeclaration},
         var_decl::{variable_assignment, variable_declaration},
+        while_loop::{while_loop, WhileLoop},
     },
     visitor::{Visitable, Visitor},
 };
@@ -22,8 +25,12 @@ use crate::{
 pub enum Statement {
     VarDecl(VariableDeclaration),
     VarAssign(VariableAssignment),
-    Return(Option<Expression>),
+    Return(Option<Expression>, Span),
     IfExpression(IfExpression),
+    WhileLoop(WhileLoop),
+    ForLoop(ForLoop),
+    Break(Span),
+    Continue(Span),
     Expr(Expression),
 }
 
@@ -33,18 +40,29 @@ impl Visitable for Statement {
     }
 }
 
+/// Parses a statement that must be terminated by a semicolon, without the semicolon.
+pub fn simple_statement(i: &str) -> IResult<&str, Statement> {
+    alt((
+        map(
+            spanned(preceded(tag("return"), opt(expression))),
+            |(value, span)| Statement::Return(value, span),
+        ),
+        map(spanned(keyword("break")), |(_, span)| {
+            Statement::Break(span)
+        }),
+        map(spanned(keyword("continue")), |(_, span)| {
+            Statement::Continue(span)
+        }),
+        map(variable_declaration, Statement::VarDecl),
+        map(variable_assignment, Statement::VarAssign),
+        map(expression, Statement::Expr),
+    ))(i)
+}
+
 fn semicolon_statement(i: &str) -> IResult<&str, Statement> {
     delimited(
         whitespace,
-        terminated(
-            alt((
-                map(preceded(tag("return"), opt(expression)), Statement::Return),
-                map(variable_declaration, Statement::VarDecl),
-                map(variable_assignment, Statement::VarAssign),
-                map(expression, Statement::Expr),
-            )),
-            char(';'),
-        ),
+        terminated(simple_statement, preceded(whitespace, char(';'))),
         whitespace,
     )(i)
 }
@@ -52,7 +70,11 @@ fn semicolon_statement(i: &str) -> IResult<&str, Statement> {
 pub fn block_statement(i: &str) -> IResult<&str, Statement> {
     delimited(
         whitespace,
-        map(if_expression, Statement::IfExpression),
+        alt((
+            map(if_expression, Statement::IfExpression),
+            map(while_loop, Statement::WhileLoop),
+            map(for_loop, Statement::ForLoop),
+        )),
         whitespace,
     )(i)
 }
@@ -63,10 +85,12 @@ pub fn statement(i: &str) -> IResult<&str, Statement> {
 
 #[cfg(test)]
 mod tests {
+    use crate::syntax::Span;
 
     use super::statement;
     use crate::syntax::types::{
-        Atom, AtomicExpression, Expression, Factor, Statement, Term, VariableDeclaration,
+        ArithmeticExpression, Atom, AtomicExpression, Expression, Factor, Statement, Term,
+        VariableDeclaration,
     };
 
     #[test]
@@ -76,6 +100,8 @@ mod tests {
         assert_eq!(
             stmt,
             Statement::VarDecl(VariableDeclaration {
+                array_length: None,
+                span: Span::default(),
                 name: String::from("i"),
                 expression: None,
                 var_type: String::from("int")
@@ -90,22 +116,55 @@ mod tests {
         assert_eq!(
             stmt,
             Statement::VarDecl(VariableDeclaration {
+                array_length: None,
+                span: Span::default(),
                 name: String::from("i"),
                 var_type: String::from("int"),
-                expression: Some(Expression {
+                expression: Some(Expression::from(ArithmeticExpression {
+                    span: Span::default(),
                     root_term: Term {
+                        span: Span::default(),
                         root_factor: Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
                             atom: Atom::Integer(3),
                             trailers: Vec::new()
                         }),
                         trail: Vec::new(),
                     },
                     trail: Vec::new()
-                })
+                }))
             })
         );
     }
 
+    #[test]
+    fn statement_break_continue() {
+        assert_eq!(
+            statement("break;").unwrap(),
+            ("", Statement::Break(Span::default()))
+        );
+        assert_eq!(
+            statement("continue ;").unwrap(),
+            ("", Statement::Continue(Span::default()))
+        );
+        assert!(matches!(
+            statement("breaking = 3;").unwrap().1,
+            Statement::VarAssign(_)
+        ));
+    }
+
+    #[test]
+    fn statement_loops() {
+        assert!(matches!(
+            statement("while (true) {}").unwrap().1,
+            Statement::WhileLoop(_)
+        ));
+        assert!(matches!(
+            statement("for (;;) {}").unwrap().1,
+            Statement::ForLoop(_)
+        ));
+    }
+
     #[test]
     fn bad_statement() {
         assert!(statement("asd askdjaks asd;").is_err());
@@ -0,0 +1,97 @@
+use nom::{
+    character::complete::char,
+    combinator::map,
+    multi::many0,
+    sequence::{delimited, preceded, terminated, tuple},
+    IResult,
+};
+
+use crate::syntax::{
+    common::{keyword, spanned, whitespace},
+    span::{Span, Spanned},
+    var_decl::{identifier, type_name},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructField {
+    pub field_type: String,
+    pub name: String,
+    pub span: Span,
+}
+
+impl Spanned for StructField {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Declaration of a record type, such as `struct Point { int x; int y; }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructDeclaration {
+    pub name: String,
+    pub fields: Vec<StructField>,
+    pub span: Span,
+}
+
+impl Spanned for StructDeclaration {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+fn struct_field(i: &str) -> IResult<&str, StructField> {
+    map(
+        spanned(tuple((type_name, identifier))),
+        |((field_type, name), span)| StructField {
+            field_type: String::from(field_type),
+            name: String::from(name),
+            span,
+        },
+    )(i)
+}
+
+pub fn struct_declaration(i: &str) -> IResult<&str, StructDeclaration> {
+    map(
+        tuple((
+            spanned(preceded(keyword("struct"), identifier)),
+            delimited(
+                preceded(whitespace, char('{')),
+                many0(terminated(struct_field, preceded(whitespace, char(';')))),
+                preceded(whitespace, char('}')),
+            ),
+        )),
+        |((name, span), fields)| StructDeclaration {
+            name: String::from(name),
+            fields,
+            span,
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::struct_declaration;
+
+    #[test]
+    fn struct_decl() {
+        let (rest, decl) =
+            struct_declaration("struct Point {\n    int x;\n    int[] y ;\n}").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(decl.name, "Point");
+        assert_eq!(decl.fields.len(), 2);
+        assert_eq!(decl.fields[1].field_type, "int[]");
+        assert_eq!(decl.fields[1].name, "y");
+    }
+
+    #[test]
+    fn empty_struct() {
+        let (rest, decl) = struct_declaration("struct Unit {}").unwrap();
+        assert_eq!(rest, "");
+        assert!(decl.fields.is_empty());
+    }
+
+    #[test]
+    fn missing_field_semicolon() {
+        assert!(struct_declaration("struct Point { int x }").is_err());
+    }
+}
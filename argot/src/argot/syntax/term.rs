@@ -6,9 +6,10 @@ use nom::{
 };
 
 use crate::syntax::{
-    common::whitespace,
+    common::{spanned, whitespace},
     factor::{factor, Factor},
     operator::{factor_operator, FactorOperator},
+    span::{Span, Spanned},
 };
 use crate::visitor::{Visitable, Visitor};
 
@@ -16,6 +17,13 @@ use crate::visitor::{Visitable, Visitor};
 pub struct Term {
     pub root_factor: Factor,
     pub trail: Vec<(FactorOperator, Factor)>,
+    pub span: Span,
+}
+
+impl Spanned for Term {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for Term {
@@ -27,17 +35,19 @@ impl Visitable for Term {
 pub fn term(i: &str) -> IResult<&str, Term> {
     let t = delimited(
         whitespace,
-        tuple((factor, many0(tuple((factor_operator, factor))))),
+        spanned(tuple((factor, many0(tuple((factor_operator, factor)))))),
         whitespace,
     );
-    map(t, |(root_fct, lst)| Term {
+    map(t, |((root_fct, lst), span)| Term {
         root_factor: root_fct,
         trail: lst,
+        span,
     })(i)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::syntax::Span;
 
     use super::{term, Factor, FactorOperator, Term};
     use crate::syntax::types::{Atom, AtomicExpression, UnaryOperator};
@@ -49,9 +59,11 @@ mod tests {
         assert_eq!(
             t,
             Term {
+                span: Span::default(),
                 root_factor: Factor::Unary(
                     UnaryOperator::Minus,
                     Box::new(Factor::Atomic(AtomicExpression {
+                        span: Span::default(),
                         atom: Atom::Integer(26),
                         trailers: Vec::new()
                     }))
@@ -68,9 +80,11 @@ mod tests {
         assert_eq!(
             t,
             Term {
+                span: Span::default(),
                 root_factor: Factor::Unary(
                     UnaryOperator::Minus,
                     Box::new(Factor::Atomic(AtomicExpression {
+                        span: Span::default(),
                         atom: Atom::Integer(26),
                         trailers: Vec::new()
                     }))
@@ -79,6 +93,7 @@ mod tests {
                     (
                         FactorOperator::Mult,
                         Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
                             atom: Atom::Integer(42),
                             trailers: Vec::new()
                         })
@@ -88,6 +103,7 @@ mod tests {
                         Factor::Unary(
                             UnaryOperator::Minus,
                             Box::new(Factor::Atomic(AtomicExpression {
+                                span: Span::default(),
                                 atom: Atom::Integer(3),
                                 trailers: Vec::new()
                             }))
@@ -1,12 +1,63 @@
-use nom::{combinator::map, IResult};
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::map,
+    sequence::{delimited, preceded},
+    IResult,
+};
 
-use crate::syntax::{argument_list::argument_list, types::ArgumentList};
+use crate::syntax::{
+    argument_list::argument_list,
+    common::whitespace,
+    expression::expression,
+    types::{ArgumentList, Expression},
+    var_decl::identifier,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Trailer {
     ArgumentList(ArgumentList),
+
+    /// Array indexing, such as `[i + 1]`.
+    Index(Box<Expression>),
+
+    /// Struct field access, such as `.x`.
+    Field(String),
+}
+
+/// Parses an array index.
+pub fn index(i: &str) -> IResult<&str, Expression> {
+    delimited(
+        preceded(whitespace, char('[')),
+        expression,
+        preceded(whitespace, char(']')),
+    )(i)
+}
+
+/// Parses an array index or a field access, which designate a part of a value.
+pub fn accessor(i: &str) -> IResult<&str, Trailer> {
+    alt((
+        map(index, |e| Trailer::Index(Box::new(e))),
+        map(preceded(preceded(whitespace, char('.')), identifier), |f| {
+            Trailer::Field(String::from(f))
+        }),
+    ))(i)
 }
 
 pub fn trailer(i: &str) -> IResult<&str, Trailer> {
-    map(argument_list, Trailer::ArgumentList)(i)
+    alt((map(argument_list, Trailer::ArgumentList), accessor))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{trailer, Trailer};
+
+    #[test]
+    fn field_trailer() {
+        assert_eq!(
+            trailer(" .x").unwrap(),
+            ("", Trailer::Field(String::from("x")))
+        );
+        assert!(matches!(trailer("[0]").unwrap().1, Trailer::Index(_)));
+    }
 }
@@ -1,11 +1,21 @@
 use nom::{
+    branch::alt,
+    bytes::complete::tag,
     character::complete::{alpha1, char},
-    combinator::{map, opt},
-    sequence::{delimited, preceded, tuple},
+    combinator::{map, opt, recognize},
+    multi::many0,
+    sequence::{delimited, pair, preceded, tuple},
     IResult,
 };
 
-use crate::syntax::{common::whitespace, expression::expression, types::Expression};
+use crate::syntax::{
+    common::{spanned, whitespace},
+    expression::expression,
+    number::integer,
+    span::{Span, Spanned},
+    trailer::accessor,
+    types::{Expression, Trailer},
+};
 
 use crate::visitor::{Visitable, Visitor};
 
@@ -14,6 +24,16 @@ pub struct VariableDeclaration {
     pub var_type: String,
     pub name: String,
     pub expression: Option<Expression>,
+
+    /// Length of the array allocated by a fixed-size array declaration, such as `int[10] a`.
+    pub array_length: Option<i32>,
+    pub span: Span,
+}
+
+impl Spanned for VariableDeclaration {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for VariableDeclaration {
@@ -25,7 +45,17 @@ impl Visitable for VariableDeclaration {
 #[derive(Clone, Debug, PartialEq)]
 pub struct VariableAssignment {
     pub name: String,
+
+    /// Indices and fields designating the part of the variable being assigned, if any.
+    pub trailers: Vec<Trailer>,
     pub expression: Expression,
+    pub span: Span,
+}
+
+impl Spanned for VariableAssignment {
+    fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl Visitable for VariableAssignment {
@@ -42,32 +72,63 @@ pub fn identifier(i: &str) -> IResult<&str, &str> {
     delimited(whitespace, alpha1, whitespace)(i)
 }
 
-pub fn variable_assignment(i: &str) -> IResult<&str, VariableAssignment> {
-    map(tuple((identifier, assign)), |(name, ass)| {
-        VariableAssignment {
-            name: String::from(name),
-            expression: ass,
-        }
-    })(i)
+/// Parses the name of a type, such as `int`, `int[][]` or `Point*`.
+pub fn type_name(i: &str) -> IResult<&str, &str> {
+    delimited(
+        whitespace,
+        recognize(pair(alpha1, many0(alt((tag("[]"), tag("*")))))),
+        whitespace,
+    )(i)
 }
 
-pub fn variable_declaration(i: &str) -> IResult<&str, VariableDeclaration> {
+fn array_length(i: &str) -> IResult<&str, i32> {
+    delimited(char('['), integer, char(']'))(i)
+}
+
+pub fn variable_assignment(i: &str) -> IResult<&str, VariableAssignment> {
     map(
-        tuple((identifier, identifier, opt(assign))),
-        |(var_type, name, ass)| VariableDeclaration {
-            var_type: String::from(var_type),
+        spanned(tuple((identifier, many0(accessor), assign))),
+        |((name, trailers, ass), span)| VariableAssignment {
             name: String::from(name),
+            trailers,
             expression: ass,
+            span,
         },
     )(i)
 }
 
+pub fn variable_declaration(i: &str) -> IResult<&str, VariableDeclaration> {
+    alt((
+        map(
+            spanned(tuple((type_name, array_length, identifier))),
+            |((element_type, length, name), span)| VariableDeclaration {
+                var_type: format!("{}[]", element_type),
+                name: String::from(name),
+                expression: None,
+                array_length: Some(length),
+                span,
+            },
+        ),
+        map(
+            spanned(tuple((type_name, identifier, opt(assign)))),
+            |((var_type, name, ass), span)| VariableDeclaration {
+                var_type: String::from(var_type),
+                name: String::from(name),
+                expression: ass,
+                array_length: None,
+                span,
+            },
+        ),
+    ))(i)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::variable_declaration;
+    use super::{variable_assignment, variable_declaration};
     use crate::syntax::types::{
-        Atom, AtomicExpression, Expression, Factor, Term, VariableDeclaration,
+        ArithmeticExpression, Atom, AtomicExpression, Expression, Factor, Term, VariableDeclaration,
     };
+    use crate::syntax::Span;
 
     #[test]
     fn int_decl() {
@@ -76,9 +137,11 @@ mod tests {
         assert_eq!(
             decl,
             VariableDeclaration {
+                span: Span::default(),
                 var_type: String::from("int"),
                 name: String::from("a"),
-                expression: None
+                expression: None,
+                array_length: None
             }
         );
     }
@@ -90,19 +153,61 @@ mod tests {
         assert_eq!(
             decl,
             VariableDeclaration {
+                span: Span::default(),
                 var_type: String::from("int"),
                 name: String::from("bing"),
-                expression: Some(Expression {
+                expression: Some(Expression::from(ArithmeticExpression {
+                    span: Span::default(),
                     root_term: Term {
+                        span: Span::default(),
                         root_factor: Factor::Atomic(AtomicExpression {
+                            span: Span::default(),
                             atom: Atom::Integer(14),
                             trailers: Vec::new()
                         }),
                         trail: Vec::new()
                     },
                     trail: Vec::new()
-                })
+                })),
+                array_length: None
             }
         )
     }
+
+    #[test]
+    fn array_decl() {
+        let (rest, decl) = variable_declaration("int[][] grid").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(decl.var_type, "int[][]");
+        assert_eq!(decl.array_length, None);
+
+        let (rest, decl) = variable_declaration("bool[16] flags").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(decl.var_type, "bool[]");
+        assert_eq!(decl.array_length, Some(16));
+
+        assert!(variable_declaration("int[3] a = b")
+            .unwrap()
+            .0
+            .starts_with('='));
+    }
+
+    #[test]
+    fn pointer_decl() {
+        let (rest, decl) = variable_declaration("Point*[] points").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(decl.var_type, "Point*[]");
+    }
+
+    #[test]
+    fn indexed_assign() {
+        let (rest, assignment) = variable_assignment("grid[i][2] = 4").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(assignment.name, "grid");
+        assert_eq!(assignment.trailers.len(), 2);
+
+        let (rest, assignment) = variable_assignment("points[0].x = 4").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(assignment.trailers.len(), 2);
+    }
 }
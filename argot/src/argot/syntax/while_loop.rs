@@ -0,0 +1,77 @@
+use nom::{
+    character::complete::char,
+    combinator::map,
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::syntax::{
+    block::{block, Block},
+    common::{keyword, spanned, whitespace},
+    expression::{expression, Expression},
+    span::{Span, Spanned},
+};
+
+use crate::visitor::{Visitable, Visitor};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WhileLoop {
+    pub condition: Expression,
+    pub block: Block,
+    pub span: Span,
+}
+
+impl Spanned for WhileLoop {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Visitable for WhileLoop {
+    fn accept<V: Visitor>(&mut self, visitor: &mut V) -> V::Result {
+        visitor.visit_while_loop(self)
+    }
+}
+
+pub fn while_loop(i: &str) -> IResult<&str, WhileLoop> {
+    map(
+        spanned(tuple((
+            preceded(
+                keyword("while"),
+                delimited(
+                    whitespace,
+                    delimited(char('('), expression, char(')')),
+                    whitespace,
+                ),
+            ),
+            block,
+        ))),
+        |((condition, block), span)| WhileLoop {
+            condition,
+            block,
+            span,
+        },
+    )(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::while_loop;
+    use crate::syntax::{expression::expression, types::Statement};
+
+    #[test]
+    fn while_empty_condition() {
+        assert!(while_loop("while () {}").is_err());
+    }
+
+    #[test]
+    fn while_body() {
+        let (rest, while_expr) = while_loop("while (true) { a = 3; break; }").unwrap();
+        assert_eq!(rest, "");
+
+        assert_eq!(while_expr.condition, expression("true").unwrap().1);
+
+        assert_eq!(while_expr.block.body.len(), 2);
+        assert!(matches!(while_expr.block.body[1], Statement::Break(_)));
+    }
+}
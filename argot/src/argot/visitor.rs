@@ -11,11 +11,15 @@ pub trait Visitor {
     fn visit_factor_operator(&mut self, v: &mut FactorOperator) -> Self::Result;
     fn visit_term(&mut self, v: &mut Term) -> Self::Result;
     fn visit_term_operator(&mut self, v: &mut TermOperator) -> Self::Result;
+    fn visit_comparison_operator(&mut self, v: &mut ComparisonOperator) -> Self::Result;
     fn visit_unary_operator(&mut self, v: &mut UnaryOperator) -> Self::Result;
     fn visit_function_declaration(&mut self, v: &mut FunctionDeclaration) -> Self::Result;
     fn visit_statement(&mut self, v: &mut Statement) -> Self::Result;
     fn visit_variable_declaration(&mut self, v: &mut VariableDeclaration) -> Self::Result;
     fn visit_expression(&mut self, v: &mut Expression) -> Self::Result;
+    fn visit_conjunction(&mut self, v: &mut Conjunction) -> Self::Result;
+    fn visit_comparison(&mut self, v: &mut Comparison) -> Self::Result;
+    fn visit_arithmetic_expression(&mut self, v: &mut ArithmeticExpression) -> Self::Result;
     fn visit_program(&mut self, v: &mut Program) -> Self::Result;
     fn visit_atomic_expression(&mut self, v: &mut AtomicExpression) -> Self::Result;
     fn visit_atom(&mut self, v: &mut Atom) -> Self::Result;
@@ -23,4 +27,7 @@ pub trait Visitor {
     fn visit_variable_assignment(&mut self, v: &mut VariableAssignment) -> Self::Result;
     fn visit_function_call(&mut self, v: &mut FunctionCall) -> Self::Result;
     fn visit_if_expression(&mut self, v: &mut IfExpression) -> Self::Result;
+    fn visit_while_loop(&mut self, v: &mut WhileLoop) -> Self::Result;
+    fn visit_for_loop(&mut self, v: &mut ForLoop) -> Self::Result;
+    fn visit_allocation(&mut self, v: &mut Allocation) -> Self::Result;
 }
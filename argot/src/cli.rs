@@ -1,39 +1,119 @@
 use std::fs;
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
-use clap::Parser;
+use assembler::Assembler;
+
+use clap::{Parser, ValueEnum};
 
 const DEFAULT_OUTPUT_NAME: &str = "a.out";
 
+/// Output of the compiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// Bytecode, written to the output file.
+    Bin,
+
+    /// Assembly, printed to stdout.
+    Asm,
+
+    /// Intermediate representation, printed to stdout.
+    Ir,
+}
+
 #[derive(Parser, Debug)]
 #[clap(version = "0.1.0", author = "William Dussault")]
 pub struct CLIRoot {
     file: PathBuf,
 
-    #[clap(long = "asm")]
+    /// Shorthand for `--emit=asm`.
+    #[clap(long = "asm", conflicts_with = "emit")]
     asm: bool,
 
+    #[clap(long = "emit", value_enum, default_value_t = Emit::Bin)]
+    emit: Emit,
+
+    /// Optimization level, from 0 for no optimizations to 2.
+    #[clap(short = 'O', default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=2))]
+    opt_level: u8,
+
+    /// Size of the largest functions inlined from `-O1`, 0 disabling inlining.
+    #[clap(long = "inline-threshold", default_value_t = argot::DEFAULT_INLINE_THRESHOLD)]
+    inline_threshold: usize,
+
+    /// Print the inlined calls and the removed functions to stderr.
+    #[clap(long = "inline-report")]
+    inline_report: bool,
+
+    /// Run the peephole optimizer on the generated assembly.
+    #[clap(long = "peephole")]
+    peephole: bool,
+
+    /// Print how many instructions each peephole rule removed to stderr. Implies `--peephole`.
+    #[clap(long = "peephole-report")]
+    peephole_report: bool,
+
     output: Option<PathBuf>,
 }
 
 impl CLIRoot {
+    /// Prints the requested reports of the optimizations to stderr.
+    fn print_report(&self, report: &argot::Report) {
+        if self.inline_report {
+            eprint!("{}", report.inlining);
+        }
+        if let (true, Some(stats)) = (self.peephole_report, report.peephole.as_ref()) {
+            eprintln!("{}", stats);
+        }
+    }
+
+    /// Compiles a program to assembly, printing the requested reports.
+    fn compile_asm(
+        &self,
+        source: &str,
+        options: &argot::Options,
+    ) -> Result<String, argot::compiler::CompileError> {
+        let (asm, report) = argot::compile_asm_with_report(source, options)?;
+        self.print_report(&report);
+        Ok(asm)
+    }
+
     pub fn run(&self) -> Result<()> {
         let prg_src = fs::read_to_string(&self.file)?;
+        let report = |e: argot::compiler::CompileError| {
+            anyhow!(e.report(&self.file.to_string_lossy(), &prg_src))
+        };
+
+        let options = argot::Options {
+            opt_level: self.opt_level,
+            inline_threshold: self.inline_threshold,
+            peephole: self.peephole || self.peephole_report,
+        };
 
-        if self.asm {
-            let asm = argot::compile_asm(&prg_src)?;
-            println!("{}", asm);
-        } else {
-            let compiled = argot::compile(&prg_src)?;
+        let emit = if self.asm { Emit::Asm } else { self.emit };
+        match emit {
+            Emit::Bin => {
+                let asm = self.compile_asm(&prg_src, &options).map_err(report)?;
+                let compiled = Assembler::new().assemble(&asm)?;
 
-            let path = match self.output.as_ref() {
-                Some(p) => p.clone(),
-                None => PathBuf::from(DEFAULT_OUTPUT_NAME),
-            };
+                let path = match self.output.as_ref() {
+                    Some(p) => p.clone(),
+                    None => PathBuf::from(DEFAULT_OUTPUT_NAME),
+                };
 
-            fs::write(path, compiled)?;
+                fs::write(path, compiled)?;
+            }
+            Emit::Asm => {
+                let asm = self.compile_asm(&prg_src, &options).map_err(report)?;
+                println!("{}", asm);
+            }
+            Emit::Ir => {
+                let (ir, optimizations) =
+                    argot::compile_ir_with_report(&prg_src, &options).map_err(report)?;
+                self.print_report(&optimizations);
+                print!("{}", ir);
+            }
         }
 
         Ok(())
@@ -11,6 +11,7 @@ fn main() {
 
     let root = cli::CLIRoot::parse();
     if let Err(e) = root.run() {
-        log::error!("{}", e.to_string());
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
 }
@@ -28,4 +28,36 @@ compiler_fts! {
     simple_if,
     if_else,
     stack_fallback,
+    fn_return,
+    while_loop,
+    for_loop,
+    comparison,
+    hello,
+    array,
+    struct_copy,
+}
+
+macro_rules! ir_fts {
+    ($($name:ident,)*) => {
+        mod ir {
+            $(
+                #[test]
+                fn $name() {
+                    const SOURCE: &str = include_str!(concat!("data/", stringify!($name), ".gt"));
+                    const EXPECTED_IR: &str = include_str!(concat!("data/", stringify!($name), ".ir"));
+                    let actual_ir = argot::compile_ir(SOURCE).unwrap().to_string();
+                    assert_eq!(EXPECTED_IR.trim(), actual_ir.trim());
+                }
+            )*
+        }
+    }
+}
+
+ir_fts! {
+    fn_arg,
+    bool_op,
+    for_loop,
+    hello,
+    array,
+    struct_copy,
 }
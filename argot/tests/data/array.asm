@@ -0,0 +1,94 @@
+.data
+s0: .asciiz "Negative array length"
+s1: .asciiz "Index out of bounds"
+.text
+jmp @main
+main:
+ld $8 0x0002
+ld $9 0x0000
+gtq $8 $9
+ld $9 0x0001
+jeq @l3
+ld $9 0x0000
+l3:
+jez $9 @l1
+ld $9 0x0004
+mul $8 $9 $9
+ld $10 0x0004
+add $9 $10 $9
+move $9 $0
+ld $v0 0x0003
+syscall
+move $v0 $9
+sw $8 0($9)
+move $9 $8
+ld $9 0x0001
+ld $10 0x0000
+gtq $9 $10
+ld $10 0x0001
+jeq @l4
+ld $10 0x0000
+l4:
+jez $10 @l0
+lw $10 0($8)
+lt $9 $10
+ld $10 0x0001
+jeq @l5
+ld $10 0x0000
+l5:
+jez $10 @l0
+ld $10 0x0004
+mul $9 $10 $9
+add $8 $9 $9
+lw $10 0($8)
+sw $10 4($9)
+ld $9 0x0001
+ld $10 0x0000
+gtq $9 $10
+ld $10 0x0001
+jeq @l6
+ld $10 0x0000
+l6:
+jez $10 @l0
+lw $10 0($8)
+lt $9 $10
+ld $10 0x0001
+jeq @l7
+ld $10 0x0000
+l7:
+jez $10 @l0
+ld $10 0x0004
+mul $9 $10 $9
+add $8 $9 $8
+lw $8 4($8)
+ld $9 0x0000
+gtq $8 $9
+ld $9 0x0001
+jeq @l8
+ld $9 0x0000
+l8:
+jez $9 @l1
+ld $9 0x0001
+mul $8 $9 $9
+ld $10 0x0004
+add $9 $10 $9
+move $9 $0
+ld $v0 0x0003
+syscall
+move $v0 $9
+sw $8 0($9)
+move $9 $8
+jmp @l2
+l0:
+ld $8 @s1
+move $8 $0
+ld $v0 0x000e
+syscall
+l1:
+ld $8 @s0
+move $8 $0
+ld $v0 0x000e
+syscall
+l2:
+ld $v0 0x0002
+syscall
@@ -2,26 +2,36 @@
 .text
 jmp @main
 main:
-sb $0 0[$ebp]
-sb $0 1[$ebp]
 ld $8 0x0001
-ld $9 0x0001
-and $8 $9
-sb $8 0[$ebp]
+jez $8 @l0
+ld $8 0x0001
+jez $8 @l0
+ld $8 0x0001
+jmp @l1
+l0:
 ld $8 0x0000
+l1:
+ld $9 0x0000
+jez $9 @l2
+jmp @l3
+l2:
 ld $9 0x0001
-or $8 $9
-sb $8 1[$ebp]
-lb $8 0[$ebp]
-lb $9 1[$ebp]
-and $8 $9
-jez $8 @a
-sb $0 0[$ebp]
+jez $9 @l4
+l3:
+ld $9 0x0001
+jmp @l5
+l4:
+ld $9 0x0000
+l5:
+jez $8 @l6
+jez $9 @l6
+ld $8 0x0001
+jmp @l7
+l6:
+ld $8 0x0000
+l7:
+jez $8 @l8
 ld $8 0x0001
-sb $8 0[$ebp]
-popb $0
-a:
-popb $0
-popb $0
+l8:
 ld $v0 0x0002
-syscall
\ No newline at end of file
+syscall
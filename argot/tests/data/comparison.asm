@@ -0,0 +1,21 @@
+.data
+.text
+jmp @main
+main:
+ld $8 0x0003
+ld $9 0x0002
+mul $8 $9 $8
+ld $9 0x0005
+gtq $8 $9
+ld $8 0x0001
+jeq @l0
+ld $8 0x0000
+l0:
+ld $9 0x0000
+neq $8 $9
+ld $8 0x0001
+jeq @l1
+ld $8 0x0000
+l1:
+ld $v0 0x0002
+syscall
@@ -0,0 +1,24 @@
+.data
+.text
+jmp @main
+main:
+ld $8 0x0003
+move $8 $20
+pushw $20
+call @square
+popw $0
+move $v0 $8
+add $20 $8 $8
+ld $v0 0x0002
+syscall
+square:
+lw $8 -12[$ebp]
+jez $8 @l0
+mul $8 $8 $8
+move $8 $v0
+jmp @l1
+l0:
+ld $8 0x0000
+move $8 $v0
+l1:
+ret
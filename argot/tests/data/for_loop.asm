@@ -0,0 +1,18 @@
+.data
+.text
+jmp @main
+main:
+ld $8 0x0000
+ld $9 0x0003
+l0:
+jez $9 @l1
+move $9 $10
+add $8 $10 $10
+move $10 $8
+ld $10 0x0001
+sub $9 $10 $10
+move $10 $9
+jmp @l0
+l1:
+ld $v0 0x0002
+syscall
@@ -0,0 +1,36 @@
+.data
+s0: .asciiz "hello"
+s1: .asciiz " world"
+s2: .asciiz "true"
+s3: .asciiz "false"
+.text
+jmp @main
+main:
+ld $8 @s0
+move $8 $0
+ld $v0 0x0001
+syscall
+ld $8 @s1
+move $8 $0
+ld $v0 0x0001
+syscall
+ld $8 0x000a
+move $8 $0
+ld $v0 0x000c
+syscall
+ld $8 0x0001
+jez $8 @l0
+ld $8 @s2
+jmp @l1
+l0:
+ld $8 @s3
+l1:
+move $8 $0
+ld $v0 0x0001
+syscall
+ld $8 0x000a
+move $8 $0
+ld $v0 0x000c
+syscall
+ld $v0 0x0002
+syscall
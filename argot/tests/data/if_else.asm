@@ -3,17 +3,11 @@
 jmp @main
 main:
 ld $8 0x0001
-jez $8 @a
-sw $0 0[$ebp]
+jez $8 @l0
 ld $8 0x002a
-sw $8 0[$ebp]
-popw $0
-jmp @b
-a:
-sw $0 0[$ebp]
+jmp @l1
+l0:
 ld $8 0x0018
-sw $8 0[$ebp]
-popw $0
-b:
+l1:
 ld $v0 0x0002
-syscall
\ No newline at end of file
+syscall
@@ -3,11 +3,8 @@
 jmp @main
 main:
 ld $8 0x0001
-jez $8 @a
-sw $0 0[$ebp]
+jez $8 @l0
 ld $8 0x0003
-sw $8 0[$ebp]
-popw $0
-a:
+l0:
 ld $v0 0x0002
-syscall
\ No newline at end of file
+syscall
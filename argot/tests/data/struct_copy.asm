@@ -0,0 +1,82 @@
+.data
+s0: .asciiz "Negative array length"
+s1: .asciiz "Index out of bounds"
+.text
+jmp @main
+main:
+sw $0 6[$ebp]
+ld $8 0x0000
+sw $8 0[$ebp]
+ld $8 0x0000
+sb $8 4[$ebp]
+ld $8 0x0003
+sw $8 0[$ebp]
+lw $1 0[$ebp]
+sw $1 5[$ebp]
+lb $1 4[$ebp]
+sb $1 9[$ebp]
+lw $1 5[$ebp]
+pushw $1
+lb $1 9[$ebp]
+pushb $1
+call @show
+popw $0
+popb $0
+popw $0
+popw $0
+popb $0
+popb $0
+ld $v0 0x0002
+syscall
+show:
+ld $8 0x0001
+ld $9 0x0000
+gtq $8 $9
+ld $9 0x0001
+jeq @l3
+ld $9 0x0000
+l3:
+jez $9 @l1
+ld $9 0x0005
+mul $8 $9 $9
+ld $10 0x0004
+add $9 $10 $9
+move $9 $0
+ld $v0 0x0003
+syscall
+move $v0 $9
+sw $8 0($9)
+move $9 $8
+ld $9 0x0000
+ld $10 0x0000
+gtq $9 $10
+ld $10 0x0001
+jeq @l4
+ld $10 0x0000
+l4:
+jez $10 @l0
+lw $10 0($8)
+lt $9 $10
+ld $10 0x0001
+jeq @l5
+ld $10 0x0000
+l5:
+jez $10 @l0
+ld $10 0x0005
+mul $9 $10 $9
+add $8 $9 $8
+lb $9 -9[$ebp]
+sb $9 8($8)
+jmp @l2
+l0:
+ld $8 @s1
+move $8 $0
+ld $v0 0x000e
+syscall
+l1:
+ld $8 @s0
+move $8 $0
+ld $v0 0x000e
+syscall
+l2:
+ret
@@ -0,0 +1,17 @@
+.data
+.text
+jmp @main
+main:
+ld $8 0x0003
+l0:
+jez $8 @l1
+ld $9 0x0001
+sub $8 $9 $9
+move $9 $8
+ld $9 0x0001
+sub $8 $9 $9
+jez $9 @l1
+jmp @l0
+l1:
+ld $v0 0x0002
+syscall
@@ -0,0 +1,255 @@
+use argot::compiler::CompileError;
+
+/// Compiles a program that must fail, returning the error and its (line, column, length).
+fn compile_error(source: &str) -> (CompileError, (usize, usize, usize)) {
+    let error = argot::compile_asm(source).unwrap_err();
+    let location = error.location(source).expect("error has no location");
+    (error, (location.line, location.column, location.length))
+}
+
+#[test]
+fn unknown_identifier() {
+    let (error, location) = compile_error("fn main() {\n    int a = 3;\n    a = b + 1;\n}");
+    assert_eq!(error.to_string(), "Unknown identifier: b");
+    assert_eq!(location, (3, 9, 1));
+}
+
+#[test]
+fn operator_type_mismatch() {
+    let (error, location) = compile_error("fn main() {\n    int a = 3 * 2 + true;\n}");
+    assert_eq!(error.to_string(), "Type mismatch: 'bool' and 'int'");
+    assert_eq!(location, (2, 13, 12));
+}
+
+#[test]
+fn argument_type_mismatch() {
+    let (error, location) =
+        compile_error("fn main() {\n    foo(1, 2);\n}\nfn foo(int a, bool b) {}");
+    assert_eq!(error.to_string(), "Type mismatch: 'int' and 'bool'");
+    assert_eq!(location, (2, 12, 1));
+}
+
+#[test]
+fn argument_redefinition() {
+    let (error, location) = compile_error("fn main() {}\nfn foo(int a, bool a) {}");
+    assert_eq!(error.to_string(), "Variable 'a' is already defined");
+    assert_eq!(location, (2, 15, 6));
+}
+
+#[test]
+fn missing_semicolon() {
+    let (error, location) = compile_error("fn main() {\n    int a = 3\n    a = 4;\n}");
+    assert!(matches!(
+        error.to_string().as_ref(),
+        "Missing ';' after statement"
+    ));
+    assert_eq!(location, (2, 14, 1));
+}
+
+#[test]
+fn trailing_input() {
+    let (error, location) = compile_error("fn main() {}\n\n}}");
+    assert_eq!(error.to_string(), "Invalid syntax");
+    assert_eq!(location, (3, 1, 2));
+}
+
+#[test]
+fn rejected_identifier() {
+    let (error, location) = compile_error("fn main() {}\nfn f1() -> int {\n    return 1;\n}");
+    assert_eq!(error.to_string(), "Invalid syntax");
+    assert_eq!(location, (2, 4, 2));
+
+    let (error, location) = compile_error("fn main() {\n    int a1 = 3;\n}");
+    assert_eq!(error.to_string(), "Invalid syntax");
+    assert_eq!(location, (2, 9, 2));
+}
+
+#[test]
+fn missing_entry_point() {
+    let error = argot::compile_asm("fn hello() {}").unwrap_err();
+    assert!(error.span().is_none());
+    assert_eq!(
+        error.report("hello.gt", "fn hello() {}"),
+        "Missing entry point: no 'main' function is defined\n --> hello.gt"
+    );
+}
+
+#[test]
+fn return_type_mismatch() {
+    let (error, location) = compile_error("fn main() {}\nfn foo() -> int {\n    return true;\n}");
+    assert_eq!(error.to_string(), "Type mismatch: 'bool' and 'int'");
+    assert_eq!(location, (3, 12, 4));
+}
+
+#[test]
+fn missing_return_value() {
+    let (error, location) = compile_error("fn main() {}\nfn foo() -> int {\n    return;\n}");
+    assert_eq!(error.to_string(), "Type mismatch: 'void' and 'int'");
+    assert_eq!(location, (3, 5, 6));
+}
+
+#[test]
+fn not_all_paths_return() {
+    let (error, location) = compile_error(
+        "fn main() {}\nfn foo(bool b) -> int {\n    if (b) {\n        return 1;\n    }\n}",
+    );
+    assert_eq!(error.to_string(), "Not all paths return a value");
+    assert_eq!(location, (2, 1, 21));
+}
+
+#[test]
+fn void_call_in_expression() {
+    let (error, location) = compile_error("fn main() {\n    int a = foo();\n}\nfn foo() {}");
+    assert_eq!(error.to_string(), "Type mismatch: 'void' and 'int'");
+    assert_eq!(location, (2, 5, 13));
+}
+
+#[test]
+fn break_outside_loop() {
+    let (error, location) = compile_error("fn main() {\n    if (true) {\n        break;\n    }\n}");
+    assert_eq!(error.to_string(), "'break' outside of a loop");
+    assert_eq!(location, (3, 9, 5));
+}
+
+#[test]
+fn comparison_type_mismatch() {
+    let (error, location) = compile_error("fn main() {\n    bool a = 1 + 2 == true;\n}");
+    assert_eq!(error.to_string(), "Type mismatch: 'bool' and 'int'");
+    assert_eq!(location, (2, 14, 13));
+}
+
+#[test]
+fn ordering_bool() {
+    let (error, location) = compile_error("fn main() {\n    bool a = true < false;\n}");
+    assert_eq!(error.to_string(), "Operator is not defined for type 'bool'");
+    assert_eq!(location, (2, 14, 12));
+}
+
+#[test]
+fn logical_operand_mismatch() {
+    let (error, location) = compile_error("fn main() {\n    bool a = true && 3;\n}");
+    assert_eq!(error.to_string(), "Type mismatch: 'int' and 'bool'");
+    assert_eq!(location, (2, 14, 9));
+}
+
+#[test]
+fn builtin_redefinition() {
+    let (error, location) = compile_error("fn main() {}\nfn print(int a) {}");
+    assert_eq!(
+        error.to_string(),
+        "Function 'print' is a builtin and cannot be redefined"
+    );
+    assert_eq!(location, (2, 1, 15));
+}
+
+#[test]
+fn function_redefinition() {
+    let (error, location) =
+        compile_error("fn main() {}\nfn foo() {}\nfn main() {\n    println(1);\n}");
+    assert_eq!(
+        error.to_string(),
+        "Function 'main' is defined multiple times"
+    );
+    assert_eq!(location, (3, 1, 9));
+}
+
+#[test]
+fn print_arguments() {
+    let (error, location) = compile_error("fn main() {\n    print(\"a\", \"b\");\n}");
+    assert_eq!(error.to_string(), "Invalid arguments");
+    assert_eq!(location, (2, 5, 15));
+}
+
+#[test]
+fn index_not_an_array() {
+    let (error, location) = compile_error("fn main() {\n    int a = 3;\n    int b = a[0];\n}");
+    assert_eq!(error.to_string(), "Values of type 'int' cannot be indexed");
+    assert_eq!(location, (3, 13, 4));
+}
+
+#[test]
+fn index_type_mismatch() {
+    let (error, location) = compile_error("fn main() {\n    int[3] a;\n    a[true] = 1;\n}");
+    assert_eq!(error.to_string(), "Type mismatch: 'bool' and 'int'");
+    assert_eq!(location, (3, 7, 4));
+}
+
+#[test]
+fn element_type_mismatch() {
+    let (error, location) = compile_error("fn main() {\n    bool[] a = new int[3];\n}");
+    assert_eq!(error.to_string(), "Type mismatch: 'int[]' and 'bool[]'");
+    assert_eq!(location, (2, 5, 21));
+}
+
+#[test]
+fn unknown_field() {
+    let (error, location) = compile_error(
+        "struct Point {\n    int x;\n}\nfn main() {\n    Point p;\n    int a = p.y;\n}",
+    );
+    assert_eq!(error.to_string(), "Unknown field: 'Point' has no field 'y'");
+    assert_eq!(location, (6, 13, 3));
+}
+
+#[test]
+fn recursive_struct() {
+    let (error, location) =
+        compile_error("struct A {\n    B b;\n}\nstruct B {\n    A a;\n}\nfn main() {}");
+    assert_eq!(error.to_string(), "Struct 'A' contains itself");
+    assert_eq!(location, (1, 1, 8));
+}
+
+#[test]
+fn struct_as_value() {
+    let (error, location) =
+        compile_error("struct Point {\n    int x;\n}\nfn main() {\n    Point p;\n    print(p);\n}");
+    assert_eq!(
+        error.to_string(),
+        "Values of type 'Point' can only be copied"
+    );
+    assert_eq!(location, (6, 11, 1));
+}
+
+#[test]
+fn struct_return_type() {
+    let (error, location) = compile_error(
+        "struct Point {\n    int x;\n}\nfn main() {}\nfn origin() -> Point {\n    Point p;\n    return p;\n}",
+    );
+    assert_eq!(
+        error.to_string(),
+        "Functions cannot return values of type 'Point'"
+    );
+    assert_eq!(location, (5, 1, 20));
+}
+
+#[test]
+fn struct_redefinition() {
+    let (error, location) = compile_error("struct int {}\nfn main() {}");
+    assert_eq!(error.to_string(), "Type 'int' is already defined");
+    assert_eq!(location, (1, 1, 10));
+}
+
+#[test]
+fn argument_count() {
+    let (error, location) = compile_error(
+        "fn main() {\n    foo(1);\n}\nfn foo(int a, bool[] b) -> int {\n    return a;\n}",
+    );
+    assert_eq!(
+        error.to_string(),
+        "Invalid arguments: 'foo' has type 'fn(int, bool[]) -> int'"
+    );
+    assert_eq!(location, (2, 5, 6));
+}
+
+#[test]
+fn unknown_type() {
+    let (error, location) = compile_error("fn main() {\n    Point*[] points;\n}");
+    assert_eq!(error.to_string(), "Unknown type: 'Point'");
+    assert_eq!(location, (2, 5, 15));
+}
+
+#[test]
+fn field_of_pointer_to_scalar() {
+    let (error, location) = compile_error("fn main() {\n    int* a = new int;\n    a.x = 3;\n}");
+    assert_eq!(error.to_string(), "Values of type 'int*' have no fields");
+    assert_eq!(location, (3, 5, 7));
+}
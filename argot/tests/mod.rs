@@ -1 +1,3 @@
 mod compiler;
+mod diagnostics;
+mod runtime;
@@ -0,0 +1,480 @@
+use instructor::Opcode;
+use vm::{SharedBuffer, Status, Trap, VMError, VM};
+
+/// Runs a program to completion, returning the value in `$v0` after each `ret`.
+fn returned_values(source: &str) -> Vec<i32> {
+    let bytecode = argot::compile(source).unwrap();
+
+    let mut vm = VM::new();
+    vm.load_bytecode(bytecode).unwrap();
+
+    let mut values = Vec::new();
+    loop {
+        let is_ret = vm.program().get(vm.pc).map(|b| Opcode::from(*b)) == Some(Opcode::RET);
+        if vm.run_once().unwrap() != Status::Running {
+            break;
+        }
+        if is_ret {
+            values.push(vm.registers()[instructor::SYSCALL_REGISTER]);
+        }
+    }
+
+    assert!(vm.stack().is_empty(), "stack was not unwound");
+    values
+}
+
+/// Runs a program to completion, returning what it printed.
+fn output(source: &str) -> String {
+    output_with(source, &argot::Options::default())
+}
+
+/// Runs a program compiled with options to completion, returning what it printed.
+fn output_with(source: &str, options: &argot::Options) -> String {
+    let bytecode = argot::compile_with(source, options).unwrap();
+    let output = SharedBuffer::new();
+
+    let mut vm = VM::new();
+    vm.load_bytecode(bytecode).unwrap();
+    vm.console_mut().set_output(output.clone());
+    assert_eq!(vm.run().unwrap(), Status::Halted);
+
+    output.to_string_lossy()
+}
+
+#[test]
+fn return_value() {
+    let values = returned_values(
+        "fn main() {\n    int a = sub(10, 3);\n}\nfn sub(int a, int b) -> int {\n    return a - b;\n}",
+    );
+    assert_eq!(values, vec![7]);
+}
+
+#[test]
+fn early_return() {
+    let values = returned_values(
+        "fn main() {\n    pick(true);\n    pick(false);\n}\n\
+         fn pick(bool b) -> int {\n    int y = 3;\n    if (b) {\n        int x = 1;\n        return x;\n    }\n    return y;\n}",
+    );
+    assert_eq!(values, vec![1, 3]);
+}
+
+#[test]
+fn call_in_expression() {
+    let values = returned_values(
+        "fn main() {\n    int a = 4;\n    id(a + add(2, 3) * 2);\n}\n\
+         fn add(int a, int b) -> int {\n    return a + b;\n}\n\
+         fn id(int x) -> int {\n    return x;\n}",
+    );
+    assert_eq!(values, vec![5, 14]);
+}
+
+#[test]
+fn recursion() {
+    let values = returned_values(
+        "fn main() {\n    fact(5);\n}\n\
+         fn fact(int n) -> int {\n    if (n) {\n        return n * fact(n - 1);\n    }\n    return 1;\n}",
+    );
+    assert_eq!(values.last(), Some(&120));
+}
+
+#[test]
+fn while_loop() {
+    let values = returned_values(
+        "fn main() {\n    sum(4);\n}\n\
+         fn sum(int n) -> int {\n    int total = 0;\n    while (n) {\n        total = total + n;\n        n = n - 1;\n    }\n    return total;\n}",
+    );
+    assert_eq!(values, vec![10]);
+}
+
+#[test]
+fn for_loop_continue() {
+    let values = returned_values(
+        "fn main() {\n    skip();\n}\n\
+         fn skip() -> int {\n    int total = 0;\n    for (int i = 5; i; i = i - 1) {\n        int x = i;\n        if (x - 3) {\n            total = total + x;\n        } else {\n            continue;\n        }\n    }\n    return total;\n}",
+    );
+    assert_eq!(values, vec![12]);
+}
+
+#[test]
+fn for_loop_break() {
+    let values = returned_values(
+        "fn main() {\n    count();\n}\n\
+         fn count() -> int {\n    int total = 0;\n    for (;;) {\n        int y = 1;\n        total = total + y;\n        if (total - 4) {} else {\n            break;\n        }\n    }\n    return total;\n}",
+    );
+    assert_eq!(values, vec![4]);
+}
+
+#[test]
+fn return_from_loop() {
+    let values = returned_values(
+        "fn main() {\n    find();\n}\n\
+         fn find() -> int {\n    for (int i = 0; true; i = i + 1) {\n        int x = i * 2;\n        while (true) {\n            if (x - 6) {\n                break;\n            }\n            return i;\n        }\n    }\n    return 0;\n}",
+    );
+    assert_eq!(values, vec![3]);
+}
+
+#[test]
+fn comparisons() {
+    let values = returned_values(
+        "fn main() {\n    cmp(1, 1);\n    cmp(2, 1);\n    cmp(1, 2);\n}\n\
+         fn cmp(int a, int b) -> int {\n    int r = 0;\n\
+         if (a == b) { r = r + 1; }\n    if (a != b) { r = r + 2; }\n\
+         if (a > b) { r = r + 4; }\n    if (a < b) { r = r + 8; }\n\
+         if (a >= b) { r = r + 16; }\n    if (a <= b) { r = r + 32; }\n\
+         return r;\n}",
+    );
+    assert_eq!(values, vec![49, 22, 42]);
+}
+
+#[test]
+fn bool_equality() {
+    let values = returned_values(
+        "fn main() {\n    same(true, false);\n    same(false, false);\n}\n\
+         fn same(bool x, bool y) -> bool {\n    return x == y;\n}",
+    );
+    assert_eq!(values, vec![0, 1]);
+}
+
+#[test]
+fn comparison_loop() {
+    let values = returned_values(
+        "fn main() {\n    count();\n}\n\
+         fn count() -> int {\n    int total = 0;\n    for (int i = 0; i < 5; i = i + 1) {\n        total = total + i * 2;\n    }\n    return total;\n}",
+    );
+    assert_eq!(values, vec![20]);
+}
+
+#[test]
+fn short_circuit() {
+    // Skipped calls do not return, so they do not show up in the returned values.
+    let values = returned_values(
+        "fn main() {\n    bool a = no() && yes();\n    bool b = yes() || no();\n    bool c = yes() && no() || yes();\n}\n\
+         fn yes() -> bool {\n    return true;\n}\n\
+         fn no() -> bool {\n    return false;\n}",
+    );
+    assert_eq!(values, vec![0, 1, 1, 0, 1]);
+}
+
+#[test]
+fn logical_precedence() {
+    let values = returned_values(
+        "fn main() {\n    check();\n    grouped();\n}\n\
+         fn check() -> bool {\n    return true || true && false;\n}\n\
+         fn grouped() -> bool {\n    return (true || true) && 1 > 2;\n}",
+    );
+    assert_eq!(values, vec![1, 0]);
+}
+
+#[test]
+fn function_names_like_labels() {
+    let source = "fn main() {\n    println(f(3));\n    a();\n    l();\n}\n\
+                  fn f(int n) -> int {\n    int total = 0;\n    while (n > 0) {\n        if (n == 2) {\n            total = total + 10;\n        } else {\n            total = total + n;\n        }\n        n = n - 1;\n    }\n    return total;\n}\n\
+                  fn a() {\n    for (int i = 0; i < 2; i = i + 1) {\n        println(b(i));\n    }\n}\n\
+                  fn b(int x) -> int {\n    if (x > 0) {\n        return x;\n    }\n    return 7;\n}\n\
+                  fn l() {\n    println(\"l\");\n}";
+    assert_eq!(output(source), "14\n7\n1\nl\n");
+}
+
+#[test]
+fn hello_world() {
+    assert_eq!(
+        output("fn main() {\n    println(\"hello world\");\n}"),
+        "hello world\n"
+    );
+}
+
+#[test]
+fn print_values() {
+    let source = "fn main() {\n    string s = \"x = \";\n    show(s, 42);\n    print(1 > 2);\n    println();\n    print(\"tab\\tend\\n\");\n}\n\
+                  fn show(string label, int value) {\n    print(label);\n    println(value * -1);\n}";
+    assert_eq!(output(source), "x = -42\nfalse\ntab\tend\n");
+}
+
+#[test]
+fn arrays() {
+    let source = "fn main() {\n    int[4] squares;\n    for (int i = 0; i < len(squares); i = i + 1) {\n        squares[i] = i * i;\n    }\n    println(sum(squares));\n\n    bool[] flags = new bool[2];\n    flags[1] = true;\n    print(flags[0]);\n    println(flags[1]);\n}\n\
+                  fn sum(int[] values) -> int {\n    int total = 0;\n    for (int i = 0; i < len(values); i = i + 1) {\n        total = total + values[i];\n    }\n    return total;\n}";
+    assert_eq!(output(source), "14\nfalsetrue\n");
+}
+
+#[test]
+fn nested_arrays() {
+    let source = "fn main() {\n    int[][] grid = new int[][2];\n    grid[1] = new int[3];\n    grid[1][2] = 7;\n    println(grid[1][2] + len(grid[1]));\n}";
+    assert_eq!(output(source), "10\n");
+}
+
+#[test]
+fn structs() {
+    let source = "struct Point {\n    int x;\n    int y;\n}\n\
+                  struct Segment {\n    Point a;\n    bool visible;\n    Point b;\n}\n\
+                  fn main() {\n    Point p;\n    p.x = 3;\n    p.y = 4;\n    Point q = p;\n    q.x = 10;\n    println(p.x);\n\n    Segment s;\n    s.a = p;\n    s.b = q;\n    s.visible = true;\n    println(width(s));\n    println(s.visible);\n\n    shift(p);\n    println(p.x);\n}\n\
+                  fn width(Segment s) -> int {\n    return s.b.x - s.a.x;\n}\n\
+                  fn shift(Point p) {\n    p.x = 100;\n    println(p.x + p.y);\n}";
+    assert_eq!(output(source), "3\n7\ntrue\n104\n3\n");
+}
+
+#[test]
+fn struct_arrays() {
+    let source = "struct Point {\n    int x;\n    int y;\n}\n\
+                  fn main() {\n    Point p;\n    p.x = 10;\n    Point[] points = new Point[3];\n    points[1] = p;\n    points[2].y = 7;\n    println(points[1].x + points[2].y + points[0].x);\n}";
+    assert_eq!(output(source), "17\n");
+}
+
+#[test]
+fn pointers() {
+    let source = "struct Point {\n    int x;\n    int y;\n}\n\
+                  struct Node {\n    int value;\n    Node* next;\n}\n\
+                  fn main() {\n    Point* p = new Point;\n    p.x = 3;\n    bump(p);\n    bump(p);\n    println(p.x + p.y);\n\n    Node* head = new Node;\n    head.next = new Node;\n    head.next.value = 7;\n    println(head.next.value);\n}\n\
+                  fn bump(Point* p) {\n    p.x = p.x + 1;\n}";
+    assert_eq!(output(source), "5\n7\n");
+}
+
+#[test]
+fn values_live_across_calls() {
+    let source = "fn main() {\n    int a = 2;\n    int b = 3;\n    int c = add(a, b);\n    println(fib(10) + a * 100 + b * 10 + c);\n}\n\
+                  fn add(int x, int y) -> int {\n    return x + y;\n}\n\
+                  fn fib(int n) -> int {\n    if (n < 2) {\n        return n;\n    }\n    return fib(n - 1) + fib(n - 2);\n}";
+    assert_eq!(output(source), "290\n");
+}
+
+#[test]
+fn spilled_values() {
+    // More variables are live at once than there are registers.
+    let names: Vec<String> = (0..40)
+        .map(|i| format!("v{}{}", (b'a' + i / 26) as char, (b'a' + i % 26) as char))
+        .collect();
+    let declarations: String = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| format!("    int {} = id({});\n", name, i))
+        .collect();
+    let source = format!(
+        "fn main() {{\n{}    println({});\n}}\nfn id(int x) -> int {{\n    return x;\n}}",
+        declarations,
+        names.join(" + ")
+    );
+    assert_eq!(output(&source), "780\n");
+}
+
+#[test]
+fn optimization_levels() {
+    let sources = [
+        "fn main() {\n    int a = 4 - 3;\n    bool big = a * 8 > 100000;\n    if (big == false) {\n        println(a * 8 + 0);\n    }\n    if (!big && 2 * 3 == 6) {\n        println(-a * 16);\n    }\n    println(70000 * 2);\n}",
+        "fn main() {\n    int n = twice(5);\n    while (!(n == 0)) {\n        n = n - 1;\n    }\n    println(n + 4 * twice(n + 3));\n}\n\
+         fn twice(int x) -> int {\n    return x * 2;\n}",
+        "fn main() {\n    int[4] squares;\n    for (int i = 0; i < len(squares); i = i + 1) {\n        squares[i] = i * i * 1;\n    }\n    println(squares[3] * 4 + squares[2] / 1);\n}",
+    ];
+    for source in sources.iter() {
+        let expected = output(source);
+        for opt_level in 1..=2 {
+            let options = argot::Options {
+                opt_level,
+                ..argot::Options::default()
+            };
+            assert_eq!(
+                output_with(source, &options),
+                expected,
+                "at -O{}",
+                opt_level
+            );
+        }
+    }
+}
+
+#[test]
+fn large_constants() {
+    let source =
+        "fn main() {\n    println(70000 * 2);\n    println(0 - 5);\n    println(3 - 100000);\n}";
+    for opt_level in 0..=2 {
+        let options = argot::Options {
+            opt_level,
+            ..argot::Options::default()
+        };
+        assert_eq!(output_with(source, &options), "140000\n-5\n-99997\n");
+    }
+}
+
+#[test]
+fn peephole() {
+    let sources = [
+        "fn main() {\n    int total = 0;\n    for (int i = 0; i < 5; i = i + 1) {\n        total = total + add(i, i);\n    }\n    println(total);\n    greet();\n}\n\
+         fn add(int a, int b) -> int {\n    return a + b;\n}\n\
+         fn greet() {\n    println(\"hi\");\n}",
+        "fn main() {\n    int x = 3;\n    x = x;\n    while (false) {\n        x = 2;\n    }\n    println(x);\n}",
+    ];
+    for source in sources.iter() {
+        let expected = output(source);
+        for opt_level in 0..=2 {
+            let options = argot::Options {
+                opt_level,
+                peephole: true,
+                ..argot::Options::default()
+            };
+            assert_eq!(
+                output_with(source, &options),
+                expected,
+                "at -O{}",
+                opt_level
+            );
+        }
+    }
+
+    // The jump to `main` is removed when it is the first function.
+    let options = argot::Options {
+        peephole: true,
+        ..argot::Options::default()
+    };
+    let (_, report) = argot::compile_asm_with_report(sources[1], &options).unwrap();
+    assert_eq!(report.peephole.unwrap().removed("jump-to-next"), 1);
+}
+
+#[test]
+fn inlining() {
+    let sources = [
+        // Struct arguments are copied.
+        "struct Point {\n    int x;\n    int y;\n}\n\
+         fn main() {\n    Point p;\n    p.x = 3;\n    p.y = 4;\n    shift(p);\n    println(p.x + norm(p));\n}\n\
+         fn shift(Point p) {\n    p.x = 100;\n    println(p.x + p.y);\n}\n\
+         fn norm(Point p) -> int {\n    return p.x * p.x + p.y * p.y;\n}",
+        // Early returns, calls in loops and nested calls.
+        "fn main() {\n    int total = 0;\n    for (int i = 0; i < 6; i = i + 1) {\n        total = total + clamp(i * 3, 2, 10) + twice(i);\n    }\n    println(total);\n    println(fib(10));\n}\n\
+         fn clamp(int x, int low, int high) -> int {\n    if (x < low) {\n        return low;\n    }\n    if (x > high) {\n        return high;\n    }\n    return x;\n}\n\
+         fn twice(int x) -> int {\n    return x + x;\n}\n\
+         fn fib(int n) -> int {\n    if (n < 2) {\n        return n;\n    }\n    return fib(n - 1) + twice(fib(n - 2)) - fib(n - 2);\n}",
+    ];
+    for source in sources.iter() {
+        let expected = output(source);
+        for inline_threshold in [0, 10, 100] {
+            let options = argot::Options {
+                opt_level: 1,
+                inline_threshold,
+                ..argot::Options::default()
+            };
+            assert_eq!(
+                output_with(source, &options),
+                expected,
+                "with a threshold of {}",
+                inline_threshold
+            );
+        }
+    }
+
+    let options = argot::Options {
+        opt_level: 1,
+        inline_threshold: 100,
+        ..argot::Options::default()
+    };
+    let (_, report) = argot::compile_asm_with_report(sources[1], &options).unwrap();
+    assert_eq!(
+        report.inlining.to_string(),
+        "inlined 1 call to twice in fib\ninlined 1 call to clamp in main\n\
+         inlined 1 call to twice in main\nremoved clamp\nremoved twice\n"
+    );
+}
+
+/// Runs a program to completion, returning what it printed and the largest size the stack
+/// reached.
+fn output_and_stack_peak(source: &str, options: &argot::Options) -> (String, usize) {
+    let bytecode = argot::compile_with(source, options).unwrap();
+    let output = SharedBuffer::new();
+
+    let mut vm = VM::new();
+    vm.load_bytecode(bytecode).unwrap();
+    vm.console_mut().set_output(output.clone());
+
+    let mut peak = 0;
+    while vm.run_once().unwrap() == Status::Running {
+        peak = peak.max(vm.stack().len());
+    }
+
+    (output.to_string_lossy(), peak)
+}
+
+#[test]
+fn tail_calls() {
+    let source = |depth: usize| {
+        format!(
+            "struct Point {{\n    int x;\n    int y;\n}}\n\
+             fn main() {{\n    println(count({0}, 0));\n    println(even({0}));\n    down({0}, false);\n    Point p;\n    println(walk(p, {0}));\n}}\n\
+             fn count(int n, int acc) -> int {{\n    if (n == 0) {{\n        return acc;\n    }}\n    return count(n - 1, acc + 2);\n}}\n\
+             fn even(int n) -> bool {{\n    if (n == 0) {{\n        return true;\n    }}\n    return odd(n - 1);\n}}\n\
+             fn odd(int n) -> bool {{\n    if (n == 0) {{\n        return false;\n    }}\n    return even(n - 1);\n}}\n\
+             fn down(int n, bool done) {{\n    if (done) {{\n        println(n);\n        return;\n    }}\n    down(n - 1, n == 1);\n}}\n\
+             fn walk(Point p, int n) -> int {{\n    if (n == 0) {{\n        return p.x - p.y;\n    }}\n    p.x = p.x + 3;\n    p.y = p.y + 1;\n    return walk(p, n - 1);\n}}",
+            depth
+        )
+    };
+
+    for opt_level in 0..=2 {
+        let options = argot::Options {
+            opt_level,
+            ..argot::Options::default()
+        };
+        let (shallow, shallow_peak) = output_and_stack_peak(&source(10), &options);
+        assert_eq!(shallow, "20\ntrue\n0\n20\n");
+
+        // The stack does not grow with the depth of the recursion.
+        let (deep, deep_peak) = output_and_stack_peak(&source(10001), &options);
+        assert_eq!(deep, "20002\nfalse\n0\n20002\n", "at -O{}", opt_level);
+        assert_eq!(deep_peak, shallow_peak, "at -O{}", opt_level);
+    }
+
+    // Each frame takes at least 16 bytes, which would not fit in the stack.
+    assert_eq!(
+        output(
+            "fn main() {\n    println(count(600000, 0));\n}\n\
+             fn count(int n, int acc) -> int {\n    if (n == 0) {\n        return acc;\n    }\n    return count(n - 1, acc + 2);\n}"
+        ),
+        "1200000\n"
+    );
+}
+
+#[test]
+fn stack_overflow() {
+    let bytecode = argot::compile(
+        "fn main() {\n    println(sum(1000000));\n}\n\
+         fn sum(int n) -> int {\n    if (n == 0) {\n        return 0;\n    }\n    return n + sum(n - 1);\n}",
+    )
+    .unwrap();
+
+    let mut vm = VM::new();
+    vm.load_bytecode(bytecode).unwrap();
+    match vm.run() {
+        Err(VMError::Fault {
+            source: Trap::StackOverflow,
+            ..
+        }) => {}
+        r => panic!("stack did not overflow: {:?}", r),
+    }
+}
+
+/// Runs a program that must abort, returning the abort message.
+fn abort_message(source: &str) -> String {
+    let bytecode = argot::compile(source).unwrap();
+
+    let mut vm = VM::new();
+    vm.load_bytecode(bytecode).unwrap();
+    match vm.run() {
+        Err(VMError::Fault {
+            source: Trap::Abort { message },
+            ..
+        }) => message,
+        r => panic!("program did not abort: {:?}", r),
+    }
+}
+
+#[test]
+fn array_bounds() {
+    assert_eq!(
+        abort_message("fn main() {\n    int[3] a;\n    a[3] = 1;\n}"),
+        "Index out of bounds"
+    );
+    assert_eq!(
+        abort_message("fn main() {\n    int[3] a;\n    int b = a[0 - 1];\n}"),
+        "Index out of bounds"
+    );
+    assert_eq!(
+        abort_message("fn main() {\n    int[] a = new int[0 - 2];\n}"),
+        "Negative array length"
+    );
+}
@@ -1,12 +1,12 @@
-use std::io;
+use instructor::{
+    Instruction, LabelConverter, Operand, Program, ELIS_HEADER_LENGTH, ELIS_HEADER_PREFIX,
+};
 
-use byteorder::{LittleEndian, WriteBytesExt};
+use snafu::ensure;
 
-use instructor::{Instruction, Operand, Program, ELIS_HEADER_LENGTH, ELIS_HEADER_PREFIX};
-
-use snafu::{ensure, ResultExt, Snafu};
-
-use crate::program_parser;
+use crate::error::*;
+use crate::peephole::{self, Stats};
+use crate::program_parser::{self, ParsedProgram};
 use crate::section::Section;
 use crate::symbol::{Symbol, SymbolTable, SymbolType};
 
@@ -16,59 +16,8 @@ enum AssemblerPhase {
     Second,
 }
 
-#[derive(Debug, Snafu)]
-pub enum AssemblerError {
-    #[snafu(display("{}", message))]
-    ParseError {
-        message: String,
-    },
-
-    #[snafu(display("Label outside of section: {}", label))]
-    LabelOutsideOfSection {
-        label: String,
-    },
-
-    InvalidAsciizDeclaration,
-
-    InvalidReadonlyBlockLength {
-        source: io::Error,
-    },
-
-    InvalidWordDeclaration,
-
-    #[snafu(display("Symbol '{}' defined multiple times", name))]
-    SymbolAlreadyDefined {
-        name: String,
-    },
-
-    #[snafu(display("Unknown directive: {}", name))]
-    UnknownDirective {
-        name: String,
-    },
-
-    #[snafu(display("Unkown Section Header: {}", name))]
-    UnknownSectionHeader {
-        name: String,
-    },
-}
-
 type Result<T> = std::result::Result<T, AssemblerError>;
 
-fn parse_program(src: &str) -> Result<Program> {
-    let (rest, program) =
-        program_parser::program(src).map_err(|_e| AssemblerError::ParseError {
-            message: String::from("Parse Error"),
-        })?;
-
-    if !rest.is_empty() {
-        Err(AssemblerError::ParseError {
-            message: format!("Incomplete parse: {}", rest),
-        })
-    } else {
-        Ok(program)
-    }
-}
-
 #[derive(Debug)]
 pub struct Assembler {
     current_phase: AssemblerPhase,
@@ -78,6 +27,9 @@ pub struct Assembler {
 
     sections: Vec<Section>,
     symbols: SymbolTable,
+
+    peephole: bool,
+    peephole_stats: Option<Stats>,
 }
 
 impl Default for Assembler {
@@ -94,16 +46,19 @@ impl Assembler {
             readonly_block: Vec::new(),
             sections: Vec::new(),
             symbols: SymbolTable::new(),
+            peephole: false,
+            peephole_stats: None,
         }
     }
 
-    fn process_section_header(&mut self, header_name: &str) -> Result<()> {
+    fn process_section_header(&mut self, header_name: &str, location: &Location) -> Result<()> {
         let section = Section::from(header_name);
 
         ensure!(
             section != Section::Unknown,
             UnknownSectionHeaderSnafu {
-                name: String::from(header_name)
+                name: String::from(header_name),
+                position: location.clone()
             }
         );
 
@@ -113,7 +68,7 @@ impl Assembler {
         Ok(())
     }
 
-    fn process_word_directive(&mut self, ins: &Instruction) -> Result<()> {
+    fn process_word_directive(&mut self, ins: &Instruction, location: &Location) -> Result<()> {
         if self.current_phase != AssemblerPhase::First {
             return Ok(());
         }
@@ -128,11 +83,13 @@ impl Assembler {
                 match ins.label_name() {
                     Some(label_name) => {
                         // Got a label name and a word value.
-                        self.symbols
-                            .update_offset(label_name, self.readonly_block.len() as u16);
+                        self.symbols.update_offset(
+                            label_name,
+                            SymbolType::Data,
+                            self.readonly_block.len() as u16,
+                        );
 
-                        // 4 other bytes for the length of the data block.
-                        self.readonly_block.write_i32::<LittleEndian>(*w).unwrap();
+                        self.readonly_block.extend_from_slice(&w.to_le_bytes());
                     }
                     _ => {
                         // Got no label, we can ditch the word
@@ -140,13 +97,47 @@ impl Assembler {
                     }
                 }
             }
-            Some(_) => return Err(AssemblerError::InvalidWordDeclaration),
+            Some(_) => {
+                return InvalidWordDeclarationSnafu {
+                    position: location.clone(),
+                }
+                .fail()
+            }
         }
 
         Ok(())
     }
 
-    fn process_asciiz_directive(&mut self, ins: &Instruction) -> Result<()> {
+    fn process_byte_directive(&mut self, ins: &Instruction, location: &Location) -> Result<()> {
+        if self.current_phase != AssemblerPhase::First {
+            return Ok(());
+        }
+
+        match ins.operand_1.as_ref() {
+            None => {}
+            Some(Operand::Integer(b)) if (0..=0xff).contains(b) => {
+                // Like words, bytes without a label cannot be referenced.
+                if let Some(label_name) = ins.label_name() {
+                    self.symbols.update_offset(
+                        label_name,
+                        SymbolType::Data,
+                        self.readonly_block.len() as u16,
+                    );
+                    self.readonly_block.push(*b as u8);
+                }
+            }
+            Some(_) => {
+                return InvalidByteDeclarationSnafu {
+                    position: location.clone(),
+                }
+                .fail()
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_asciiz_directive(&mut self, ins: &Instruction, location: &Location) -> Result<()> {
         if self.current_phase != AssemblerPhase::First {
             return Ok(());
         }
@@ -161,8 +152,11 @@ impl Assembler {
                     Some(label_name) => {
                         // Got a label name and a string literal.
                         // Let's insert it in the ro table.
-                        self.symbols
-                            .update_offset(label_name, self.readonly_block.len() as u16);
+                        self.symbols.update_offset(
+                            label_name,
+                            SymbolType::Data,
+                            self.readonly_block.len() as u16,
+                        );
 
                         for byte in s.as_bytes() {
                             self.readonly_block.push(*byte)
@@ -177,16 +171,69 @@ impl Assembler {
             }
             Some(_) => {
                 // Operand is not a string.
-                return Err(AssemblerError::InvalidAsciizDeclaration);
+                return InvalidAsciizDeclarationSnafu {
+                    position: location.clone(),
+                }
+                .fail();
             }
         }
 
-        // For asciiz directives, the first operand is the string constant itself.
-        if let Some(Operand::Str(_str_val)) = &ins.operand_1 {
-            // What to do here?
-            // TODO: Might be able to remove this whole block...
-        } else {
-            return Err(AssemblerError::InvalidAsciizDeclaration);
+        Ok(())
+    }
+
+    fn process_instruction(
+        &mut self,
+        instruction: &Instruction,
+        location: &Location,
+        label_offset: u16,
+    ) -> Result<()> {
+        if let Some(name) = instruction.label_name() {
+            // We have a label
+            ensure!(
+                self.current_section.is_some(),
+                LabelOutsideOfSectionSnafu {
+                    label: name.clone(),
+                    position: location.clone()
+                }
+            );
+            ensure!(
+                !self.symbols.has_symbol(name),
+                SymbolAlreadyDefinedSnafu {
+                    name: name.clone(),
+                    position: location.clone()
+                }
+            );
+            let symbol = Symbol::new(String::from(name), SymbolType::Label, label_offset);
+            self.symbols.add(symbol);
+        }
+
+        if let Some(name) = instruction.directive.as_ref() {
+            // We have a directive.
+            if instruction.has_operands() {
+                // Match which directive it is.
+                match name.as_ref() {
+                    "asciiz" => {
+                        // Null-terminated ascii string.
+                        self.process_asciiz_directive(instruction, location)?;
+                    }
+                    "word" => {
+                        self.process_word_directive(instruction, location)?;
+                    }
+                    "byte" => {
+                        self.process_byte_directive(instruction, location)?;
+                    }
+                    _ => {
+                        return UnknownDirectiveSnafu {
+                            name: name.clone(),
+                            position: location.clone(),
+                        }
+                        .fail();
+                    }
+                }
+            } else {
+                // No operands => section header.
+                self.process_section_header(name, location)?;
+            }
         }
 
         Ok(())
@@ -195,82 +242,73 @@ impl Assembler {
     /// Phase one is the assembler pre-processing routine.
     ///
     /// It is mainly tasked with extracting labels and directives.
-    fn phase_one(&mut self, program: &Program) -> Result<()> {
+    /// Errors are collected so every faulty instruction gets reported.
+    fn phase_one(&mut self, src: &str, program: &ParsedProgram, errors: &mut Vec<AssemblerError>) {
         self.current_phase = AssemblerPhase::First;
 
         let mut current_label_offset = 0;
 
-        for instruction in program.instructions.iter() {
-            if let Some(name) = instruction.label_name() {
-                // We have a label
-                ensure!(
-                    self.current_section.is_some(),
-                    LabelOutsideOfSectionSnafu {
-                        label: name.clone()
-                    }
-                );
-                ensure!(
-                    !self.symbols.has_symbol(name),
-                    SymbolAlreadyDefinedSnafu { name: name.clone() }
-                );
-                let symbol =
-                    Symbol::new(String::from(name), SymbolType::Label, current_label_offset);
-                self.symbols.add(symbol);
-            }
-
-            if let Some(name) = instruction.directive.as_ref() {
-                // We have a directive.
-                if instruction.has_operands() {
-                    // Match which directive it is.
-                    match name.as_ref() {
-                        "asciiz" => {
-                            // Null-terminated ascii string.
-                            self.process_asciiz_directive(instruction)?;
-                        }
-                        "word" => {
-                            self.process_word_directive(instruction)?;
-                        }
-                        _ => {
-                            return Err(AssemblerError::UnknownDirective { name: name.clone() });
-                        }
-                    }
-                } else {
-                    // No operands => section header.
-                    self.process_section_header(name)?;
-                }
+        for (instruction, offset) in program.program.instructions.iter().zip(&program.offsets) {
+            let location = Location::new(src, *offset);
+            if let Err(e) = self.process_instruction(instruction, &location, current_label_offset) {
+                errors.push(e);
             }
 
             if let Some(op) = instruction.opcode.as_ref() {
                 current_label_offset += op.width();
             }
         }
-        Ok(())
     }
 
-    fn write_header(&self, program_vector: &mut Vec<u8>) -> Result<()> {
+    /// Ensures every label used as an operand is defined.
+    fn check_labels(&self, src: &str, program: &ParsedProgram, errors: &mut Vec<AssemblerError>) {
+        for (instruction, offset) in program.program.instructions.iter().zip(&program.offsets) {
+            if instruction.opcode.is_none() {
+                continue;
+            }
+
+            let operands = [
+                &instruction.operand_1,
+                &instruction.operand_2,
+                &instruction.operand_3,
+            ];
+            for operand in operands.iter().copied().flatten() {
+                if let Operand::Label(name) = operand {
+                    if self.symbols.offset_of(name).is_none() {
+                        let label_offset = program
+                            .label_offsets
+                            .get(&(*offset, name.clone()))
+                            .unwrap_or(offset);
+                        errors.push(AssemblerError::UndefinedLabel {
+                            name: name.clone(),
+                            position: Location::new(src, *label_offset),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_header(&self, program_vector: &mut Vec<u8>) {
         // 4 bytes for magic number
         program_vector.extend_from_slice(&ELIS_HEADER_PREFIX);
 
         // 4 other bytes for the length of the data block.
-        program_vector
-            .write_u32::<LittleEndian>(self.readonly_block.len() as u32)
-            .context(InvalidReadonlyBlockLengthSnafu)?;
+        program_vector.extend_from_slice(&(self.readonly_block.len() as u32).to_le_bytes());
 
         // Padding the remaining header length.
         for _i in 8..ELIS_HEADER_LENGTH {
             program_vector.push(0_u8);
         }
-
-        Ok(())
     }
 
-    fn phase_two(&mut self, program: &Program) -> Result<Vec<u8>> {
+    fn phase_two(&mut self, program: &Program) -> Vec<u8> {
         self.current_phase = AssemblerPhase::Second;
         let mut compiled_prg = Vec::with_capacity(
             ELIS_HEADER_LENGTH + self.readonly_block.len() + program.instructions.len(),
         );
 
-        self.write_header(&mut compiled_prg)?;
+        self.write_header(&mut compiled_prg);
 
         // Write the ro block to the program.
         compiled_prg.extend_from_slice(&self.readonly_block);
@@ -283,17 +321,50 @@ impl Assembler {
             }
         }
 
-        Ok(compiled_prg)
+        compiled_prg
     }
 
-    pub fn assemble(&mut self, raw: &str) -> Result<Vec<u8>> {
-        match parse_program(raw) {
-            Ok(prog) => {
-                // Actual assembly steps.
-                self.phase_one(&prog)?;
-                self.phase_two(&prog)
-            }
-            Err(e) => Err(e),
+    /// Enables the peephole optimizer on the assembled programs. See [`peephole`].
+    pub fn set_peephole(&mut self, enabled: bool) {
+        self.peephole = enabled;
+    }
+
+    /// Returns the statistics of the peephole optimizer on the last assembled program, if it is
+    /// enabled.
+    pub fn peephole_stats(&self) -> Option<&Stats> {
+        self.peephole_stats.as_ref()
+    }
+
+    /// Returns the symbols declared by the last assembled program.
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// Assembles a program to ELIS bytecode.
+    ///
+    /// On failure, all the errors found in the source are returned along with their location.
+    pub fn assemble(&mut self, raw: &str) -> std::result::Result<Vec<u8>, AssemblerErrors> {
+        let mut parsed = program_parser::program(raw);
+        self.peephole_stats = None;
+        if self.peephole && parsed.errors.is_empty() {
+            let instructions = std::mem::take(&mut parsed.program.instructions);
+            let offsets = std::mem::take(&mut parsed.offsets);
+            let (instructions, offsets, stats) = peephole::optimize_tagged(instructions, offsets);
+            parsed.program.instructions = instructions;
+            parsed.offsets = offsets;
+            self.peephole_stats = Some(stats);
         }
+
+        let mut errors = parsed.errors.clone();
+
+        self.phase_one(raw, &parsed, &mut errors);
+        self.check_labels(raw, &parsed, &mut errors);
+
+        if !errors.is_empty() {
+            errors.sort_by_key(|e| (e.location().line, e.location().column));
+            return Err(AssemblerErrors::new(errors));
+        }
+
+        Ok(self.phase_two(&parsed.program))
     }
 }
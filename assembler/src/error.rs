@@ -0,0 +1,185 @@
+use std::fmt;
+
+use snafu::Snafu;
+
+/// Position of a diagnostic in the assembler source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    /// 1-based line number.
+    pub line: usize,
+
+    /// 1-based column number, in characters.
+    pub column: usize,
+
+    /// The full source line, without its terminator.
+    pub source_line: String,
+}
+
+impl Location {
+    /// Computes the location of a byte offset in a source string.
+    pub fn new(src: &str, offset: usize) -> Location {
+        let offset = offset.min(src.len());
+        let line_start = src[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = src[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(src.len());
+
+        Location {
+            line: src[..line_start].matches('\n').count() + 1,
+            column: src[line_start..offset].chars().count() + 1,
+            source_line: String::from(src[line_start..line_end].trim_end_matches('\r')),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum AssemblerError {
+    #[snafu(display("{}", message))]
+    ParseError { message: String, position: Location },
+
+    #[snafu(display("Label outside of section: {}", label))]
+    LabelOutsideOfSection { label: String, position: Location },
+
+    #[snafu(display("Invalid .asciiz declaration, expected a string"))]
+    InvalidAsciizDeclaration { position: Location },
+
+    #[snafu(display("Invalid .word declaration, expected an integer"))]
+    InvalidWordDeclaration { position: Location },
+
+    #[snafu(display("Invalid .byte declaration, expected an integer from 0 to 255"))]
+    InvalidByteDeclaration { position: Location },
+
+    #[snafu(display("Symbol '{}' defined multiple times", name))]
+    SymbolAlreadyDefined { name: String, position: Location },
+
+    #[snafu(display("Undefined label: {}", name))]
+    UndefinedLabel { name: String, position: Location },
+
+    #[snafu(display("Unknown directive: {}", name))]
+    UnknownDirective { name: String, position: Location },
+
+    #[snafu(display("Unknown opcode: {}", name))]
+    UnknownOpcode { name: String, position: Location },
+
+    #[snafu(display("Unkown Section Header: {}", name))]
+    UnknownSectionHeader { name: String, position: Location },
+}
+
+impl AssemblerError {
+    pub fn location(&self) -> &Location {
+        match self {
+            AssemblerError::ParseError { position, .. }
+            | AssemblerError::LabelOutsideOfSection { position, .. }
+            | AssemblerError::InvalidAsciizDeclaration { position }
+            | AssemblerError::InvalidWordDeclaration { position }
+            | AssemblerError::InvalidByteDeclaration { position }
+            | AssemblerError::SymbolAlreadyDefined { position, .. }
+            | AssemblerError::UndefinedLabel { position, .. }
+            | AssemblerError::UnknownDirective { position, .. }
+            | AssemblerError::UnknownOpcode { position, .. }
+            | AssemblerError::UnknownSectionHeader { position, .. } => position,
+        }
+    }
+
+    /// Formats the error along with its source line, underlining the offending token.
+    ///
+    /// # Examples
+    /// ```
+    /// use assembler::Assembler;
+    ///
+    /// let errors = Assembler::new().assemble(".text\npush $0").unwrap_err();
+    /// assert_eq!(
+    ///     errors.errors()[0].report(),
+    ///     "Unknown opcode: push\n --> 2:1\n  |\n2 | push $0\n  | ^^^^"
+    /// );
+    /// ```
+    pub fn report(&self) -> String {
+        let location = self.location();
+        let line_number = location.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        let token_length = location
+            .source_line
+            .chars()
+            .skip(location.column - 1)
+            .take_while(|c| !c.is_whitespace())
+            .count()
+            .max(1);
+
+        format!(
+            "{}\n{} --> {}\n{} |\n{} | {}\n{} | {}{}",
+            self,
+            &gutter[1..],
+            location,
+            gutter,
+            line_number,
+            location.source_line,
+            gutter,
+            " ".repeat(location.column - 1),
+            "^".repeat(token_length)
+        )
+    }
+}
+
+/// All the errors reported while assembling a program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssemblerErrors {
+    errors: Vec<AssemblerError>,
+}
+
+impl AssemblerErrors {
+    pub(crate) fn new(errors: Vec<AssemblerError>) -> AssemblerErrors {
+        debug_assert!(!errors.is_empty());
+        AssemblerErrors { errors }
+    }
+
+    /// Returns the errors, in source order.
+    pub fn errors(&self) -> &[AssemblerError] {
+        &self.errors
+    }
+}
+
+impl From<AssemblerError> for AssemblerErrors {
+    fn from(e: AssemblerError) -> AssemblerErrors {
+        AssemblerErrors::new(vec![e])
+    }
+}
+
+impl fmt::Display for AssemblerErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reports: Vec<String> = self.errors.iter().map(|e| e.report()).collect();
+        write!(f, "{}", reports.join("\n\n"))
+    }
+}
+
+impl std::error::Error for AssemblerErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::Location;
+
+    #[test]
+    fn location_from_offset() {
+        let src = "ld $0 1\r\n  add $0 $1 $2\nret";
+
+        let loc = Location::new(src, 0);
+        assert_eq!((loc.line, loc.column), (1, 1));
+        assert_eq!(loc.source_line, "ld $0 1");
+
+        let loc = Location::new(src, 11);
+        assert_eq!((loc.line, loc.column), (2, 3));
+        assert_eq!(loc.source_line, "  add $0 $1 $2");
+
+        let loc = Location::new(src, src.len());
+        assert_eq!((loc.line, loc.column), (3, 4));
+        assert_eq!(loc.source_line, "ret");
+    }
+}
@@ -1,14 +1,17 @@
 mod asm;
 mod common;
 mod directive_parser;
+mod error;
 mod instruction_parser;
 mod label_parser;
 mod opcode_parser;
 mod operand_parser;
+pub mod peephole;
 mod program_parser;
 mod section;
 mod symbol;
 
 pub use asm::Assembler;
-pub use asm::AssemblerError;
+pub use error::{AssemblerError, AssemblerErrors, Location};
 pub use instructor::Program;
+pub use symbol::SymbolTable;
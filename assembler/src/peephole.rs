@@ -0,0 +1,359 @@
+//! Peephole optimizer, rewriting short sequences of redundant instructions.
+//!
+//! Each rule of [`RULES`] is tried at every instruction of a program, until no rule applies
+//! anymore. Rules never match sequences that are jumped into, so the instructions they rewrite
+//! always run together.
+use std::fmt;
+
+use instructor::{Instruction, Opcode, Operand};
+
+use crate::error::AssemblerErrors;
+use crate::program_parser;
+
+/// Number of instructions a rule replaces at the start of a sequence, and their replacement, if
+/// the rule applies.
+type Rewrite = Option<(usize, Vec<Instruction>)>;
+
+/// Rewrite of a sequence of instructions.
+pub struct Rule {
+    pub name: &'static str,
+    pub description: &'static str,
+
+    rewrite: fn(&[Instruction]) -> Rewrite,
+}
+
+/// Rules of the optimizer, in the order they are tried.
+pub const RULES: &[Rule] = &[
+    Rule {
+        name: "push-pop",
+        description: "`pushw $a` followed by `popw $a`",
+        rewrite: push_pop,
+    },
+    Rule {
+        name: "push-pop-move",
+        description: "`pushw $a` followed by `popw $b`, replaced by `move $a $b`",
+        rewrite: push_pop_move,
+    },
+    Rule {
+        name: "self-move",
+        description: "`move $a $a`",
+        rewrite: self_move,
+    },
+    Rule {
+        name: "move-back",
+        description: "`move $b $a` right after `move $a $b`",
+        rewrite: move_back,
+    },
+    Rule {
+        name: "store-load",
+        description: "`lw $a x` right after `sw $a x`",
+        rewrite: store_load,
+    },
+    Rule {
+        name: "jump-to-next",
+        description: "`jmp` to the next instruction",
+        rewrite: jump_to_next,
+    },
+];
+
+/// Returns the first two instructions of a sequence, if they are both operations.
+fn pair(sequence: &[Instruction]) -> Option<(&Instruction, &Instruction)> {
+    match sequence {
+        [first, second, ..] if first.opcode.is_some() && second.opcode.is_some() => {
+            Some((first, second))
+        }
+        _ => None,
+    }
+}
+
+fn move_instruction(source: Operand, dest: Operand) -> Instruction {
+    Instruction {
+        opcode: Some(Opcode::MOV),
+        operand_1: Some(source),
+        operand_2: Some(dest),
+        ..Default::default()
+    }
+}
+
+fn push_pop(sequence: &[Instruction]) -> Rewrite {
+    let (push, pop) = pair(sequence)?;
+    let matches = push.opcode == Some(Opcode::PUSHW)
+        && pop.opcode == Some(Opcode::POPW)
+        && push.operand_1 == pop.operand_1;
+    matches.then(|| (2, Vec::new()))
+}
+
+fn push_pop_move(sequence: &[Instruction]) -> Rewrite {
+    let (push, pop) = pair(sequence)?;
+    if push.opcode != Some(Opcode::PUSHW) || pop.opcode != Some(Opcode::POPW) {
+        return None;
+    }
+    let (source, dest) = (push.operand_1.clone()?, pop.operand_1.clone()?);
+    Some((2, vec![move_instruction(source, dest)]))
+}
+
+fn self_move(sequence: &[Instruction]) -> Rewrite {
+    let instruction = sequence.first()?;
+    let matches = instruction.opcode == Some(Opcode::MOV)
+        && instruction.operand_1.is_some()
+        && instruction.operand_1 == instruction.operand_2;
+    matches.then(|| (1, Vec::new()))
+}
+
+fn move_back(sequence: &[Instruction]) -> Rewrite {
+    let (first, second) = pair(sequence)?;
+    let matches = first.opcode == Some(Opcode::MOV)
+        && second.opcode == Some(Opcode::MOV)
+        && first.operand_1 == second.operand_2
+        && first.operand_2 == second.operand_1;
+    matches.then(|| (2, vec![first.clone()]))
+}
+
+fn store_load(sequence: &[Instruction]) -> Rewrite {
+    let (store, load) = pair(sequence)?;
+    let matches = store.opcode == Some(Opcode::SW)
+        && load.opcode == Some(Opcode::LW)
+        && store.operand_1 == load.operand_1
+        && store.operand_2 == load.operand_2;
+    matches.then(|| (2, vec![store.clone()]))
+}
+
+fn jump_to_next(sequence: &[Instruction]) -> Rewrite {
+    let (jump, next) = match sequence {
+        [jump, next, ..] => (jump, next),
+        _ => return None,
+    };
+    let matches = jump.opcode == Some(Opcode::JMP)
+        && matches!(&jump.operand_1, Some(Operand::Label(target)) if next.label.as_ref() == Some(target));
+    matches.then(|| (1, Vec::new()))
+}
+
+/// Number of instructions removed by each rule.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    removed: Vec<usize>,
+}
+
+impl Stats {
+    fn new() -> Stats {
+        Stats {
+            removed: vec![0; RULES.len()],
+        }
+    }
+
+    /// Returns the number of instructions removed by a rule.
+    pub fn removed(&self, rule: &str) -> usize {
+        RULES
+            .iter()
+            .position(|r| r.name == rule)
+            .map(|i| self.removed[i])
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of instructions removed by all rules.
+    pub fn total(&self) -> usize {
+        self.removed.iter().sum()
+    }
+}
+
+/// Formats the statistics as a table, with a line per rule.
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = RULES.iter().map(|r| r.name.len()).max().unwrap_or(0);
+        for (rule, removed) in RULES.iter().zip(self.removed.iter()) {
+            writeln!(f, "{:width$}  {}", rule.name, removed, width = width)?;
+        }
+        write!(f, "{:width$}  {}", "total", self.total(), width = width)
+    }
+}
+
+/// Applies a rule at the start of a sequence, returning how many instructions it replaces and
+/// their replacement.
+///
+/// The label of the first instruction is kept on the replacement, or moved to the instruction
+/// following the replaced ones when it is an operation. Other replaced instructions must not have labels.
+fn apply(rule: &Rule, sequence: &mut [Instruction]) -> Rewrite {
+    let (count, mut replacement) = (rule.rewrite)(sequence)?;
+    if sequence[1..count].iter().any(|i| i.label.is_some()) {
+        return None;
+    }
+
+    if let Some(label) = sequence[0].label.clone() {
+        match replacement.first_mut() {
+            Some(first) if first.label.is_none() => first.label = Some(label),
+            Some(_) => return None,
+            None => match sequence.get_mut(count) {
+                Some(next) if next.opcode.is_some() && next.label.is_none() => {
+                    next.label = Some(label)
+                }
+                _ => return None,
+            },
+        }
+    }
+
+    Some((count, replacement))
+}
+
+/// Optimizes a sequence of instructions, along with a tag per instruction. Replacements get the
+/// tag of the first instruction they replace.
+pub(crate) fn optimize_tagged<T: Copy>(
+    mut instructions: Vec<Instruction>,
+    mut tags: Vec<T>,
+) -> (Vec<Instruction>, Vec<T>, Stats) {
+    debug_assert_eq!(instructions.len(), tags.len());
+    let mut stats = Stats::new();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        let mut optimized = Vec::with_capacity(instructions.len());
+        let mut optimized_tags = Vec::with_capacity(tags.len());
+        let mut i = 0;
+        while i < instructions.len() {
+            let rewrite = RULES.iter().enumerate().find_map(|(index, rule)| {
+                apply(rule, &mut instructions[i..]).map(|rewrite| (index, rewrite))
+            });
+
+            match rewrite {
+                Some((rule, (count, replacement))) => {
+                    stats.removed[rule] += count - replacement.len();
+                    optimized_tags.extend(replacement.iter().map(|_| tags[i]));
+                    optimized.extend(replacement);
+                    i += count;
+                    changed = true;
+                }
+                None => {
+                    optimized.push(instructions[i].clone());
+                    optimized_tags.push(tags[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        instructions = optimized;
+        tags = optimized_tags;
+    }
+
+    (instructions, tags, stats)
+}
+
+/// Optimizes a sequence of instructions, returning the number of instructions removed by each
+/// rule.
+pub fn optimize(instructions: &mut Vec<Instruction>) -> Stats {
+    let tags = vec![(); instructions.len()];
+    let (optimized, _, stats) = optimize_tagged(std::mem::take(instructions), tags);
+    *instructions = optimized;
+    stats
+}
+
+/// Optimizes an assembler source, returning the optimized source and the number of instructions
+/// removed by each rule.
+pub fn optimize_source(src: &str) -> Result<(String, Stats), AssemblerErrors> {
+    let mut parsed = program_parser::program(src);
+    if !parsed.errors.is_empty() {
+        return Err(AssemblerErrors::new(parsed.errors));
+    }
+
+    let stats = optimize(&mut parsed.program.instructions);
+    Ok((parsed.program.to_string(), stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{optimize, optimize_source, Stats, RULES};
+    use crate::program_parser;
+
+    /// Optimizes a program, returning the optimized program and the statistics.
+    fn optimized(source: &str) -> (String, Stats) {
+        let mut program = program_parser::program(source).program;
+        let stats = optimize(&mut program.instructions);
+        (program.to_string(), stats)
+    }
+
+    #[test]
+    fn push_pop() {
+        let (program, stats) = optimized("pushw $1\npopw $1\npushb $2\npopb $2\nret");
+        assert_eq!(program, "pushb $2\npopb $2\nret\n");
+        assert_eq!(stats.removed("push-pop"), 2);
+    }
+
+    #[test]
+    fn push_pop_move() {
+        let (program, stats) = optimized("pushw $1\npopw $v0\nret");
+        assert_eq!(program, "move $1 $v0\nret\n");
+        assert_eq!(stats.removed("push-pop-move"), 1);
+    }
+
+    #[test]
+    fn self_move() {
+        let (program, stats) = optimized("move $3 $3\nmove $3 $4\n");
+        assert_eq!(program, "move $3 $4\n");
+        assert_eq!(stats.removed("self-move"), 1);
+    }
+
+    #[test]
+    fn move_back() {
+        let (program, stats) = optimized("move $3 $4\nmove $4 $3\nmove $4 $5\n");
+        assert_eq!(program, "move $3 $4\nmove $4 $5\n");
+        assert_eq!(stats.removed("move-back"), 1);
+    }
+
+    #[test]
+    fn store_load() {
+        let (program, stats) =
+            optimized("sw $1 4[$ebp]\nlw $1 4[$ebp]\nsw $1 4[$ebp]\nlw $2 4[$ebp]\n");
+        assert_eq!(program, "sw $1 4[$ebp]\nsw $1 4[$ebp]\nlw $2 4[$ebp]\n");
+        assert_eq!(stats.removed("store-load"), 1);
+    }
+
+    #[test]
+    fn jump_to_next() {
+        let (program, stats) = optimized("jmp @a\na: ret\njmp @a\nret");
+        assert_eq!(program, "a: ret\njmp @a\nret\n");
+        assert_eq!(stats.removed("jump-to-next"), 1);
+    }
+
+    #[test]
+    fn labels() {
+        // Instructions that are jumped to are not merged with the previous ones.
+        let (program, stats) = optimized("pushw $1\na: popw $1\njmp @a");
+        assert_eq!(program, "pushw $1\na: popw $1\njmp @a\n");
+        assert_eq!(stats.total(), 0);
+
+        // The label of a removed instruction moves to the next one.
+        let (program, _) = optimized("a: move $1 $1\nret\njmp @a");
+        assert_eq!(program, "a: ret\njmp @a\n");
+
+        // Unless that one has its own label.
+        let (program, _) = optimized("a: jmp @b\nb: ret\njmp @a");
+        assert_eq!(program, "a: jmp @b\nb: ret\njmp @a\n");
+    }
+
+    #[test]
+    fn repeated_passes() {
+        // Removing the moves makes the push and the pop adjacent.
+        let (program, stats) = optimized("pushw $1\nmove $2 $2\npopw $1\nret");
+        assert_eq!(program, "ret\n");
+        assert_eq!(stats.removed("self-move"), 1);
+        assert_eq!(stats.removed("push-pop"), 2);
+    }
+
+    #[test]
+    fn report() {
+        let (_, stats) = optimized("move $1 $1\nmove $2 $2\n");
+        let report = stats.to_string();
+        assert_eq!(report.lines().count(), RULES.len() + 1);
+        assert!(report.contains("self-move      2"), "{}", report);
+        assert!(report.ends_with("total          2"), "{}", report);
+    }
+
+    #[test]
+    fn source() {
+        let (source, stats) = optimize_source(".text\nmove $1 $1\nret\n").unwrap();
+        assert_eq!(source, ".text\nret\n");
+        assert_eq!(stats.total(), 1);
+
+        assert!(optimize_source("mull $1 $2 $3").is_err());
+    }
+}
@@ -1,15 +1,143 @@
-use instructor::Program;
+use std::collections::HashMap;
 
-use nom::{branch::alt, combinator::map, multi::many1, IResult};
+use instructor::{Instruction, Opcode, Operand, Program};
 
+use nom::{branch::alt, Err as NErr, IResult};
+
+use crate::common::whitespace;
 use crate::directive_parser::directive;
+use crate::error::{AssemblerError, Location};
 use crate::instruction_parser as instruction;
 
-pub fn program(i: &str) -> IResult<&str, Program> {
-    map(
-        many1(alt((directive, instruction::instruction))),
-        |instructions| Program { instructions },
-    )(i)
+/// A parsed program, along with the source offset of each instruction.
+#[derive(Debug)]
+pub struct ParsedProgram {
+    pub program: Program,
+    pub offsets: Vec<usize>,
+
+    /// Source offset of each label operand, by offset of its instruction and label name.
+    pub label_offsets: HashMap<(usize, String), usize>,
+
+    pub errors: Vec<AssemblerError>,
+}
+
+fn item(i: &str) -> IResult<&str, Instruction> {
+    alt((directive, instruction::instruction))(i)
+}
+
+/// Returns the offset of the end of the line containing `offset`.
+fn line_end(src: &str, offset: usize) -> usize {
+    src[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or(src.len())
+}
+
+/// Returns the mnemonic of an instruction, skipping its label declaration.
+fn mnemonic(text: &str) -> (usize, &str) {
+    let start = text.find(':').map(|i| i + 1).unwrap_or(0);
+    let trimmed = text[start..].trim_start();
+    let start = text.len() - trimmed.len();
+    let end = trimmed
+        .find(|c: char| !c.is_alphabetic())
+        .unwrap_or(trimmed.len());
+    (start, &trimmed[..end])
+}
+
+/// Returns the offsets of the label operands of an instruction in its source text.
+fn label_operands<'a>(instruction: &'a Instruction, text: &str) -> Vec<(&'a String, usize)> {
+    let operands = [
+        &instruction.operand_1,
+        &instruction.operand_2,
+        &instruction.operand_3,
+    ];
+
+    let mut labels = Vec::new();
+    let mut start = mnemonic(text).0;
+    for operand in operands.iter().copied().flatten() {
+        if let Operand::Label(name) = operand {
+            let reference = format!("@{}", name);
+            if let Some(i) = text[start..].find(&reference) {
+                labels.push((name, start + i));
+                start += i + reference.len();
+            }
+        }
+    }
+    labels
+}
+
+/// Parses a program.
+///
+/// Parsing resumes on the next line after an error, so all errors of the program can be reported at once.
+pub fn program(src: &str) -> ParsedProgram {
+    let mut parsed = ParsedProgram {
+        program: Program {
+            instructions: Vec::new(),
+        },
+        offsets: Vec::new(),
+        label_offsets: HashMap::new(),
+        errors: Vec::new(),
+    };
+
+    let mut rest = src;
+    loop {
+        rest = whitespace(rest).map(|(r, _)| r).unwrap_or(rest);
+        if rest.is_empty() {
+            break;
+        }
+
+        let offset = src.len() - rest.len();
+        let end_of_line = line_end(src, offset);
+
+        let error = match item(rest) {
+            Ok((r, instruction)) => {
+                let text = &rest[..rest.len() - r.len()];
+                let (mnemonic_start, name) = mnemonic(text);
+
+                if instruction.opcode == Some(Opcode::IGL) && name != "igl" {
+                    AssemblerError::UnknownOpcode {
+                        name: String::from(name),
+                        position: Location::new(src, offset + mnemonic_start),
+                    }
+                } else {
+                    for (name, label_offset) in label_operands(&instruction, text) {
+                        parsed
+                            .label_offsets
+                            .insert((offset, name.clone()), offset + label_offset);
+                    }
+                    parsed.program.instructions.push(instruction);
+                    parsed.offsets.push(offset);
+                    rest = r;
+                    continue;
+                }
+            }
+            Err(e) => {
+                let error_offset = match e {
+                    NErr::Error(e) | NErr::Failure(e) => src.len() - e.input.len(),
+                    NErr::Incomplete(_) => offset,
+                }
+                .clamp(offset, end_of_line);
+
+                let message = if error_offset == offset {
+                    "Expected an instruction, a directive or a label"
+                } else if error_offset == end_of_line {
+                    "Missing operand"
+                } else {
+                    "Invalid operand"
+                };
+
+                AssemblerError::ParseError {
+                    message: String::from(message),
+                    position: Location::new(src, error_offset),
+                }
+            }
+        };
+
+        parsed.errors.push(error);
+        rest = src.get(end_of_line + 1..).unwrap_or("");
+    }
+
+    parsed
 }
 
 #[cfg(test)]
@@ -17,6 +145,7 @@ mod tests {
     use instructor::{Instruction, Opcode, Operand, Program};
 
     use super::program;
+    use crate::error::AssemblerError;
 
     #[test]
     fn parse_program() {
@@ -43,9 +172,19 @@ mod tests {
                 },
             ],
         };
-        let (rest, actual_program) = program("ld $0 100\nld $1 25\nadd $0 $1 $2").unwrap();
-        assert_eq!(rest, "");
-        assert_eq!(expected_program, actual_program);
+        let parsed = program("ld $0 100\nld $1 25\nadd $0 $1 $2");
+        assert!(parsed.errors.is_empty());
+        assert_eq!(parsed.offsets, vec![0, 10, 19]);
+        assert_eq!(expected_program, parsed.program);
+    }
+
+    #[test]
+    fn label_offsets() {
+        let parsed = program("start: jmp @start\n  jez $1 @end\nend: ld $0 @start");
+        assert!(parsed.errors.is_empty());
+        assert_eq!(parsed.label_offsets[&(0, String::from("start"))], 11);
+        assert_eq!(parsed.label_offsets[&(20, String::from("end"))], 27);
+        assert_eq!(parsed.label_offsets[&(32, String::from("start"))], 43);
     }
 
     #[test]
@@ -71,8 +210,39 @@ mod tests {
             ],
         };
 
-        let (rest, actual_program) = program(source).unwrap();
-        assert_eq!(rest, "");
-        assert_eq!(expected_program, actual_program);
+        let parsed = program(source);
+        assert!(parsed.errors.is_empty());
+        assert_eq!(expected_program, parsed.program);
+    }
+
+    #[test]
+    fn parse_program_errors() {
+        let parsed = program("ld $0 100\nld 100 $0\npush $0\nld $1\n  \"oops\"\nret");
+
+        let errors: Vec<(String, usize, usize)> = parsed
+            .errors
+            .iter()
+            .map(|e| (e.to_string(), e.location().line, e.location().column))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (String::from("Invalid operand"), 2, 4),
+                (String::from("Unknown opcode: push"), 3, 1),
+                (String::from("Missing operand"), 4, 6),
+                (
+                    String::from("Expected an instruction, a directive or a label"),
+                    5,
+                    3
+                ),
+            ]
+        );
+
+        // Valid lines are still parsed.
+        assert_eq!(parsed.program.instructions.len(), 2);
+        assert!(matches!(
+            parsed.errors[1],
+            AssemblerError::UnknownOpcode { .. }
+        ));
     }
 }
@@ -1,33 +1,43 @@
 use instructor::LabelConverter;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SymbolType {
+    /// Label of an instruction, its offset is relative to the program text.
     Label,
+
+    /// Label of a constant, its offset is relative to the ro block.
+    Data,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Symbol {
     name: String,
     offset: u16,
-    //symbol_type: SymbolType,
+    symbol_type: SymbolType,
 }
 
 impl Symbol {
-    pub fn new(name: String, _symbol_type: SymbolType, offset: u16) -> Symbol {
+    pub fn new(name: String, symbol_type: SymbolType, offset: u16) -> Symbol {
         Symbol {
             name,
-            //symbol_type,
+            symbol_type,
             offset,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SymbolTable {
     // TODO: Maybe use hashmap here if perf. is an issue.
     symbols: Vec<Symbol>,
 }
 
+impl Default for SymbolTable {
+    fn default() -> SymbolTable {
+        SymbolTable::new()
+    }
+}
+
 impl SymbolTable {
     pub fn new() -> SymbolTable {
         SymbolTable {
@@ -39,14 +49,23 @@ impl SymbolTable {
         self.symbols.push(s)
     }
 
-    pub fn update_offset(&mut self, symbol_name: &str, offset: u16) {
+    pub fn update_offset(&mut self, symbol_name: &str, symbol_type: SymbolType, offset: u16) {
         for symbol in self.symbols.iter_mut() {
             if symbol.name == symbol_name {
+                symbol.symbol_type = symbol_type;
                 symbol.offset = offset;
             }
         }
     }
 
+    /// Returns the names and program text offsets of instruction labels.
+    pub fn labels(&self) -> impl Iterator<Item = (&str, u16)> {
+        self.symbols
+            .iter()
+            .filter(|s| s.symbol_type == SymbolType::Label)
+            .map(|s| (s.name.as_str(), s.offset))
+    }
+
     pub fn has_symbol(&self, s: &str) -> bool {
         for symbol in self.symbols.iter() {
             if symbol.name == s {
@@ -85,4 +104,15 @@ mod tests {
 
         assert!(sym.offset_of("nonexistent").is_none());
     }
+
+    #[test]
+    fn test_labels() {
+        let mut sym = SymbolTable::new();
+        sym.add(Symbol::new(String::from("text"), SymbolType::Label, 4));
+        sym.add(Symbol::new(String::from("data"), SymbolType::Label, 0));
+        sym.update_offset("data", SymbolType::Data, 8);
+
+        assert_eq!(sym.labels().collect::<Vec<_>>(), vec![("text", 4)]);
+        assert_eq!(sym.offset_of("data"), Some(8));
+    }
 }
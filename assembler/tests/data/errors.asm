@@ -0,0 +1,10 @@
+.data
+w: .word "nope"
+.text
+start: ld $0 10
+ld $1 20
+start: add $0 $1 $2
+    mull $0 $1 $2
+jmp @finish
+inc
+jez $1 @nowhere
@@ -29,4 +29,78 @@ mod tests {
         let actual_asm = Assembler::new().assemble(SOURCE).unwrap();
         assert_eq!(actual_asm, EXPECTED_ASM);
     }
+
+    #[test]
+    pub fn ft_byte_directive() {
+        const SOURCE: &str = ".data\nsmall: .byte 7\nlarge: .byte 255\n.text\nlcw $0 @large";
+
+        let actual_asm = Assembler::new().assemble(SOURCE).unwrap();
+        assert_eq!(actual_asm[4..8], [2, 0, 0, 0]);
+        assert_eq!(actual_asm[instructor::ELIS_HEADER_LENGTH..][..2], [7, 255]);
+
+        let errors = Assembler::new()
+            .assemble(".data\nb: .byte 256\n.text")
+            .unwrap_err();
+        assert_eq!(
+            errors.errors()[0].to_string(),
+            "Invalid .byte declaration, expected an integer from 0 to 255"
+        );
+    }
+
+    #[test]
+    pub fn ft_errors() {
+        const SOURCE: &str = include_str!("./data/errors.asm");
+
+        let errors = Assembler::new().assemble(SOURCE).unwrap_err();
+        let actual: Vec<(String, usize, usize)> = errors
+            .errors()
+            .iter()
+            .map(|e| (e.to_string(), e.location().line, e.location().column))
+            .collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                (
+                    String::from("Invalid .word declaration, expected an integer"),
+                    2,
+                    1
+                ),
+                (String::from("Symbol 'start' defined multiple times"), 6, 1),
+                (String::from("Unknown opcode: mull"), 7, 5),
+                (String::from("Undefined label: finish"), 8, 5),
+                (String::from("Missing operand"), 9, 4),
+                (String::from("Undefined label: nowhere"), 10, 8),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn ft_error_report() {
+        let errors = Assembler::new()
+            .assemble(".text\nstart: ld $0 12\n  jmp @end")
+            .unwrap_err();
+
+        assert_eq!(
+            errors.to_string(),
+            "Undefined label: end\n --> 3:7\n  |\n3 |   jmp @end\n  |       ^^^^"
+        );
+    }
+
+    #[test]
+    pub fn ft_peephole() {
+        const SOURCE: &str = ".text\npushw $1\npopw $1\nmove $2 $2\njmp @end\nend: ld $0 12\nret\n";
+
+        let mut assembler = Assembler::new();
+        assembler.set_peephole(true);
+        let optimized = assembler.assemble(SOURCE).unwrap();
+        let expected = Assembler::new().assemble(".text\nld $0 12\nret\n").unwrap();
+        assert_eq!(optimized, expected);
+
+        let stats = assembler.peephole_stats().unwrap();
+        assert_eq!(stats.removed("push-pop"), 2);
+        assert_eq!(stats.removed("self-move"), 1);
+        assert_eq!(stats.removed("jump-to-next"), 1);
+        assert_eq!(stats.total(), 4);
+    }
 }
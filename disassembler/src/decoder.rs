@@ -0,0 +1,181 @@
+use std::convert::TryFrom;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use instructor::{Address, Instruction, MemorySection, Opcode, Operand, REGISTER_COUNT};
+
+use snafu::ensure;
+
+use crate::error::*;
+
+/// Encodings of the operands an instruction can take.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OperandKind {
+    /// 1 byte.
+    Register,
+
+    /// 2 bytes, big-endian.
+    Integer,
+
+    /// Register byte, little-endian i32 offset, section byte.
+    Address,
+}
+
+fn operand_layout(opcode: Opcode) -> &'static [OperandKind] {
+    use OperandKind::*;
+
+    match opcode {
+        Opcode::LOAD | Opcode::LCW | Opcode::JEZ => &[Register, Integer],
+        Opcode::ADD | Opcode::SUB | Opcode::MUL | Opcode::DIV => &[Register, Register, Register],
+        Opcode::JMP | Opcode::JMPF | Opcode::JMPB | Opcode::JEQ | Opcode::CALL => &[Integer],
+        Opcode::EQ
+        | Opcode::NEQ
+        | Opcode::GT
+        | Opcode::GTQ
+        | Opcode::LT
+        | Opcode::LTQ
+        | Opcode::SHIFTL
+        | Opcode::SHIFTR
+        | Opcode::AND
+        | Opcode::OR
+        | Opcode::MOV => &[Register, Register],
+        Opcode::INC
+        | Opcode::DEC
+        | Opcode::RJMP
+        | Opcode::PUSHW
+        | Opcode::PUSHB
+        | Opcode::POPW
+        | Opcode::POPB
+        | Opcode::NOT
+        | Opcode::NEG => &[Register],
+        Opcode::SW | Opcode::LW | Opcode::SB | Opcode::LB => &[Register, Address],
+        Opcode::SYSC | Opcode::IGL | Opcode::RET => &[],
+    }
+}
+
+fn decode_register(byte: u8, offset: usize) -> Result<u8> {
+    ensure!(
+        (byte as usize) < REGISTER_COUNT,
+        InvalidRegisterSnafu {
+            offset,
+            register: byte
+        }
+    );
+    Ok(byte)
+}
+
+/// Decodes the instruction starting at `offset` in a program text.
+///
+/// Jump targets are decoded as integers. Returns the instruction along with its width.
+pub fn decode_instruction(text: &[u8], offset: usize) -> Result<(Instruction, usize)> {
+    let byte = *text
+        .get(offset)
+        .ok_or(DisassemblerError::TruncatedInstruction { offset })?;
+    let opcode = Opcode::from(byte);
+    ensure!(opcode as u8 == byte, UnknownOpcodeSnafu { offset, byte });
+
+    let width = opcode.width() as usize;
+    let bytes = text
+        .get(offset..offset + width)
+        .ok_or(DisassemblerError::TruncatedInstruction { offset })?;
+
+    let mut operands = Vec::with_capacity(3);
+    let mut cursor = 1;
+    for kind in operand_layout(opcode) {
+        let operand = match kind {
+            OperandKind::Register => {
+                cursor += 1;
+                Operand::Register(decode_register(bytes[cursor - 1], offset)?)
+            }
+            OperandKind::Integer => {
+                cursor += 2;
+                let value = ((bytes[cursor - 2] as u16) << 8) | bytes[cursor - 1] as u16;
+                Operand::Integer(value as i32)
+            }
+            OperandKind::Address => {
+                cursor += 6;
+                let register = decode_register(bytes[cursor - 6], offset)?;
+                let section = MemorySection::try_from(bytes[cursor - 1]).map_err(|section| {
+                    DisassemblerError::InvalidMemorySection { offset, section }
+                })?;
+                Operand::Address(Address {
+                    register,
+                    offset: LittleEndian::read_i32(&bytes[cursor - 5..cursor - 1]),
+                    section,
+                })
+            }
+        };
+        operands.push(operand);
+    }
+    debug_assert_eq!(cursor, width);
+
+    let mut operands = operands.into_iter();
+    let instruction = Instruction {
+        opcode: Some(opcode),
+        operand_1: operands.next(),
+        operand_2: operands.next(),
+        operand_3: operands.next(),
+        ..Default::default()
+    };
+
+    Ok((instruction, width))
+}
+
+#[cfg(test)]
+mod tests {
+    use instructor::{Address, Opcode, Operand};
+
+    use super::decode_instruction;
+    use crate::error::DisassemblerError;
+
+    #[test]
+    fn decode_load() {
+        let (instruction, width) = decode_instruction(&[1, 3, 0x01, 0xf4], 0).unwrap();
+        assert_eq!(width, 4);
+        assert_eq!(instruction.opcode, Some(Opcode::LOAD));
+        assert_eq!(instruction.operand_1, Some(Operand::Register(3)));
+        assert_eq!(instruction.operand_2, Some(Operand::Integer(500)));
+        assert_eq!(instruction.operand_3, None);
+    }
+
+    #[test]
+    fn decode_address() {
+        let (instruction, width) =
+            decode_instruction(&[19, 24, 2, 34, 0xfc, 0xff, 0xff, 0xff, 0], 1).unwrap();
+        assert_eq!(width, 8);
+        assert_eq!(instruction.opcode, Some(Opcode::SW));
+        assert_eq!(
+            instruction.operand_2,
+            Some(Operand::Address(Address::new_stack(34, -4)))
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            decode_instruction(&[0xff], 0).unwrap_err(),
+            DisassemblerError::UnknownOpcode {
+                offset: 0,
+                byte: 0xff
+            }
+        );
+        assert_eq!(
+            decode_instruction(&[1, 3], 0).unwrap_err(),
+            DisassemblerError::TruncatedInstruction { offset: 0 }
+        );
+        assert_eq!(
+            decode_instruction(&[16, 40], 0).unwrap_err(),
+            DisassemblerError::InvalidRegister {
+                offset: 0,
+                register: 40
+            }
+        );
+        assert_eq!(
+            decode_instruction(&[24, 2, 34, 0, 0, 0, 0, 7], 0).unwrap_err(),
+            DisassemblerError::InvalidMemorySection {
+                offset: 0,
+                section: 7
+            }
+        );
+    }
+}
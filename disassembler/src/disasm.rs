@@ -0,0 +1,218 @@
+use std::collections::BTreeSet;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use instructor::{Instruction, Opcode, Operand, Program, ELIS_HEADER_LENGTH, ELIS_HEADER_PREFIX};
+
+use snafu::ensure;
+
+use crate::decoder::decode_instruction;
+use crate::error::*;
+
+fn code_label(offset: usize) -> String {
+    format!("L{:04x}", offset)
+}
+
+fn data_label(offset: usize) -> String {
+    format!("D{:04x}", offset)
+}
+
+fn section(name: &str) -> Instruction {
+    Instruction {
+        directive: Some(String::from(name)),
+        ..Default::default()
+    }
+}
+
+/// Returns whether a byte can appear in an `.asciiz` literal.
+fn is_printable(b: u8) -> bool {
+    (0x20..0x7f).contains(&b) && b != b'"'
+}
+
+/// Decodes the read-only block into labeled `.asciiz` and `.word` declarations. Bytes that fit
+/// neither are declared one at a time with `.byte`.
+fn decode_data(ro_block: &[u8]) -> Vec<(usize, Instruction)> {
+    let mut declarations = Vec::new();
+
+    let mut offset = 0;
+    while offset < ro_block.len() {
+        let rest = &ro_block[offset..];
+        let printable_run = rest.iter().take_while(|b| is_printable(**b)).count();
+
+        // Short strings padded with nulls up to a word are more likely to be small words.
+        let is_small_word =
+            rest.len() >= 4 && printable_run < 3 && rest[printable_run..4].iter().all(|b| *b == 0);
+
+        let (directive, operand, size) =
+            if printable_run > 0 && rest.get(printable_run) == Some(&0) && !is_small_word {
+                let s = String::from_utf8_lossy(&rest[..printable_run]).into_owned();
+                ("asciiz", Operand::Str(s), printable_run + 1)
+            } else if rest.len() >= 4 && LittleEndian::read_i32(rest) >= 0 {
+                ("word", Operand::Integer(LittleEndian::read_i32(rest)), 4)
+            } else if rest[0] == 0 {
+                ("asciiz", Operand::Str(String::new()), 1)
+            } else {
+                ("byte", Operand::Integer(rest[0] as i32), 1)
+            };
+
+        declarations.push((
+            offset,
+            Instruction {
+                label: Some(data_label(offset)),
+                directive: Some(String::from(directive)),
+                operand_1: Some(operand),
+                ..Default::default()
+            },
+        ));
+        offset += size;
+    }
+
+    declarations
+}
+
+/// Returns the operand holding the absolute program offset an instruction jumps to.
+pub fn jump_target(instruction: &mut Instruction) -> Option<&mut Operand> {
+    match instruction.opcode? {
+        Opcode::JMP | Opcode::JEQ | Opcode::CALL => instruction.operand_1.as_mut(),
+        Opcode::JEZ => instruction.operand_2.as_mut(),
+        _ => None,
+    }
+}
+
+/// Disassembles an ELIS executable.
+///
+/// The resulting program assembles back to the same bytecode. Since symbol names are not
+/// part of the executable, labels are generated from offsets: `Lxxxx` for jump and call
+/// targets in the program text, and `Dxxxx` for constants in the read-only block.
+///
+/// # Examples
+/// ```
+/// let bytecode = assembler::Assembler::new()
+///     .assemble(".data\n.text\nstart: jmp @start")
+///     .unwrap();
+///
+/// let program = disassembler::disassemble(&bytecode).unwrap();
+/// assert_eq!(program.to_string(), ".data\n.text\nL0000: jmp @L0000\n");
+/// ```
+pub fn disassemble(bytecode: &[u8]) -> Result<Program> {
+    ensure!(
+        bytecode.len() >= ELIS_HEADER_LENGTH && bytecode[0..4] == ELIS_HEADER_PREFIX,
+        InvalidHeaderSnafu
+    );
+
+    let ro_size = LittleEndian::read_u32(&bytecode[4..8]) as usize;
+    ensure!(
+        ro_size <= bytecode.len() - ELIS_HEADER_LENGTH,
+        ReadOnlyBlockTooLongSnafu { size: ro_size }
+    );
+
+    let ro_block = &bytecode[ELIS_HEADER_LENGTH..ELIS_HEADER_LENGTH + ro_size];
+    let text = &bytecode[ELIS_HEADER_LENGTH + ro_size..];
+
+    let data = decode_data(ro_block);
+
+    let mut code = Vec::new();
+    let mut offset = 0;
+    while offset < text.len() {
+        let (instruction, width) = decode_instruction(text, offset)?;
+        code.push((offset, instruction));
+        offset += width;
+    }
+
+    // Jump targets that land on an instruction boundary (or the end of the program) get a label.
+    let boundaries: BTreeSet<usize> = code
+        .iter()
+        .map(|(offset, _)| *offset)
+        .chain(std::iter::once(text.len()))
+        .collect();
+
+    let mut targets = BTreeSet::new();
+    for (_, instruction) in code.iter_mut() {
+        if let Some(operand) = jump_target(instruction) {
+            if let Operand::Integer(target) = *operand {
+                if boundaries.contains(&(target as usize)) {
+                    targets.insert(target as usize);
+                    *operand = Operand::Label(code_label(target as usize));
+                }
+            }
+        }
+
+        // Constant loads reference the read-only block. The immediates of `ld` are left as
+        // integers, since they are more often plain numbers than addresses.
+        if instruction.opcode == Some(Opcode::LCW) {
+            if let Some(Operand::Integer(target)) = instruction.operand_2 {
+                if data.iter().any(|(offset, _)| *offset == target as usize) {
+                    instruction.operand_2 = Some(Operand::Label(data_label(target as usize)));
+                }
+            }
+        }
+    }
+
+    for (offset, instruction) in code.iter_mut() {
+        if targets.contains(offset) {
+            instruction.label = Some(code_label(*offset));
+        }
+    }
+
+    let mut instructions = vec![section("data")];
+    instructions.extend(data.into_iter().map(|(_, i)| i));
+    instructions.push(section("text"));
+    instructions.extend(code.into_iter().map(|(_, i)| i));
+
+    if targets.contains(&text.len()) {
+        instructions.push(Instruction {
+            label: Some(code_label(text.len())),
+            ..Default::default()
+        });
+    }
+
+    Ok(Program { instructions })
+}
+
+#[cfg(test)]
+mod tests {
+    use instructor::Operand;
+
+    use super::{decode_data, disassemble};
+    use crate::error::DisassemblerError;
+
+    #[test]
+    fn data_strings() {
+        let data = decode_data(b"hey\0\0");
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].0, 0);
+        assert_eq!(data[0].1.to_string(), "D0000: .asciiz \"hey\"");
+        assert_eq!(data[1].1.operand_1, Some(Operand::Str(String::new())));
+    }
+
+    #[test]
+    fn data_words() {
+        let data = decode_data(&[42, 0, 0, 0]);
+        assert_eq!(data[0].1.to_string(), "D0000: .word 42");
+    }
+
+    #[test]
+    fn data_bytes() {
+        let data = decode_data(&[42, 0, 0, 0, 0xff, 0xff]);
+        let declarations: Vec<String> = data.iter().map(|(_, i)| i.to_string()).collect();
+        assert_eq!(
+            declarations,
+            vec!["D0000: .word 42", "D0004: .byte 255", "D0005: .byte 255"]
+        );
+
+        let data = decode_data(&[b'o', b'k', 1]);
+        let declarations: Vec<String> = data.iter().map(|(_, i)| i.to_string()).collect();
+        assert_eq!(
+            declarations,
+            vec!["D0000: .byte 111", "D0001: .byte 107", "D0002: .byte 1"]
+        );
+    }
+
+    #[test]
+    fn invalid_header() {
+        assert_eq!(
+            disassemble(&[0; 12]).unwrap_err(),
+            DisassemblerError::InvalidHeader
+        );
+    }
+}
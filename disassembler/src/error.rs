@@ -0,0 +1,25 @@
+use snafu::Snafu;
+
+#[derive(Debug, PartialEq, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum DisassemblerError {
+    #[snafu(display("Invalid ELIS header"))]
+    InvalidHeader,
+
+    #[snafu(display("Read-only block of {}b exceeds the program size", size))]
+    ReadOnlyBlockTooLong { size: usize },
+
+    #[snafu(display("Unknown opcode {:#04x} at {:#06x}", byte, offset))]
+    UnknownOpcode { offset: usize, byte: u8 },
+
+    #[snafu(display("Truncated instruction at {:#06x}", offset))]
+    TruncatedInstruction { offset: usize },
+
+    #[snafu(display("Invalid register {} at {:#06x}", register, offset))]
+    InvalidRegister { offset: usize, register: u8 },
+
+    #[snafu(display("Invalid memory section {:#04x} at {:#06x}", section, offset))]
+    InvalidMemorySection { offset: usize, section: u8 },
+}
+
+pub type Result<T> = std::result::Result<T, DisassemblerError>;
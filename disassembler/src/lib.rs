@@ -0,0 +1,7 @@
+mod decoder;
+mod disasm;
+mod error;
+
+pub use decoder::decode_instruction;
+pub use disasm::{disassemble, jump_target};
+pub use error::DisassemblerError;
@@ -0,0 +1,108 @@
+use assembler::Assembler;
+
+fn roundtrip(source: &str) {
+    let bytecode = Assembler::new().assemble(source).unwrap();
+    let disassembled = disassembler::disassemble(&bytecode).unwrap().to_string();
+    let reassembled = Assembler::new().assemble(&disassembled).unwrap();
+    assert_eq!(bytecode, reassembled, "disassembly:\n{}", disassembled);
+}
+
+macro_rules! roundtrip_fts {
+    ($($name:ident => $file:literal,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                roundtrip(include_str!($file));
+            }
+        )*
+    }
+}
+
+roundtrip_fts! {
+    example_add_word => "../../examples/asm/add_word.asm",
+    example_alloc => "../../examples/asm/alloc.asm",
+    example_call => "../../examples/asm/call.asm",
+    example_directive => "../../examples/asm/directive.asm",
+    example_dyn_str => "../../examples/asm/dyn_str.asm",
+    example_echo => "../../examples/asm/echo.asm",
+    example_loop => "../../examples/asm/loop.asm",
+    example_print_int => "../../examples/asm/print_int.asm",
+    example_read_byte => "../../examples/asm/read_byte.asm",
+    example_read_int => "../../examples/asm/read_int.asm",
+    argot_bool_op => "../../argot/tests/data/bool_op.asm",
+    argot_fn_arg => "../../argot/tests/data/fn_arg.asm",
+    argot_if_else => "../../argot/tests/data/if_else.asm",
+    argot_stack_fallback => "../../argot/tests/data/stack_fallback.asm",
+    argot_var_ref => "../../argot/tests/data/var_ref.asm",
+}
+
+#[test]
+fn reconstruct_labels() {
+    const SOURCE: &str = ".data
+hello: .asciiz \"Hello\"
+answer: .word 42
+.text
+main: lcw $1 @answer
+call @proc
+jez $1 @end
+ld $v0 2
+syscall
+proc: ld $0 @hello
+ld $v0 1
+syscall
+ret
+end:";
+
+    let bytecode = Assembler::new().assemble(SOURCE).unwrap();
+    let program = disassembler::disassemble(&bytecode).unwrap();
+    assert_eq!(
+        program.to_string(),
+        ".data
+D0000: .asciiz \"Hello\"
+D0006: .word 42
+.text
+lcw $1 @D0006
+call @L0010
+jez $1 @L001a
+ld $v0 2
+syscall
+L0010: ld $0 0
+ld $v0 1
+syscall
+ret
+L001a:
+"
+    );
+}
+
+#[test]
+fn raw_bytes() {
+    roundtrip(".data\nhi: .asciiz \"hi\"\nodd: .byte 1\nend: .byte 200\n.text\nld $0 @hi\nld $v0 1\nsyscall");
+}
+
+#[test]
+fn load_immediates() {
+    const SOURCE: &str = ".data
+hi: .asciiz \"hi\"
+bye: .asciiz \"bye\"
+.text
+ld $0 @bye
+ld $v0 3
+syscall
+lcw $1 @bye";
+
+    let bytecode = Assembler::new().assemble(SOURCE).unwrap();
+    let program = disassembler::disassemble(&bytecode).unwrap();
+    assert_eq!(
+        program.to_string(),
+        ".data
+D0000: .asciiz \"hi\"
+D0003: .asciiz \"bye\"
+.text
+ld $0 3
+ld $v0 3
+syscall
+lcw $1 @D0003
+"
+    );
+}
@@ -0,0 +1,27 @@
+.data
+prompt: .asciiz "What is your name? "
+greeting: .asciiz "Hello, "
+
+.text
+ld $0 @prompt
+ld $v0 1
+syscall
+
+ld $v0 6
+syscall
+move $v0 $1
+
+ld $0 @greeting
+ld $v0 1
+syscall
+
+move $1 $0
+ld $v0 5
+syscall
+
+ld $v0 4
+move $1 $0
+syscall
+
+ld $v0 2
+syscall
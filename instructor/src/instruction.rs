@@ -1,7 +1,9 @@
+use std::fmt;
+
 use crate::{LabelConverter, Opcode, Operand};
 
 /// A single Slang instruction.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Instruction {
     /// The opcode of this instruction.
     ///
@@ -88,8 +90,50 @@ impl Instruction {
         // Write all instructions to the stream & gather byte count.
         let cur_size = &[&self.operand_1, &self.operand_2, &self.operand_3]
             .iter()
-            .map(|op| Instruction::write_operand(*op, w, converter))
+            .map(|op| Instruction::write_operand(op, w, converter))
             .sum::<usize>();
         debug_assert_eq!(self.opcode.as_ref().unwrap().width() - 1, *cur_size as u16);
     }
 }
+
+/// Formats instructions using the assembler syntax.
+///
+/// # Examples
+/// ```
+/// use instructor::{Instruction, Opcode, Operand};
+///
+/// let instruction = Instruction {
+///     label: Some(String::from("start")),
+///     opcode: Some(Opcode::LOAD),
+///     operand_1: Some(Operand::Register(0)),
+///     operand_2: Some(Operand::Integer(10)),
+///     ..Default::default()
+/// };
+/// assert_eq!(instruction.to_string(), "start: ld $0 10");
+/// ```
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+
+        if let Some(label) = self.label.as_ref() {
+            parts.push(format!("{}:", label));
+        }
+
+        if let Some(directive) = self.directive.as_ref() {
+            parts.push(format!(".{}", directive));
+        }
+
+        if let Some(opcode) = self.opcode {
+            parts.push(String::from(opcode.mnemonic()));
+        }
+
+        for operand in [&self.operand_1, &self.operand_2, &self.operand_3]
+            .iter()
+            .filter_map(|op| op.as_ref())
+        {
+            parts.push(operand.to_string());
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
@@ -121,6 +121,59 @@ pub enum Opcode {
 }
 
 impl Opcode {
+    /// Returns the assembly mnemonic of the opcode.
+    ///
+    /// # Examples
+    /// ```
+    /// use instructor::Opcode;
+    ///
+    /// assert_eq!(Opcode::MOV.mnemonic(), "move");
+    /// assert_eq!(Opcode::from(Opcode::SHIFTL.mnemonic()), Opcode::SHIFTL);
+    /// ```
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::LOAD => "ld",
+            Opcode::ADD => "add",
+            Opcode::SUB => "sub",
+            Opcode::MUL => "mul",
+            Opcode::DIV => "div",
+            Opcode::JMP => "jmp",
+            Opcode::JMPF => "jmpf",
+            Opcode::JMPB => "jmpb",
+            Opcode::EQ => "eq",
+            Opcode::NEQ => "neq",
+            Opcode::GT => "gt",
+            Opcode::LT => "lt",
+            Opcode::GTQ => "gtq",
+            Opcode::LTQ => "ltq",
+            Opcode::JEQ => "jeq",
+            Opcode::INC => "inc",
+            Opcode::DEC => "dec",
+            Opcode::RJMP => "rjmp",
+            Opcode::SYSC => "syscall",
+            Opcode::PUSHW => "pushw",
+            Opcode::POPW => "popw",
+            Opcode::MOV => "move",
+            Opcode::LCW => "lcw",
+            Opcode::SW => "sw",
+            Opcode::LW => "lw",
+            Opcode::SB => "sb",
+            Opcode::LB => "lb",
+            Opcode::CALL => "call",
+            Opcode::RET => "ret",
+            Opcode::NEG => "neg",
+            Opcode::PUSHB => "pushb",
+            Opcode::POPB => "popb",
+            Opcode::NOT => "not",
+            Opcode::SHIFTL => "shl",
+            Opcode::SHIFTR => "shr",
+            Opcode::AND => "and",
+            Opcode::OR => "or",
+            Opcode::JEZ => "jez",
+            Opcode::IGL => "igl",
+        }
+    }
+
     pub fn width(self) -> u16 {
         match self {
             Opcode::LOAD => 4,
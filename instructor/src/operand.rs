@@ -1,8 +1,10 @@
+use std::convert::TryFrom;
+use std::fmt;
 use std::mem;
 
 use byteorder::{LittleEndian, WriteBytesExt};
 
-use crate::LabelConverter;
+use crate::{LabelConverter, STACK_BASE_REGISTER, STACK_POINTER_REGISTER, SYSCALL_REGISTER};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum MemorySection {
@@ -19,17 +21,20 @@ impl From<MemorySection> for u8 {
     }
 }
 
-impl From<u8> for MemorySection {
-    fn from(u: u8) -> MemorySection {
+impl TryFrom<u8> for MemorySection {
+    /// The unknown section byte.
+    type Error = u8;
+
+    fn try_from(u: u8) -> Result<MemorySection, u8> {
         match u {
-            0 => MemorySection::Stack,
-            1 => MemorySection::Heap,
-            _ => panic!("Invalid section"), // TODO: Handle error properly with TryFrom.
+            0 => Ok(MemorySection::Stack),
+            1 => Ok(MemorySection::Heap),
+            _ => Err(u),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Address {
     pub offset: i32,
     pub register: u8,
@@ -55,7 +60,7 @@ impl Address {
 }
 
 /// Operand Types.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Operand {
     /// Integer literal operand.
     Integer(i32),
@@ -112,3 +117,42 @@ impl Operand {
         }
     }
 }
+
+/// Formats operands using the assembler syntax.
+///
+/// # Examples
+/// ```
+/// use instructor::{Address, Operand};
+///
+/// assert_eq!(Operand::Register(32).to_string(), "$v0");
+/// assert_eq!(Operand::Address(Address::new_stack(34, -4)).to_string(), "-4[$ebp]");
+/// assert_eq!(Operand::Label(String::from("main")).to_string(), "@main");
+/// ```
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Integer(i) => write!(f, "{}", i),
+            Operand::Register(r) => write_register(f, *r),
+            Operand::Label(l) => write!(f, "@{}", l),
+            Operand::Str(s) => write!(f, "\"{}\"", s),
+            Operand::Address(addr) => {
+                let (open, close) = match addr.section {
+                    MemorySection::Stack => ('[', ']'),
+                    MemorySection::Heap => ('(', ')'),
+                };
+                write!(f, "{}{}", addr.offset, open)?;
+                write_register(f, addr.register)?;
+                write!(f, "{}", close)
+            }
+        }
+    }
+}
+
+fn write_register(f: &mut fmt::Formatter<'_>, register: u8) -> fmt::Result {
+    match register as usize {
+        SYSCALL_REGISTER => write!(f, "$v0"),
+        STACK_POINTER_REGISTER => write!(f, "$esp"),
+        STACK_BASE_REGISTER => write!(f, "$ebp"),
+        _ => write!(f, "${}", register),
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::Instruction;
 
 #[derive(Debug, PartialEq)]
@@ -6,3 +8,13 @@ pub struct Program {
     /// The vector of instructions composing the program.
     pub instructions: Vec<Instruction>,
 }
+
+/// Formats the program as assembler source, one instruction per line.
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in self.instructions.iter() {
+            writeln!(f, "{}", instruction)?;
+        }
+        Ok(())
+    }
+}
@@ -20,7 +20,42 @@ pub enum SysCall {
     /// Print string. Prints a string from dynamic memory.
     PRINTS,
 
-    /// Illegal syscall. Panics.
+    /// Read line. Reads a line of input, without its line terminator, into a newly allocated
+    /// null-terminated heap buffer.
+    /// Writes the start address of the buffer in $v0.
+    READLN,
+
+    /// Read integer. Reads a line of input and parses it as a signed integer.
+    /// Writes the integer in $v0.
+    READI,
+
+    /// Read byte. Reads a single byte of input.
+    /// Writes the byte in $v0, or -1 if the input is exhausted.
+    READB,
+
+    /// Print integer. Prints the value of $0 as a signed decimal.
+    PRINTI,
+
+    /// Print unsigned. Prints the value of $0 as an unsigned decimal.
+    PRINTU,
+
+    /// Print hex. Prints the value of $0 as lowercase hexadecimal, without prefix.
+    PRINTX,
+
+    /// Print char. Prints the lowest byte of $0.
+    PRINTC,
+
+    /// Format integer. Formats the value of $0 as a signed decimal into a newly allocated
+    /// null-terminated heap buffer.
+    /// Writes the start address of the buffer in $v0.
+    FMTI,
+
+    /// Abort. Terminates the process with a trap, using the string at the RO offset in $0 as the
+    /// message.
+    ABORT,
+
+    /// Illegal syscall. No handler is registered for it, so executing it faults with an
+    /// `IllegalSyscall` trap.
     IGL,
 }
 
@@ -33,6 +68,15 @@ impl From<i32> for SysCall {
             3 => SysCall::ALLOC,
             4 => SysCall::FREE,
             5 => SysCall::PRINTS,
+            6 => SysCall::READLN,
+            7 => SysCall::READI,
+            8 => SysCall::READB,
+            9 => SysCall::PRINTI,
+            10 => SysCall::PRINTU,
+            11 => SysCall::PRINTX,
+            12 => SysCall::PRINTC,
+            13 => SysCall::FMTI,
+            14 => SysCall::ABORT,
             _ => SysCall::IGL,
         }
     }
@@ -1,12 +1,14 @@
 use std::path::PathBuf;
+use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
-use vm::VM;
+use vm::{Status, VM};
 
-use crate::load::load_program;
+use crate::debug::debug_loop;
+use crate::load::{load_optimized_program, load_program};
 use crate::repl::repl_loop;
 
 #[derive(Parser, Debug)]
@@ -15,17 +17,79 @@ pub struct CLIRoot {
     /// Path to the .asm file to run. Starts the REPL if unspecified.
     #[clap(short = 'f', long = "file")]
     file: Option<PathBuf>,
+
+    /// Maximum amount of fuel the program can consume. Unlimited if unspecified.
+    #[clap(long = "fuel")]
+    fuel: Option<u64>,
+
+    /// Print the execution time of the program to stderr.
+    #[clap(long = "time")]
+    time: bool,
+
+    /// Run the peephole optimizer when assembling the program.
+    #[clap(long = "peephole")]
+    peephole: bool,
+
+    /// Print how many instructions each peephole rule removed to stderr. Implies `--peephole`.
+    #[clap(long = "peephole-report")]
+    peephole_report: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Debug a program with an interactive prompt.
+    Debug {
+        /// Path to the .asm or ELIS file to debug.
+        file: PathBuf,
+    },
+
+    /// Print the assembly of a program.
+    Disas {
+        /// Path to the .asm or ELIS file to disassemble.
+        file: PathBuf,
+    },
 }
 
 impl CLIRoot {
     pub fn run(&self) -> Result<()> {
+        match self.command.as_ref() {
+            Some(Command::Debug { file }) => return debug_loop(file, self.fuel),
+            Some(Command::Disas { file }) => {
+                let program = disassembler::disassemble(&load_program(file)?)?;
+                print!("{}", program);
+                return Ok(());
+            }
+            None => {}
+        }
+
         match self.file.as_ref() {
             Some(f) => {
                 // Compile & load the program, and start the VM.
-                let program = load_program(&f)?;
+                let program = if self.peephole || self.peephole_report {
+                    let (program, stats) = load_optimized_program(f)?;
+                    if let (true, Some(stats)) = (self.peephole_report, stats) {
+                        eprintln!("{}", stats);
+                    }
+                    program
+                } else {
+                    load_program(f)?
+                };
                 let mut vm = VM::new();
                 vm.load_bytecode(program)?;
-                vm.run();
+                vm.set_fuel(self.fuel);
+
+                let start = Instant::now();
+                let status = vm.run()?;
+                if self.time {
+                    eprintln!("Done in {}us", start.elapsed().as_micros());
+                }
+
+                if status == Status::OutOfFuel {
+                    bail!("program ran out of fuel at {:#06x}", vm.pc);
+                }
                 Ok(())
             }
             None => {
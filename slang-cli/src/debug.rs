@@ -0,0 +1,359 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+
+use assembler::SymbolTable;
+
+use disassembler::{decode_instruction, jump_target};
+
+use instructor::{
+    Instruction, Operand, REGULAR_REGISTER_COUNT, STACK_BASE_REGISTER, STACK_POINTER_REGISTER,
+    SYSCALL_REGISTER,
+};
+
+use vm::debug::{read_word, Debugger, StopReason, Watch};
+use vm::{VMError, VM};
+
+use crate::load::load_program_with_symbols;
+use crate::repl::user_input;
+
+/// Number of instructions shown on each side of the pc by `disas`.
+const DISAS_CONTEXT: usize = 5;
+
+/// Number of words shown by `heap` when no count is given.
+const DEFAULT_DUMP_WORDS: usize = 16;
+
+const HELP: &str = "Commands:
+  break <pc|label>          Set a breakpoint (b)
+  delete <pc|label>         Remove a breakpoint (d)
+  watch <$reg|heap addr|stack addr>
+                            Stop when a value changes (w)
+  step                      Execute one instruction (s)
+  next                      Execute one instruction, stepping over calls (n)
+  continue                  Run until a breakpoint or watchpoint (c)
+  regs                      Print the registers (r)
+  stack                     Print the stack words
+  heap [x/N] <addr>         Print N heap words starting at addr
+  disas                     Disassemble around the pc
+  bt                        Print the call stack
+  quit                      Exit the debugger (q)";
+
+fn parse_number(s: &str) -> Result<usize> {
+    let value = match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    value.map_err(|_| anyhow!("invalid number: {}", s))
+}
+
+struct DebugSession {
+    vm: VM,
+    debugger: Debugger,
+    symbols: Option<SymbolTable>,
+    halted: bool,
+}
+
+impl DebugSession {
+    /// Resolves a pc or a label to a program offset.
+    fn location(&self, s: &str) -> Result<usize> {
+        if let Ok(pc) = parse_number(s) {
+            return Ok(pc);
+        }
+
+        self.symbols
+            .as_ref()
+            .and_then(|symbols| symbols.labels().find(|(name, _)| *name == s))
+            .map(|(_, offset)| offset as usize)
+            .ok_or_else(|| anyhow!("unknown label: {}", s))
+    }
+
+    /// Returns the name of the label at a program offset.
+    fn label_at(&self, pc: usize) -> Option<String> {
+        self.symbols
+            .as_ref()?
+            .labels()
+            .find(|(_, offset)| *offset as usize == pc)
+            .map(|(name, _)| String::from(name))
+    }
+
+    /// Formats a program offset as `label+offset` using the closest preceding label.
+    fn symbolize(&self, pc: usize) -> String {
+        let closest = self.symbols.as_ref().and_then(|symbols| {
+            symbols
+                .labels()
+                .filter(|(_, offset)| (*offset as usize) <= pc)
+                .max_by_key(|(_, offset)| *offset)
+        });
+
+        match closest {
+            Some((name, offset)) if offset as usize == pc => format!("{:#06x} <{}>", pc, name),
+            Some((name, offset)) => format!("{:#06x} <{}+{}>", pc, name, pc - offset as usize),
+            None => format!("{:#06x}", pc),
+        }
+    }
+
+    /// Decodes the program into `(pc, instruction)` pairs, stopping at the first invalid instruction.
+    fn decode(&self) -> Vec<(usize, Instruction)> {
+        let program = self.vm.program();
+        let mut instructions = Vec::new();
+
+        let mut pc = 0;
+        while let Ok((mut instruction, width)) = decode_instruction(program, pc) {
+            if let Some(operand) = jump_target(&mut instruction) {
+                if let Operand::Integer(target) = *operand {
+                    if let Some(label) = self.label_at(target as usize) {
+                        *operand = Operand::Label(label);
+                    }
+                }
+            }
+
+            instructions.push((pc, instruction));
+            pc += width;
+        }
+
+        instructions
+    }
+
+    fn print_current(&self) {
+        let instructions = self.decode();
+        match instructions.iter().find(|(pc, _)| *pc == self.vm.pc) {
+            Some((pc, instruction)) => println!("=> {}: {}", self.symbolize(*pc), instruction),
+            None => println!("=> {} (end of program)", self.symbolize(self.vm.pc)),
+        }
+    }
+
+    /// Executes instructions with `f`, unless the program already halted.
+    fn resume<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Debugger, &mut VM) -> Result<StopReason, VMError>,
+    {
+        if self.halted {
+            bail!("the program is not running");
+        }
+
+        let reason = f(&mut self.debugger, &mut self.vm)?;
+        self.halted = reason == StopReason::Halted;
+        self.report(reason);
+        Ok(())
+    }
+
+    fn report(&self, reason: StopReason) {
+        match reason {
+            StopReason::Breakpoint { pc } => println!("Breakpoint at {}", self.symbolize(pc)),
+            StopReason::Watchpoint { watch, old, new } => {
+                println!("Watchpoint {}: {:?} => {:?}", watch, old, new)
+            }
+            StopReason::Step => {}
+            StopReason::Halted => {
+                println!("Program halted.");
+                return;
+            }
+            StopReason::OutOfFuel => println!("Program ran out of fuel."),
+        }
+        self.print_current();
+    }
+
+    fn print_registers(&self) {
+        let registers = self.vm.registers();
+        for (i, row) in registers[..REGULAR_REGISTER_COUNT].chunks(4).enumerate() {
+            let line: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(j, v)| format!("${:<2} {:#010x} {:>11}", i * 4 + j, v, v))
+                .collect();
+            println!("{}", line.join("  "));
+        }
+        println!(
+            "$v0  {:#010x} {:>11}  $esp {:#010x}  $ebp {:#010x}",
+            registers[SYSCALL_REGISTER],
+            registers[SYSCALL_REGISTER],
+            registers[STACK_POINTER_REGISTER],
+            registers[STACK_BASE_REGISTER]
+        );
+        println!("pc   {:#06x}  eq {}", self.vm.pc, self.vm.equal_flag);
+    }
+
+    fn print_stack(&self) {
+        let memory = self.vm.stack().memory();
+        let esp = self.vm.registers()[STACK_POINTER_REGISTER] as usize;
+        let ebp = self.vm.registers()[STACK_BASE_REGISTER] as usize;
+
+        if memory.is_empty() {
+            println!("Stack is empty.");
+        }
+
+        for address in (0..memory.len()).step_by(4) {
+            let mut markers = Vec::new();
+            if address == ebp {
+                markers.push("<- $ebp");
+            }
+            if address == esp {
+                markers.push("<- $esp");
+            }
+            match read_word(memory, address) {
+                Some(v) => println!(
+                    "{:#06x}: {:#010x} {:>11} {}",
+                    address,
+                    v,
+                    v,
+                    markers.join(" ")
+                ),
+                None => println!("{:#06x}: {:02x?}", address, &memory[address..]),
+            }
+        }
+
+        if esp >= memory.len() && !memory.is_empty() {
+            println!("{:#06x}: <- $esp", esp);
+        }
+    }
+
+    fn print_heap(&self, args: &[&str]) -> Result<()> {
+        let (count, address) = match args {
+            [format, address] => {
+                let count = format
+                    .strip_prefix("x/")
+                    .ok_or_else(|| anyhow!("invalid format: {}", format))?;
+                (parse_number(count)?, parse_number(address)?)
+            }
+            [address] => (DEFAULT_DUMP_WORDS, parse_number(address)?),
+            _ => bail!("usage: heap [x/N] <addr>"),
+        };
+
+        let memory = self.vm.heap().memory();
+        for i in 0..count {
+            let word_address = address.saturating_add(i * 4);
+            match read_word(memory, word_address) {
+                Some(v) => println!("{:#06x}: {:#010x} {:>11}", word_address, v, v),
+                None => {
+                    println!("{:#06x}: <out of bounds>", word_address);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn print_disassembly(&self) {
+        let instructions = self.decode();
+        let current = instructions
+            .iter()
+            .position(|(pc, _)| *pc >= self.vm.pc)
+            .unwrap_or(instructions.len());
+
+        let start = current.saturating_sub(DISAS_CONTEXT);
+        let end = (current + DISAS_CONTEXT + 1).min(instructions.len());
+        for (pc, instruction) in &instructions[start..end] {
+            let marker = if *pc == self.vm.pc { "=>" } else { "  " };
+            println!("{} {}: {}", marker, self.symbolize(*pc), instruction);
+        }
+    }
+
+    fn print_backtrace(&self) {
+        let memory = self.vm.stack().memory();
+        println!("#0 {}", self.symbolize(self.vm.pc));
+
+        // Each frame stores the return address and the caller's $ebp right below its own $ebp.
+        let mut ebp = self.vm.registers()[STACK_BASE_REGISTER] as usize;
+        let mut depth = 1;
+        while ebp >= 8 {
+            let (return_address, caller_ebp) =
+                match (read_word(memory, ebp - 8), read_word(memory, ebp - 4)) {
+                    (Some(r), Some(e)) => (r as usize, e as usize),
+                    _ => break,
+                };
+
+            println!("#{} {}", depth, self.symbolize(return_address));
+            if caller_ebp >= ebp {
+                break;
+            }
+            ebp = caller_ebp;
+            depth += 1;
+        }
+    }
+
+    fn watch(&mut self, args: &[&str]) -> Result<()> {
+        let watch = match args {
+            [register] if register.starts_with('$') => {
+                let register = match &register[1..] {
+                    "v0" => SYSCALL_REGISTER as u8,
+                    "esp" => STACK_POINTER_REGISTER as u8,
+                    "ebp" => STACK_BASE_REGISTER as u8,
+                    r => r.parse()?,
+                };
+                Watch::Register(register)
+            }
+            ["heap", address] => Watch::Heap(parse_number(address)?),
+            ["stack", address] => Watch::Stack(parse_number(address)?),
+            _ => bail!("usage: watch <$reg|heap addr|stack addr>"),
+        };
+
+        self.debugger.add_watchpoint(watch, &self.vm);
+        println!("Watching {}", watch);
+        Ok(())
+    }
+
+    fn run_command(&mut self, cmd: &str) -> Result<bool> {
+        let tokens: Vec<&str> = cmd.split_whitespace().collect();
+        let (command, args) = match tokens.split_first() {
+            Some((command, args)) => (*command, args),
+            None => return Ok(true),
+        };
+
+        match (command, args) {
+            ("break" | "b", [location]) => {
+                let pc = self.location(location)?;
+                self.debugger.add_breakpoint(pc);
+                println!("Breakpoint at {}", self.symbolize(pc));
+            }
+            ("delete" | "d", [location]) => {
+                let pc = self.location(location)?;
+                if !self.debugger.remove_breakpoint(pc) {
+                    bail!("no breakpoint at {:#06x}", pc);
+                }
+            }
+            ("watch" | "w", args) => self.watch(args)?,
+            ("step" | "s", []) => self.resume(|debugger, vm| debugger.step(vm))?,
+            ("next" | "n", []) => self.resume(|debugger, vm| debugger.step_over(vm))?,
+            ("continue" | "c", []) => self.resume(|debugger, vm| debugger.run_until_break(vm))?,
+            ("regs" | "r", []) => self.print_registers(),
+            ("stack", []) => self.print_stack(),
+            ("heap", args) => self.print_heap(args)?,
+            ("disas", []) => self.print_disassembly(),
+            ("bt", []) => self.print_backtrace(),
+            ("help" | "h", []) => println!("{}", HELP),
+            ("quit" | "q", []) => return Ok(false),
+            _ => bail!("unknown command: {} (try 'help')", cmd),
+        }
+
+        Ok(true)
+    }
+}
+
+pub fn debug_loop<P: AsRef<Path>>(path: P, fuel: Option<u64>) -> Result<()> {
+    let (program, symbols) = load_program_with_symbols(path)?;
+
+    let mut vm = VM::new();
+    vm.load_bytecode(program)?;
+    vm.set_fuel(fuel);
+
+    let mut session = DebugSession {
+        vm,
+        debugger: Debugger::new(),
+        symbols,
+        halted: false,
+    };
+
+    println!("SLang VM v0.1.0 debugger. Type 'help' for a list of commands.");
+    session.print_current();
+
+    loop {
+        let inpt = user_input("(sdb) ")?;
+
+        match session.run_command(&inpt) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+    Ok(())
+}
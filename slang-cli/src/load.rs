@@ -3,18 +3,40 @@ use std::path::Path;
 
 use anyhow::Result;
 
-use assembler::Assembler;
+use assembler::{peephole::Stats, Assembler, SymbolTable};
 
 use instructor::ELIS_HEADER_PREFIX;
 
-pub fn load_program<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+/// Loads a program, along with the assembler used if it was assembled from source.
+fn load<P: AsRef<Path>>(path: P, peephole: bool) -> Result<(Vec<u8>, Option<Assembler>)> {
     let raw_prog = fs::read(path.as_ref())?;
     if raw_prog.starts_with(&ELIS_HEADER_PREFIX) {
         // Already compiled.
-        Ok(raw_prog)
+        Ok((raw_prog, None))
     } else {
         let raw_source = String::from_utf8(raw_prog)?;
-        let compiled_program = Assembler::new().assemble(&raw_source)?;
-        Ok(compiled_program)
+        let mut asm = Assembler::new();
+        asm.set_peephole(peephole);
+        let compiled_program = asm.assemble(&raw_source)?;
+        Ok((compiled_program, Some(asm)))
     }
 }
+
+pub fn load_program<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    Ok(load(path, false)?.0)
+}
+
+/// Loads a program, along with its symbols if it was assembled from source.
+pub fn load_program_with_symbols<P: AsRef<Path>>(
+    path: P,
+) -> Result<(Vec<u8>, Option<SymbolTable>)> {
+    let (program, asm) = load(path, false)?;
+    Ok((program, asm.map(|asm| asm.symbols().clone())))
+}
+
+/// Loads a program, running the peephole optimizer if it is assembled from source. The
+/// statistics of the optimizer are returned along with the program.
+pub fn load_optimized_program<P: AsRef<Path>>(path: P) -> Result<(Vec<u8>, Option<Stats>)> {
+    let (program, asm) = load(path, true)?;
+    Ok((program, asm.and_then(|asm| asm.peephole_stats().cloned())))
+}
@@ -1,4 +1,5 @@
 mod cli;
+mod debug;
 mod load;
 mod repl;
 
@@ -6,9 +6,10 @@ use std::io::Write;
 use anyhow::Result;
 
 use assembler::Assembler;
+use instructor::{STACK_BASE_REGISTER, STACK_POINTER_REGISTER, SYSCALL_REGISTER};
 use vm::VM;
 
-fn user_input(prompt: &str) -> Result<String> {
+pub fn user_input(prompt: &str) -> Result<String> {
     let mut s = String::new();
     print!("{}", prompt);
     io::stdout().flush()?;
@@ -34,20 +35,35 @@ fn run_command(vm: &mut VM, cmd: &str) -> Result<bool> {
         }
         ".program" => {
             println!("Instructions currently in VM memory:");
-            for instruction in vm.program() {
-                println!("{}", instruction);
+            let mut offset = 0;
+            while offset < vm.program().len() {
+                match disassembler::decode_instruction(vm.program(), offset) {
+                    Ok((instruction, width)) => {
+                        println!("{:#06x}: {}", offset, instruction);
+                        offset += width;
+                    }
+                    Err(e) => {
+                        println!("{:#06x}: {}", offset, e);
+                        break;
+                    }
+                }
             }
         }
         ".reg" => {
             println!("Current VM state:");
             let slice_ref: [i32; 32] = vm.registers()[0..32].try_into().unwrap();
             println!("{:#?}", slice_ref);
-            // TODO: Print special registers also.
+            println!(
+                "$v0: {}, $esp: {}, $ebp: {}",
+                vm.registers()[SYSCALL_REGISTER],
+                vm.registers()[STACK_POINTER_REGISTER],
+                vm.registers()[STACK_BASE_REGISTER]
+            );
             println!("End of listing");
         }
         ".run" => {
             println!("Running to end of program...");
-            vm.run();
+            vm.run()?;
         }
         ".unl" => {
             println!("Unloading program...");
@@ -63,7 +79,7 @@ fn run_command(vm: &mut VM, cmd: &str) -> Result<bool> {
             let mut asm = Assembler::new();
             let program = asm.assemble(cmd)?;
             vm.load_bytecode(program)?;
-            vm.run_once();
+            vm.run_once()?;
         }
     }
 
@@ -1,20 +1,15 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 use assembler::Assembler;
-use vm::VM;
+use vm::{Status, VM};
 
-fn execute_test(source: Vec<u8>, max_epochs: usize) -> VM {
+fn execute_test(source: Vec<u8>, max_epochs: u64) -> VM {
     let mut vm = VM::new();
     vm.load_bytecode(source).unwrap();
+    vm.set_fuel(Some(max_epochs));
 
-    let mut current_epochs = 0;
-    let mut keepalive = true;
-    while keepalive {
-        keepalive = vm.run_once();
-        current_epochs += 1;
-        if current_epochs >= max_epochs {
-            panic!("Test took too long");
-        }
+    if vm.run().unwrap() == Status::OutOfFuel {
+        panic!("Test took too long");
     }
 
     vm
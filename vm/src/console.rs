@@ -0,0 +1,141 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Input source and output sink used by the VM's I/O syscalls.
+///
+/// Defaults to the process' stdin and stdout.
+pub struct Console {
+    input: BufReader<Box<dyn Read + Send>>,
+    output: Box<dyn Write + Send>,
+}
+
+impl Console {
+    /// Creates a console reading from stdin and writing to stdout.
+    pub fn new() -> Console {
+        Console::with_io(io::stdin(), io::stdout())
+    }
+
+    /// Creates a console reading from `input` and writing to `output`.
+    pub fn with_io<R, W>(input: R, output: W) -> Console
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        Console {
+            input: BufReader::new(Box::new(input)),
+            output: Box::new(output),
+        }
+    }
+
+    /// Replaces the input source.
+    pub fn set_input<R: Read + Send + 'static>(&mut self, input: R) {
+        self.input = BufReader::new(Box::new(input));
+    }
+
+    /// Replaces the output sink.
+    pub fn set_output<W: Write + Send + 'static>(&mut self, output: W) {
+        self.output = Box::new(output);
+    }
+
+    #[inline]
+    pub fn input(&mut self) -> &mut dyn BufRead {
+        &mut self.input
+    }
+
+    #[inline]
+    pub fn output(&mut self) -> &mut dyn Write {
+        &mut self.output
+    }
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        Console::new()
+    }
+}
+
+impl fmt::Debug for Console {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Console").finish_non_exhaustive()
+    }
+}
+
+/// In-memory output sink whose clones share the same buffer.
+///
+/// Useful to capture the output of a program.
+///
+/// # Examples
+/// ```
+/// use std::io::Write;
+/// use vm::SharedBuffer;
+///
+/// let buffer = SharedBuffer::new();
+/// let mut sink = buffer.clone();
+/// write!(sink, "hello").unwrap();
+/// assert_eq!(buffer.to_string_lossy(), "hello");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SharedBuffer {
+    data: Arc<Mutex<Vec<u8>>>,
+}
+
+impl SharedBuffer {
+    pub fn new() -> SharedBuffer {
+        SharedBuffer::default()
+    }
+
+    fn data(&self) -> MutexGuard<'_, Vec<u8>> {
+        // The buffer is only appended to, so it is still usable after a panic while writing.
+        self.data.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns a copy of the bytes written so far.
+    pub fn contents(&self) -> Vec<u8> {
+        self.data().clone()
+    }
+
+    /// Returns the bytes written so far as a string, replacing invalid UTF-8.
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.data()).into_owned()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Console, SharedBuffer};
+
+    #[test]
+    fn redirect_io() {
+        let buffer = SharedBuffer::new();
+        let mut console = Console::with_io(&b"first\nsecond\n"[..], buffer.clone());
+
+        let mut line = String::new();
+        console.input().read_line(&mut line).unwrap();
+        assert_eq!(line, "first\n");
+
+        write!(console.output(), "out").unwrap();
+        assert_eq!(buffer.contents(), b"out");
+    }
+
+    #[test]
+    fn set_input() {
+        let mut console = Console::with_io(&b"a"[..], SharedBuffer::new());
+        console.set_input(&b"b\n"[..]);
+
+        let mut line = String::new();
+        console.input().read_line(&mut line).unwrap();
+        assert_eq!(line, "b\n");
+    }
+}
@@ -1 +1,4 @@
 pub use instructor::{REGISTER_COUNT, SYSCALL_REGISTER};
+
+/// Maximum size of the VM stack, in bytes.
+pub const STACK_SIZE_LIMIT: usize = 8 * 1024 * 1024;
@@ -0,0 +1,291 @@
+//! Debugging support for the VM.
+//!
+//! The [`Debugger`] drives a [`VM`] one instruction at a time through
+//! [`VM::run_once`], pausing on breakpoints and watchpoints.
+use std::collections::BTreeSet;
+use std::convert::TryInto;
+use std::fmt;
+
+use instructor::{LabelConverter, Opcode};
+
+use crate::{Status, VMError, VM};
+
+type Result<T> = std::result::Result<T, VMError>;
+
+/// A location observed by a watchpoint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Watch {
+    /// The value of a register.
+    Register(u8),
+
+    /// The word stored at a heap address.
+    Heap(usize),
+
+    /// The word stored at a stack address.
+    Stack(usize),
+}
+
+impl Watch {
+    /// Reads the current value of the watched location.
+    ///
+    /// Returns `None` if the location is not currently addressable.
+    pub fn read(&self, vm: &VM) -> Option<i32> {
+        match *self {
+            Watch::Register(register) => vm.registers().get(register as usize).copied(),
+            Watch::Heap(address) => read_word(vm.heap().memory(), address),
+            Watch::Stack(address) => read_word(vm.stack().memory(), address),
+        }
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Watch::Register(register) => write!(f, "${}", register),
+            Watch::Heap(address) => write!(f, "heap[{:#06x}]", address),
+            Watch::Stack(address) => write!(f, "stack[{:#06x}]", address),
+        }
+    }
+}
+
+/// Reads the little-endian word at an address of a memory block, such as the heap or the stack.
+///
+/// Returns `None` if the word is not entirely within the block.
+pub fn read_word(memory: &[u8], address: usize) -> Option<i32> {
+    let bytes = memory.get(address..address.checked_add(4)?)?;
+    Some(i32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Why the debugger handed control back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+    /// The pc reached a breakpoint. The instruction at the breakpoint was not executed yet.
+    Breakpoint { pc: usize },
+
+    /// A watched location changed value.
+    Watchpoint {
+        watch: Watch,
+        old: Option<i32>,
+        new: Option<i32>,
+    },
+
+    /// The requested step completed.
+    Step,
+
+    /// The program terminated.
+    Halted,
+
+    /// The VM ran out of fuel.
+    OutOfFuel,
+}
+
+#[derive(Debug)]
+struct Watchpoint {
+    watch: Watch,
+    value: Option<i32>,
+}
+
+/// Breakpoints and watchpoints over a VM.
+#[derive(Debug, Default)]
+pub struct Debugger {
+    breakpoints: BTreeSet<usize>,
+    watchpoints: Vec<Watchpoint>,
+}
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        Debugger::default()
+    }
+
+    /// Adds a breakpoint at a pc. Returns false if it was already set.
+    pub fn add_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.insert(pc)
+    }
+
+    /// Adds a breakpoint at the offset of a label.
+    ///
+    /// Returns the pc of the breakpoint, or `None` if the label is unknown.
+    pub fn add_label_breakpoint<T: LabelConverter>(
+        &mut self,
+        label: &str,
+        labels: &T,
+    ) -> Option<usize> {
+        let pc = labels.offset_of(label)? as usize;
+        self.breakpoints.insert(pc);
+        Some(pc)
+    }
+
+    /// Removes a breakpoint. Returns false if no breakpoint was set at this pc.
+    pub fn remove_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    /// Returns the breakpoints, in increasing pc order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Watches a location for changes, starting from its current value.
+    pub fn add_watchpoint(&mut self, watch: Watch, vm: &VM) {
+        self.remove_watchpoint(watch);
+        self.watchpoints.push(Watchpoint {
+            watch,
+            value: watch.read(vm),
+        });
+    }
+
+    /// Stops watching a location. Returns false if it was not watched.
+    pub fn remove_watchpoint(&mut self, watch: Watch) -> bool {
+        let count = self.watchpoints.len();
+        self.watchpoints.retain(|w| w.watch != watch);
+        self.watchpoints.len() != count
+    }
+
+    /// Returns the watched locations.
+    pub fn watchpoints(&self) -> impl Iterator<Item = Watch> + '_ {
+        self.watchpoints.iter().map(|w| w.watch)
+    }
+
+    /// Executes a single instruction.
+    pub fn step(&mut self, vm: &mut VM) -> Result<StopReason> {
+        let result = self.step_once(vm);
+        vm.flush_output();
+        result
+    }
+
+    /// Executes a single instruction, without flushing the output of the program.
+    fn step_once(&mut self, vm: &mut VM) -> Result<StopReason> {
+        match vm.run_once()? {
+            Status::Running => {}
+            Status::Halted => return Ok(StopReason::Halted),
+            Status::OutOfFuel => return Ok(StopReason::OutOfFuel),
+        }
+
+        Ok(self.check_watchpoints(vm).unwrap_or(StopReason::Step))
+    }
+
+    /// Executes a single instruction, running `CALL` instructions until the callee returns.
+    pub fn step_over(&mut self, vm: &mut VM) -> Result<StopReason> {
+        let result = self.step_over_once(vm);
+        vm.flush_output();
+        result
+    }
+
+    fn step_over_once(&mut self, vm: &mut VM) -> Result<StopReason> {
+        let call_width = Opcode::CALL.width() as usize;
+        if vm.program().get(vm.pc).map(|b| Opcode::from(*b)) != Some(Opcode::CALL) {
+            return self.step_once(vm);
+        }
+
+        let return_pc = vm.pc + call_width;
+        let stack_depth = vm.stack().len();
+
+        let reason = self.step_once(vm)?;
+        if reason != StopReason::Step {
+            return Ok(reason);
+        }
+
+        self.run_while(vm, |vm| {
+            !(vm.pc == return_pc && vm.stack().len() <= stack_depth)
+        })
+    }
+
+    /// Runs until a breakpoint or watchpoint is hit, or the program stops.
+    ///
+    /// A breakpoint at the current pc does not stop execution, so this can resume after a breakpoint.
+    pub fn run_until_break(&mut self, vm: &mut VM) -> Result<StopReason> {
+        let result = self.step_once(vm).and_then(|reason| match reason {
+            StopReason::Step => self.run_while(vm, |_vm| true),
+            reason => Ok(reason),
+        });
+        vm.flush_output();
+        result
+    }
+
+    /// Steps while `keep_going` holds, stopping on breakpoints and watchpoints.
+    fn run_while<F>(&mut self, vm: &mut VM, keep_going: F) -> Result<StopReason>
+    where
+        F: Fn(&VM) -> bool,
+    {
+        while keep_going(vm) {
+            if self.breakpoints.contains(&vm.pc) {
+                return Ok(StopReason::Breakpoint { pc: vm.pc });
+            }
+
+            let reason = self.step_once(vm)?;
+            if reason != StopReason::Step {
+                return Ok(reason);
+            }
+        }
+
+        Ok(StopReason::Step)
+    }
+
+    fn check_watchpoints(&mut self, vm: &VM) -> Option<StopReason> {
+        let mut reason = None;
+
+        // Update every watchpoint, so values are current whichever one triggered.
+        for watchpoint in self.watchpoints.iter_mut() {
+            let value = watchpoint.watch.read(vm);
+            if value != watchpoint.value {
+                if reason.is_none() {
+                    reason = Some(StopReason::Watchpoint {
+                        watch: watchpoint.watch,
+                        old: watchpoint.value,
+                        new: value,
+                    });
+                }
+                watchpoint.value = value;
+            }
+        }
+
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_word, Debugger, Watch};
+    use crate::VM;
+
+    #[test]
+    fn read_words() {
+        let memory = [1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(read_word(&memory, 0), Some(1));
+        assert_eq!(read_word(&memory, 4), Some(-1));
+        assert_eq!(read_word(&memory, 5), None);
+        assert_eq!(read_word(&memory, usize::MAX), None);
+    }
+
+    #[test]
+    fn watch_register() {
+        let mut vm = VM::new();
+        vm.registers_mut()[3] = 12;
+        assert_eq!(Watch::Register(3).read(&vm), Some(12));
+        assert_eq!(Watch::Register(200).read(&vm), None);
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut debugger = Debugger::new();
+        assert!(debugger.add_breakpoint(8));
+        assert!(!debugger.add_breakpoint(8));
+        assert!(debugger.add_breakpoint(4));
+        assert_eq!(debugger.breakpoints().collect::<Vec<_>>(), vec![4, 8]);
+
+        assert!(debugger.remove_breakpoint(8));
+        assert!(!debugger.remove_breakpoint(8));
+    }
+
+    #[test]
+    fn watchpoints() {
+        let vm = VM::new();
+        let mut debugger = Debugger::new();
+        debugger.add_watchpoint(Watch::Register(0), &vm);
+        debugger.add_watchpoint(Watch::Register(0), &vm);
+        assert_eq!(debugger.watchpoints().count(), 1);
+
+        assert!(debugger.remove_watchpoint(Watch::Register(0)));
+        assert!(!debugger.remove_watchpoint(Watch::Register(0)));
+    }
+}
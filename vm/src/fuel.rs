@@ -0,0 +1,72 @@
+use instructor::Opcode;
+
+/// Number of distinct opcode bytes.
+const OPCODE_SPACE: usize = 256;
+
+/// Default fuel cost of executing a single instruction.
+pub const DEFAULT_FUEL_COST: u64 = 1;
+
+/// Fuel cost of each opcode, used to meter execution when the VM has a fuel budget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuelCosts {
+    costs: [u64; OPCODE_SPACE],
+}
+
+impl FuelCosts {
+    /// Creates a cost table where every opcode costs [`DEFAULT_FUEL_COST`].
+    pub fn new() -> FuelCosts {
+        FuelCosts {
+            costs: [DEFAULT_FUEL_COST; OPCODE_SPACE],
+        }
+    }
+
+    /// Sets the cost of executing an opcode.
+    ///
+    /// # Examples
+    /// ```
+    /// use instructor::Opcode;
+    /// use vm::FuelCosts;
+    ///
+    /// let mut costs = FuelCosts::new();
+    /// costs.set(Opcode::SYSC, 10);
+    /// assert_eq!(costs.cost(Opcode::SYSC), 10);
+    /// assert_eq!(costs.cost(Opcode::ADD), 1);
+    /// ```
+    pub fn set(&mut self, opcode: Opcode, cost: u64) {
+        self.costs[opcode as usize] = cost;
+    }
+
+    /// Returns the cost of executing an opcode.
+    #[inline]
+    pub fn cost(&self, opcode: Opcode) -> u64 {
+        self.costs[opcode as usize]
+    }
+}
+
+impl Default for FuelCosts {
+    fn default() -> FuelCosts {
+        FuelCosts::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use instructor::Opcode;
+
+    use super::{FuelCosts, DEFAULT_FUEL_COST};
+
+    #[test]
+    fn default_costs() {
+        let costs = FuelCosts::new();
+        assert_eq!(costs.cost(Opcode::LOAD), DEFAULT_FUEL_COST);
+        assert_eq!(costs.cost(Opcode::IGL), DEFAULT_FUEL_COST);
+    }
+
+    #[test]
+    fn custom_cost() {
+        let mut costs = FuelCosts::new();
+        costs.set(Opcode::CALL, 5);
+        assert_eq!(costs.cost(Opcode::CALL), 5);
+        assert_eq!(costs.cost(Opcode::RET), DEFAULT_FUEL_COST);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::memutil;
+use crate::trap::{Result, Trap};
 
 #[derive(Debug, PartialEq)]
 struct MemoryBlock {
@@ -42,6 +43,11 @@ impl Heap {
         self.memory.len()
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty()
+    }
+
     #[inline]
     pub fn memory(&self) -> &[u8] {
         &self.memory
@@ -95,21 +101,21 @@ impl Heap {
         ptr
     }
 
-    pub fn free(&mut self, ptr: usize) {
+    pub fn free(&mut self, ptr: usize) -> Result<()> {
         log::trace!("received request to free [{:#06x}]", ptr);
         let idx = match self.index.iter().position(|e| e.start_index == ptr) {
             Some(i) => i,
             None => {
                 log::error!("invalid free of [{:#06x}]", ptr);
-                panic!("Invalid free")
+                return Err(Trap::InvalidFree { ptr });
             }
         };
 
         {
-            let node = self.index.get_mut(idx).unwrap();
+            let node = &mut self.index[idx];
             if node.is_free {
                 log::error!("double free of [{:#06x}]", ptr);
-                panic!("Double free");
+                return Err(Trap::DoubleFree { ptr });
             }
             node.is_free = true;
         }
@@ -128,31 +134,27 @@ impl Heap {
         }
         log::trace!("shrinking by {}b", amt_to_shrink);
         self.memory.resize(self.memory.len() - amt_to_shrink, 0);
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{memutil, Heap, MemoryBlock};
+    use super::{memutil, Heap, MemoryBlock, Trap};
 
     #[test]
     fn alloc() {
         let mut heap = Heap::new();
         {
             // Allocate 4 bytes to the heap.
-            let mut ptr = heap.alloc(4);
-            for i in 0..4 {
-                heap.memory[ptr] = i;
-                ptr = ptr + 1;
-            }
+            let ptr = heap.alloc(4);
+            heap.memory[ptr..ptr + 4].copy_from_slice(&[0, 1, 2, 3]);
         }
 
         {
-            let mut ptr = heap.alloc(2);
-            for i in 0..2 {
-                heap.memory[ptr] = 8 - i;
-                ptr = ptr + 1;
-            }
+            let ptr = heap.alloc(2);
+            heap.memory[ptr..ptr + 2].copy_from_slice(&[8, 7]);
         }
 
         assert_eq!(heap.memory.len(), memutil::align(4) + memutil::align(6));
@@ -173,7 +175,7 @@ mod tests {
 
         // When a single block of memory is allocated,
         // it can be resized immediately upon free().
-        heap.free(0);
+        heap.free(0).unwrap();
 
         assert_eq!(heap.memory.len(), 0);
     }
@@ -185,33 +187,31 @@ mod tests {
         heap.index = vec![MemoryBlock::new(0, 4), MemoryBlock::new(4, 2)];
 
         // Since the [4-6] block is still allocated, freeing the [0-4] block won't shrink the process memory.
-        heap.free(0);
+        heap.free(0).unwrap();
         assert_eq!(heap.memory.len(), 6);
         assert!(heap.index[0].is_free);
 
         // However, if we free the [4-6] block too, the whole memory should be freed.
-        heap.free(4);
+        heap.free(4).unwrap();
         assert_eq!(heap.memory.len(), 0);
     }
 
     #[test]
-    #[should_panic(expected = "Double free")]
     fn double_free() {
         let mut heap = Heap::new();
         heap.memory = vec![0, 1];
         heap.index = vec![MemoryBlock::new(0, 2)];
 
-        heap.free(0);
-        heap.free(0);
+        heap.free(0).unwrap();
+        assert_eq!(heap.free(0), Err(Trap::DoubleFree { ptr: 0 }));
     }
 
     #[test]
-    #[should_panic(expected = "Invalid free")]
     fn invalid_free() {
         let mut heap = Heap::new();
         heap.memory = vec![0, 1];
         heap.index = vec![MemoryBlock::new(0, 2)];
 
-        heap.free(18);
+        assert_eq!(heap.free(18), Err(Trap::InvalidFree { ptr: 18 }));
     }
 }
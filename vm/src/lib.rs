@@ -1,4 +1,7 @@
+mod console;
 mod constants;
+pub mod debug;
+mod fuel;
 mod heap;
 mod loader;
 mod machine;
@@ -6,4 +9,11 @@ pub mod memutil;
 mod op;
 mod stack;
 mod syscall;
-pub use machine::VM;
+mod trap;
+pub use console::{Console, SharedBuffer};
+pub use fuel::{FuelCosts, DEFAULT_FUEL_COST};
+pub use heap::Heap;
+pub use machine::{Status, VMError, VM};
+pub use stack::Stack;
+pub use syscall::{SyscallHandler, SyscallTable};
+pub use trap::{MemoryRegion, Trap};
@@ -39,7 +39,7 @@ impl Program {
         let header = Header::from_bytes(&data[0..ELIS_HEADER_LENGTH])?;
 
         ensure!(
-            (header.ro_block_size + ELIS_HEADER_LENGTH) as usize <= data.len(),
+            header.ro_block_size + ELIS_HEADER_LENGTH <= data.len(),
             ReadOnlySectionTooLongSnafu
         );
 
@@ -1,24 +1,50 @@
-use std::io::Cursor;
+use std::convert::TryFrom;
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, LittleEndian};
 
-use instructor::{Address, MemorySection, Opcode, SysCall};
+use instructor::{Address, MemorySection, Opcode};
 
 use snafu::{ResultExt, Snafu};
 
+use crate::console::Console;
 use crate::constants::{REGISTER_COUNT, SYSCALL_REGISTER};
+use crate::fuel::FuelCosts;
 use crate::heap::Heap;
 use crate::op;
 use crate::stack::Stack;
-use crate::syscall::execute_syscall;
+use crate::syscall::{execute_syscall, SyscallTable};
+use crate::trap::{self, Trap};
 
 #[derive(Debug, Snafu)]
 pub enum VMError {
-    LoadingError { source: crate::loader::LoadError },
+    LoadingError {
+        source: crate::loader::LoadError,
+    },
+
+    #[snafu(display("Fault at {:#06x} ({:?}): {}", pc, opcode, source))]
+    Fault {
+        pc: usize,
+        opcode: Opcode,
+        source: Trap,
+    },
 }
 
 type Result<T> = std::result::Result<T, VMError>;
 
+/// Execution state of the VM.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    /// The program can keep running.
+    Running,
+
+    /// The program terminated.
+    Halted,
+
+    /// The fuel budget cannot cover the next instruction.
+    /// Execution can be resumed after refueling.
+    OutOfFuel,
+}
+
 pub struct VM {
     // Registers 0-31 are regular registers. Reg 32 is the syscall register.
     registers: [i32; REGISTER_COUNT],
@@ -32,6 +58,12 @@ pub struct VM {
 
     pub pc: usize,
     program: Vec<u8>,
+
+    fuel: Option<u64>,
+    fuel_costs: FuelCosts,
+
+    syscalls: SyscallTable,
+    console: Console,
 }
 
 impl VM {
@@ -46,6 +78,12 @@ impl VM {
 
             pc: 0,
             program: Vec::new(),
+
+            fuel: None,
+            fuel_costs: FuelCosts::new(),
+
+            syscalls: SyscallTable::with_builtins(),
+            console: Console::new(),
         }
     }
 
@@ -112,8 +150,58 @@ impl VM {
         self.remainder = v;
     }
 
+    /// Returns the remaining fuel, or `None` if execution is unmetered.
+    #[inline]
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Sets the fuel budget. `None` disables metering.
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    /// Adds fuel to the current budget, enabling metering if it was disabled.
+    pub fn refuel(&mut self, amount: u64) {
+        self.fuel = Some(self.fuel.unwrap_or(0).saturating_add(amount));
+    }
+
+    #[inline]
+    pub fn fuel_costs(&self) -> &FuelCosts {
+        &self.fuel_costs
+    }
+
+    #[inline]
+    pub fn fuel_costs_mut(&mut self) -> &mut FuelCosts {
+        &mut self.fuel_costs
+    }
+
+    #[inline]
+    pub fn syscalls(&self) -> &SyscallTable {
+        &self.syscalls
+    }
+
+    #[inline]
+    pub fn syscalls_mut(&mut self) -> &mut SyscallTable {
+        &mut self.syscalls
+    }
+
+    #[inline]
+    pub fn console(&self) -> &Console {
+        &self.console
+    }
+
+    #[inline]
+    pub fn console_mut(&mut self) -> &mut Console {
+        &mut self.console
+    }
+
     pub fn load_bytecode(&mut self, bytecode: Vec<u8>) -> Result<()> {
         let program = crate::loader::Program::new(bytecode).context(LoadingSnafu)?;
+        log::debug!(
+            "loaded program with a {}b ro block",
+            program.header.ro_block_size
+        );
 
         // TODO: Use program struct directly instead of unpacking.
         self.program = program.program_text;
@@ -123,154 +211,251 @@ impl VM {
     }
 
     #[inline]
-    fn decode_opcode(&mut self) -> Opcode {
-        let opcode = Opcode::from(self.program[self.pc]);
-        self.pc += 1;
-
-        opcode
+    fn next_bytes(&mut self, count: usize) -> trap::Result<&[u8]> {
+        let bytes = self
+            .program
+            .get(self.pc..self.pc + count)
+            .ok_or(Trap::PcOutOfRange { pc: self.pc })?;
+        self.pc += count;
+
+        Ok(bytes)
     }
 
     #[inline]
-    fn next_8_bits(&mut self) -> u8 {
-        let result = self.program[self.pc];
-        self.pc += 1;
-
-        result
+    fn next_8_bits(&mut self) -> trap::Result<u8> {
+        Ok(self.next_bytes(1)?[0])
     }
 
     #[inline]
-    fn next_16_bits(&mut self) -> u16 {
-        let result = ((self.program[self.pc] as u16) << 8) | self.program[self.pc + 1] as u16;
-        self.pc += 2;
-
-        result
+    fn next_16_bits(&mut self) -> trap::Result<u16> {
+        let bytes = self.next_bytes(2)?;
+        Ok(((bytes[0] as u16) << 8) | bytes[1] as u16)
     }
 
     #[inline]
-    fn next_i32(&mut self) -> i32 {
-        let mut rdr = Cursor::new(&self.program[self.pc..self.pc + 4]);
-        let v = rdr.read_i32::<LittleEndian>().unwrap(); // TODO: Handle
-        self.pc += 4;
-        v
+    fn next_i32(&mut self) -> trap::Result<i32> {
+        Ok(LittleEndian::read_i32(self.next_bytes(4)?))
     }
 
     #[inline]
-    fn next_address(&mut self) -> Address {
-        let register = self.next_8_bits();
-        let offset = self.next_i32();
-        let section = MemorySection::from(self.next_8_bits());
+    fn next_register(&mut self) -> trap::Result<u8> {
+        let register = self.next_8_bits()?;
+        if register as usize >= REGISTER_COUNT {
+            return Err(Trap::InvalidRegister { register });
+        }
 
-        Address {
+        Ok(register)
+    }
+
+    #[inline]
+    fn next_address(&mut self) -> trap::Result<Address> {
+        let register = self.next_register()?;
+        let offset = self.next_i32()?;
+        let section_byte = self.next_8_bits()?;
+        let section = MemorySection::try_from(section_byte)
+            .map_err(|section| Trap::InvalidMemorySection { section })?;
+
+        Ok(Address {
             register,
             offset,
             section,
+        })
+    }
+
+    /// Consumes the fuel required by the next instruction.
+    ///
+    /// Returns false if the remaining fuel cannot cover it.
+    fn consume_fuel(&mut self) -> bool {
+        if let (Some(fuel), Some(opcode_byte)) = (self.fuel, self.program.get(self.pc)) {
+            let cost = self.fuel_costs.cost(Opcode::from(*opcode_byte));
+            if fuel < cost {
+                log::debug!("out of fuel at {:#06x}", self.pc);
+                return false;
+            }
+            self.fuel = Some(fuel - cost);
         }
+        true
     }
 
-    pub fn run_once(&mut self) -> bool {
-        self.execute_instruction()
+    /// Executes a single instruction.
+    pub fn run_once(&mut self) -> Result<Status> {
+        if !self.consume_fuel() {
+            return Ok(Status::OutOfFuel);
+        }
+
+        let pc = self.pc;
+        let keepalive = self.execute_instruction().map_err(|source| {
+            let opcode = self
+                .program
+                .get(pc)
+                .map(|b| Opcode::from(*b))
+                .unwrap_or(Opcode::IGL);
+
+            log::debug!("fault at {:#06x} ({:?}): {}", pc, opcode, source);
+
+            // Rewind so the pc points to the faulting instruction.
+            self.pc = pc;
+            VMError::Fault { pc, opcode, source }
+        })?;
+
+        Ok(if keepalive {
+            Status::Running
+        } else {
+            Status::Halted
+        })
     }
 
-    pub fn run(&mut self) {
+    /// Runs the program until it halts or runs out of fuel.
+    pub fn run(&mut self) -> Result<Status> {
         let start = std::time::Instant::now();
-        let mut keepalive = true;
-        while keepalive {
-            keepalive = self.execute_instruction();
+
+        let result = loop {
+            match self.run_once() {
+                Ok(Status::Running) => continue,
+                r => break r,
+            }
+        };
+
+        if let Ok(Status::Halted) = result {
+            self.post_run_validations();
         }
 
-        self.post_run_validations();
+        // The output is flushed on faults too, so that what the program printed before is kept.
+        self.flush_output();
 
         let dur = std::time::Instant::now().duration_since(start);
-        println!("Done in {}us", dur.as_micros());
+        log::debug!("done in {}us", dur.as_micros());
+        result
+    }
+
+    /// Flushes what the program wrote to the console output.
+    pub(crate) fn flush_output(&mut self) {
+        if let Err(e) = self.console.output().flush() {
+            log::warn!("failed to flush program output: {}", e);
+        }
     }
 
     fn post_run_validations(&self) {
         log::debug!("running debug validations");
 
         log::debug!("validating that stack is empty");
-        debug_assert_eq!(self.stack.len(), 0);
+        if !self.stack.is_empty() {
+            log::warn!(
+                "program exited with {}b left on the stack",
+                self.stack.len()
+            );
+        }
     }
 
-    fn execute_instruction(&mut self) -> bool {
-        if self.pc >= self.program.len() {
-            println!("End of program reached.");
-            return false;
+    fn execute_instruction(&mut self) -> trap::Result<bool> {
+        if self.pc == self.program.len() {
+            log::debug!("end of program reached");
+            return Ok(false);
         }
 
-        match self.decode_opcode() {
-            Opcode::LOAD => op::reg::load(self.next_8_bits(), self.next_16_bits(), self),
+        if self.pc > self.program.len() {
+            return Err(Trap::PcOutOfRange { pc: self.pc });
+        }
+
+        let opcode_byte = self.program[self.pc];
+        self.pc += 1;
+
+        match Opcode::from(opcode_byte) {
+            Opcode::LOAD => op::reg::load(self.next_register()?, self.next_16_bits()?, self),
             Opcode::ADD => op::math::add(
-                self.next_8_bits(),
-                self.next_8_bits(),
-                self.next_8_bits(),
+                self.next_register()?,
+                self.next_register()?,
+                self.next_register()?,
                 self,
             ),
             Opcode::SUB => op::math::sub(
-                self.next_8_bits(),
-                self.next_8_bits(),
-                self.next_8_bits(),
+                self.next_register()?,
+                self.next_register()?,
+                self.next_register()?,
                 self,
             ),
             Opcode::MUL => op::math::mul(
-                self.next_8_bits(),
-                self.next_8_bits(),
-                self.next_8_bits(),
+                self.next_register()?,
+                self.next_register()?,
+                self.next_register()?,
                 self,
             ),
             Opcode::DIV => op::math::div(
-                self.next_8_bits(),
-                self.next_8_bits(),
-                self.next_8_bits(),
+                self.next_register()?,
+                self.next_register()?,
+                self.next_register()?,
                 self,
-            ),
-            Opcode::JMP => op::branch::jmp(self.next_16_bits(), self),
-            Opcode::JMPF => op::branch::jmpf(self.next_16_bits(), self),
-            Opcode::JMPB => op::branch::jmpb(self.next_16_bits(), self),
-            Opcode::RJMP => op::branch::rjmp(self.next_8_bits(), self),
-            Opcode::EQ => op::math::eq(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::NEQ => op::math::neq(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::GT => op::math::gt(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::LT => op::math::lt(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::GTQ => op::math::gtq(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::LTQ => op::math::ltq(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::JEQ => op::branch::jeq(self.next_16_bits(), self),
-            Opcode::INC => op::reg::inc(self.next_8_bits(), self),
-            Opcode::DEC => op::reg::dec(self.next_8_bits(), self),
+            )?,
+            Opcode::JMP => op::branch::jmp(self.next_16_bits()?, self),
+            Opcode::JMPF => op::branch::jmpf(self.next_16_bits()?, self),
+            Opcode::JMPB => op::branch::jmpb(self.next_16_bits()?, self)?,
+            Opcode::RJMP => op::branch::rjmp(self.next_register()?, self),
+            Opcode::EQ => op::math::eq(self.next_register()?, self.next_register()?, self),
+            Opcode::NEQ => op::math::neq(self.next_register()?, self.next_register()?, self),
+            Opcode::GT => op::math::gt(self.next_register()?, self.next_register()?, self),
+            Opcode::LT => op::math::lt(self.next_register()?, self.next_register()?, self),
+            Opcode::GTQ => op::math::gtq(self.next_register()?, self.next_register()?, self),
+            Opcode::LTQ => op::math::ltq(self.next_register()?, self.next_register()?, self),
+            Opcode::JEQ => op::branch::jeq(self.next_16_bits()?, self),
+            Opcode::INC => op::reg::inc(self.next_register()?, self),
+            Opcode::DEC => op::reg::dec(self.next_register()?, self),
             Opcode::SYSC => {
                 // Execute a syscall.
                 log::trace!("syscall {:#06x}", self.registers[SYSCALL_REGISTER]);
-                let call_id = SysCall::from(self.registers[SYSCALL_REGISTER]);
-                let should_continue = execute_syscall(call_id, self);
+                let should_continue = execute_syscall(self.registers[SYSCALL_REGISTER], self)?;
 
                 if !should_continue {
-                    return false;
+                    return Ok(false);
                 }
             }
-            Opcode::PUSHW => op::stack::pushw(self.next_8_bits(), self),
-            Opcode::POPW => op::stack::popw(self.next_8_bits(), self),
-            Opcode::MOV => op::reg::mov(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::LCW => op::ro::lcw(self.next_8_bits(), self.next_16_bits(), self),
-            Opcode::SW => op::memory::sw(self.next_8_bits(), &self.next_address(), self),
-            Opcode::LW => op::memory::lw(self.next_8_bits(), &self.next_address(), self),
-            Opcode::SB => op::memory::sb(self.next_8_bits(), &self.next_address(), self),
-            Opcode::LB => op::memory::lb(self.next_8_bits(), &self.next_address(), self),
-            Opcode::CALL => op::branch::call(self.next_16_bits(), self),
-            Opcode::RET => op::branch::ret(self),
-            Opcode::NEG => op::math::neg(self.next_8_bits(), self),
-            Opcode::PUSHB => op::stack::pushb(self.next_8_bits(), self),
-            Opcode::POPB => op::stack::popb(self.next_8_bits(), self),
-            Opcode::JEZ => op::branch::jez(self.next_8_bits(), self.next_16_bits(), self),
-            Opcode::NOT => op::bitwise::not(self.next_8_bits(), self),
-            Opcode::SHIFTL => op::bitwise::shiftl(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::SHIFTR => op::bitwise::shiftr(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::AND => op::bitwise::and(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::OR => op::bitwise::or(self.next_8_bits(), self.next_8_bits(), self),
-            Opcode::IGL => {
-                println!("Illegal opcode. Terminating");
-                return false;
+            Opcode::PUSHW => op::stack::pushw(self.next_register()?, self)?,
+            Opcode::POPW => op::stack::popw(self.next_register()?, self)?,
+            Opcode::MOV => op::reg::mov(self.next_register()?, self.next_register()?, self),
+            Opcode::LCW => op::ro::lcw(self.next_register()?, self.next_16_bits()?, self)?,
+            Opcode::SW => op::memory::sw(self.next_register()?, &self.next_address()?, self)?,
+            Opcode::LW => op::memory::lw(self.next_register()?, &self.next_address()?, self)?,
+            Opcode::SB => op::memory::sb(self.next_register()?, &self.next_address()?, self)?,
+            Opcode::LB => op::memory::lb(self.next_register()?, &self.next_address()?, self)?,
+            Opcode::CALL => op::branch::call(self.next_16_bits()?, self)?,
+            Opcode::RET => op::branch::ret(self)?,
+            Opcode::NEG => op::math::neg(self.next_register()?, self),
+            Opcode::PUSHB => op::stack::pushb(self.next_register()?, self)?,
+            Opcode::POPB => op::stack::popb(self.next_register()?, self)?,
+            Opcode::JEZ => op::branch::jez(self.next_register()?, self.next_16_bits()?, self),
+            Opcode::NOT => op::bitwise::not(self.next_register()?, self),
+            Opcode::SHIFTL => {
+                op::bitwise::shiftl(self.next_register()?, self.next_register()?, self)
             }
+            Opcode::SHIFTR => {
+                op::bitwise::shiftr(self.next_register()?, self.next_register()?, self)
+            }
+            Opcode::AND => op::bitwise::and(self.next_register()?, self.next_register()?, self),
+            Opcode::OR => op::bitwise::or(self.next_register()?, self.next_register()?, self),
+            Opcode::IGL => return Err(Trap::IllegalOpcode { byte: opcode_byte }),
+        }
+        Ok(true)
+    }
+}
+
+impl Default for VM {
+    fn default() -> VM {
+        VM {
+            registers: [0; REGISTER_COUNT],
+            ro_block: Vec::new(),
+            remainder: 0,
+            equal_flag: false,
+            stack: Stack::new(),
+            heap: Heap::new(),
+
+            pc: 0,
+            program: Vec::new(),
+
+            fuel: None,
+            fuel_costs: FuelCosts::new(),
+
+            syscalls: SyscallTable::with_builtins(),
+            console: Console::new(),
         }
-        true
     }
 }
 
@@ -278,8 +463,9 @@ impl VM {
 mod tests {
     use byteorder::{LittleEndian, WriteBytesExt};
 
-    use super::VM;
-    use instructor::STACK_POINTER_REGISTER;
+    use super::{Status, VMError, VM};
+    use crate::trap::{MemoryRegion, Trap};
+    use instructor::{Opcode, STACK_POINTER_REGISTER};
 
     #[test]
     fn test_create_vm() {
@@ -289,13 +475,194 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vm_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<VM>();
+    }
+
     #[test]
     fn test_opcode_igl() {
         let mut test_vm = VM::new();
         let test_bytes = vec![0, 0, 0, 0];
         test_vm.program = test_bytes;
-        test_vm.run_once();
-        assert_eq!(test_vm.pc, 1);
+        match test_vm.run_once() {
+            Err(VMError::Fault { pc, opcode, source }) => {
+                assert_eq!(pc, 0);
+                assert_eq!(opcode, Opcode::IGL);
+                assert_eq!(source, Trap::IllegalOpcode { byte: 0 });
+            }
+            r => panic!("expected a fault, got {:?}", r),
+        }
+        assert_eq!(test_vm.pc, 0);
+    }
+
+    #[test]
+    fn test_end_of_program() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![16, 0];
+        assert_eq!(test_vm.run_once().unwrap(), Status::Running);
+        assert_eq!(test_vm.run_once().unwrap(), Status::Halted);
+    }
+
+    #[test]
+    fn test_out_of_fuel() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![6, 0, 0]; // Infinite loop.
+        test_vm.set_fuel(Some(10));
+
+        assert_eq!(test_vm.run().unwrap(), Status::OutOfFuel);
+        assert_eq!(test_vm.fuel(), Some(0));
+        assert_eq!(test_vm.pc, 0);
+    }
+
+    #[test]
+    fn test_refuel() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![16, 0, 16, 0, 16, 0];
+        test_vm.set_fuel(Some(2));
+
+        assert_eq!(test_vm.run().unwrap(), Status::OutOfFuel);
+        assert_eq!(test_vm.registers[0], 2);
+
+        test_vm.refuel(5);
+        assert_eq!(test_vm.run().unwrap(), Status::Halted);
+        assert_eq!(test_vm.registers[0], 3);
+        assert_eq!(test_vm.fuel(), Some(4));
+    }
+
+    #[test]
+    fn test_fuel_costs() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![16, 0, 17, 0];
+        test_vm.fuel_costs_mut().set(Opcode::DEC, 3);
+        test_vm.set_fuel(Some(3));
+
+        assert_eq!(test_vm.run_once().unwrap(), Status::Running);
+        assert_eq!(test_vm.run_once().unwrap(), Status::OutOfFuel);
+        assert_eq!(test_vm.pc, 2);
+
+        test_vm.refuel(1);
+        assert_eq!(test_vm.run_once().unwrap(), Status::Running);
+        assert_eq!(test_vm.fuel(), Some(0));
+    }
+
+    #[test]
+    fn test_truncated_instruction() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![1, 0, 1];
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                pc: 0,
+                opcode: Opcode::LOAD,
+                source: Trap::PcOutOfRange { pc: 2 }
+            })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_register() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![16, 200];
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                source: Trap::InvalidRegister { register: 200 },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_jump_out_of_range() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![6, 0, 10];
+        test_vm.run_once().unwrap();
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                pc: 10,
+                opcode: Opcode::IGL,
+                source: Trap::PcOutOfRange { pc: 10 }
+            })
+        ));
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let mut test_vm = VM::new();
+        test_vm.registers[0] = 20;
+        test_vm.program = vec![5, 0, 1, 2];
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                opcode: Opcode::DIV,
+                source: Trap::DivisionByZero,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_memory_section() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![25, 0, 1, 0, 0, 0, 0, 7];
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                source: Trap::InvalidMemorySection { section: 7 },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_out_of_bounds_load() {
+        let mut test_vm = VM::new();
+        test_vm.registers[1] = 12;
+        test_vm.program = vec![25, 0, 1, 0, 0, 0, 0, 1];
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                pc: 0,
+                opcode: Opcode::LW,
+                source: Trap::OutOfBounds {
+                    region: MemoryRegion::Heap,
+                    address: 12,
+                    width: 4
+                }
+            })
+        ));
+    }
+
+    #[test]
+    fn test_stack_underflow() {
+        let mut test_vm = VM::new();
+        test_vm.program = vec![21, 0];
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                opcode: Opcode::POPW,
+                source: Trap::StackUnderflow,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_illegal_syscall() {
+        let mut test_vm = VM::new();
+        test_vm.registers[32] = 0x7f;
+        test_vm.program = vec![19];
+        assert!(matches!(
+            test_vm.run_once(),
+            Err(VMError::Fault {
+                opcode: Opcode::SYSC,
+                source: Trap::IllegalSyscall { id: 0x7f },
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -303,7 +670,7 @@ mod tests {
         let mut test_vm = VM::new();
         test_vm.program = vec![1, 0, 1, 244];
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[0], 500);
     }
 
@@ -314,7 +681,7 @@ mod tests {
         test_vm.registers[1] = 8;
         test_vm.program = vec![2, 0, 1, 2];
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[2], 18);
     }
 
@@ -325,7 +692,7 @@ mod tests {
         test_vm.registers[1] = 8;
         test_vm.program = vec![3, 0, 1, 2];
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[2], 2);
     }
 
@@ -336,7 +703,7 @@ mod tests {
         test_vm.registers[1] = 8;
         test_vm.program = vec![4, 0, 1, 2];
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[2], 80);
     }
 
@@ -347,7 +714,7 @@ mod tests {
         test_vm.registers[1] = 8;
         test_vm.program = vec![5, 0, 1, 2];
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[2], 2);
         assert_eq!(test_vm.remainder, 4);
     }
@@ -356,7 +723,7 @@ mod tests {
     fn test_opcode_jmp() {
         let mut test_vm = VM::new();
         test_vm.program = vec![6, 0, 10, 0];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.pc, 10);
     }
@@ -365,7 +732,7 @@ mod tests {
     fn test_opcode_jmpf() {
         let mut test_vm = VM::new();
         test_vm.program = vec![7, 0, 2, 6, 0, 0, 0];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.pc, 5);
     }
 
@@ -374,7 +741,7 @@ mod tests {
         let mut test_vm = VM::new();
         test_vm.pc = 2;
         test_vm.program = vec![7, 0, 8, 0, 5];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.pc, 0);
     }
 
@@ -386,12 +753,12 @@ mod tests {
         test_vm.program = vec![9, 0, 1, 9, 0, 1];
 
         // Exec the first instruction -- should be equal.
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
 
         // Change one of the registers, next instruction should not be equal.
         test_vm.registers[0] = 9;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(!test_vm.equal_flag);
     }
 
@@ -403,12 +770,12 @@ mod tests {
         test_vm.program = vec![10, 0, 1, 10, 0, 1];
 
         // Exec the first instruction -- should not be equal.
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(!test_vm.equal_flag);
 
         // Change one of the registers, next instruction should be equal.
         test_vm.registers[0] = 9;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
     }
 
@@ -420,12 +787,12 @@ mod tests {
         test_vm.program = vec![11, 0, 1, 11, 0, 1];
 
         // Exec the first instruction -- should be equal.
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
 
         // Change one of the registers, next instruction not should be equal.
         test_vm.registers[0] = 9;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(!test_vm.equal_flag);
     }
 
@@ -437,12 +804,12 @@ mod tests {
         test_vm.program = vec![12, 0, 1, 12, 0, 1];
 
         // Exec the first instruction -- should be equal.
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
 
         // Change one of the registers, next instruction should not be equal.
         test_vm.registers[0] = 11;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(!test_vm.equal_flag);
     }
 
@@ -451,17 +818,17 @@ mod tests {
         let mut test_vm = VM::new();
         test_vm.registers[0] = 11;
         test_vm.registers[1] = 10;
-        test_vm.program = vec![13, 0, 1, 0, 13, 0, 1, 0, 13, 0, 1, 0];
+        test_vm.program = vec![13, 0, 1, 13, 0, 1, 13, 0, 1];
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
 
         test_vm.registers[0] = 10;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
 
         test_vm.registers[0] = 9;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(!test_vm.equal_flag);
     }
 
@@ -470,17 +837,17 @@ mod tests {
         let mut test_vm = VM::new();
         test_vm.registers[0] = 9;
         test_vm.registers[1] = 10;
-        test_vm.program = vec![14, 0, 1, 0, 14, 0, 1, 0, 14, 0, 1, 0];
+        test_vm.program = vec![14, 0, 1, 14, 0, 1, 14, 0, 1];
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
 
         test_vm.registers[0] = 10;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(test_vm.equal_flag);
 
         test_vm.registers[0] = 11;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert!(!test_vm.equal_flag);
     }
 
@@ -489,7 +856,7 @@ mod tests {
         let mut test_vm = VM::new();
         test_vm.equal_flag = true;
         test_vm.program = vec![15, 0, 7, 0, 16, 0, 0, 0, 16, 0, 0, 0];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.pc, 7);
     }
 
@@ -499,7 +866,7 @@ mod tests {
         test_vm.program = vec![16, 0, 0, 0];
 
         assert_eq!(test_vm.registers[0], 0);
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[0], 1);
     }
 
@@ -509,7 +876,7 @@ mod tests {
         test_vm.program = vec![17, 0, 0, 0];
 
         test_vm.registers[0] = 10;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[0], 9);
     }
 
@@ -519,7 +886,7 @@ mod tests {
         assert!(test_vm.stack.is_empty());
         test_vm.registers[0] = 12;
         test_vm.program = vec![20, 0, 0, 0];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.stack, vec![12, 0, 0, 0].into());
     }
 
@@ -530,7 +897,7 @@ mod tests {
         test_vm.registers[STACK_POINTER_REGISTER] = 8;
 
         test_vm.program = vec![21, 0, 0, 0];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers[0], 32);
         assert_eq!(test_vm.stack, vec![18, 0, 0, 0].into());
@@ -543,7 +910,7 @@ mod tests {
 
         assert_eq!(test_vm.registers[0], 0);
         test_vm.program = vec![22, 1, 0, 0];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers[0], 18);
     }
@@ -555,7 +922,7 @@ mod tests {
 
         assert_eq!(test_vm.registers[0], 0);
         test_vm.program = vec![23, 0, 0, 4, 1];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers[0], 10);
     }
@@ -571,7 +938,7 @@ mod tests {
         test_vm.registers[0] = 512;
         test_vm.registers[1] = 4;
         test_vm.program = vec![24, 0, 1, 0, 0, 0, 0, 1];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(
             &test_vm.heap().memory(),
@@ -596,7 +963,7 @@ mod tests {
         test_vm.program = vec![25, 0, 1, 0, 0, 0, 0, 1];
 
         assert_eq!(test_vm.registers[0], 0);
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[0], 42);
     }
 
@@ -611,7 +978,7 @@ mod tests {
         test_vm.registers[0] = 42;
         test_vm.registers[1] = 3;
         test_vm.program = vec![26, 0, 1, 0, 0, 0, 0, 1];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.heap().memory()[3], 42)
     }
@@ -627,7 +994,7 @@ mod tests {
         test_vm.program = vec![27, 0, 1, 0, 0, 0, 0, 1];
 
         assert_eq!(test_vm.registers[0], 0);
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers[0], 42);
     }
 
@@ -637,7 +1004,7 @@ mod tests {
 
         test_vm.registers_mut()[15] = 18;
         test_vm.program = vec![30, 15];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers()[15], -18);
     }
 
@@ -647,7 +1014,7 @@ mod tests {
 
         test_vm.registers_mut()[15] = 14;
         test_vm.program = vec![31, 15];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.stack().memory(), vec![14].as_slice());
     }
 
@@ -655,11 +1022,11 @@ mod tests {
     fn test_opcode_popb() {
         let mut test_vm = VM::new();
 
-        test_vm.stack_mut().push_u8(42);
+        test_vm.stack_mut().push_u8(42).unwrap();
         test_vm.registers_mut()[STACK_POINTER_REGISTER] = 1;
 
         test_vm.program = vec![32, 10];
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers()[10], 42);
         assert_eq!(test_vm.stack().len(), 0);
@@ -671,7 +1038,7 @@ mod tests {
 
         test_vm.program = vec![33, 15];
         test_vm.registers_mut()[15] = 0x0000002a;
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
         assert_eq!(test_vm.registers()[15], -43);
     }
 
@@ -684,7 +1051,7 @@ mod tests {
         test_vm.registers_mut()[15] = 21;
         test_vm.registers_mut()[10] = 1;
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers_mut()[10], 1);
         assert_eq!(test_vm.registers_mut()[15], 42);
@@ -699,7 +1066,7 @@ mod tests {
         test_vm.registers_mut()[15] = 84;
         test_vm.registers_mut()[10] = 1;
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers_mut()[10], 1);
         assert_eq!(test_vm.registers_mut()[15], 42);
@@ -714,7 +1081,7 @@ mod tests {
         test_vm.registers_mut()[15] = 59;
         test_vm.registers_mut()[10] = 46;
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers_mut()[10], 46);
         assert_eq!(test_vm.registers_mut()[15], 42);
@@ -729,25 +1096,9 @@ mod tests {
         test_vm.registers_mut()[15] = 8;
         test_vm.registers_mut()[10] = 34;
 
-        test_vm.run_once();
+        test_vm.run_once().unwrap();
 
         assert_eq!(test_vm.registers_mut()[10], 34);
         assert_eq!(test_vm.registers_mut()[15], 42);
     }
 }
-
-impl Default for VM {
-    fn default() -> VM {
-        VM {
-            registers: [0; REGISTER_COUNT],
-            ro_block: Vec::new(),
-            remainder: 0,
-            equal_flag: false,
-            stack: Stack::new(),
-            heap: Heap::new(),
-
-            pc: 0,
-            program: Vec::new(),
-        }
-    }
-}
@@ -2,6 +2,7 @@ use std::mem;
 
 use instructor::{STACK_BASE_REGISTER, STACK_POINTER_REGISTER};
 
+use crate::trap::{Result, Trap};
 use crate::VM;
 
 #[inline]
@@ -17,9 +18,13 @@ pub fn jmpf(addr_offset: u16, vm: &mut VM) {
 }
 
 #[inline]
-pub fn jmpb(addr_offset: u16, vm: &mut VM) {
-    vm.pc -= addr_offset as usize;
+pub fn jmpb(addr_offset: u16, vm: &mut VM) -> Result<()> {
+    vm.pc = vm
+        .pc
+        .checked_sub(addr_offset as usize)
+        .ok_or(Trap::PcOutOfRange { pc: vm.pc })?;
     log::trace!("jmpb {:#06x}", addr_offset);
+    Ok(())
 }
 
 #[inline]
@@ -51,13 +56,13 @@ pub fn jez(register: u8, target_addr: u16, vm: &mut VM) {
 }
 
 #[inline]
-pub fn call(target_addr: u16, vm: &mut VM) {
+pub fn call(target_addr: u16, vm: &mut VM) -> Result<()> {
     log::trace!("call {:#06x}", target_addr);
 
     // Push the return address (which is the current pc) on the stack.
     // Function arguments must be pushed on the stack before calling call().
     let return_address = vm.pc;
-    vm.stack_mut().push_i32(return_address as i32);
+    vm.stack_mut().push_i32(return_address as i32)?;
     vm.registers_mut()[STACK_POINTER_REGISTER] += mem::size_of::<i32>() as i32;
 
     // Jump to the beginning of the function.
@@ -65,24 +70,28 @@ pub fn call(target_addr: u16, vm: &mut VM) {
 
     // Save the pre-call ebp (current base pointer on the stack).
     let current_stack_base = vm.registers()[STACK_BASE_REGISTER];
-    vm.stack_mut().push_i32(current_stack_base);
+    vm.stack_mut().push_i32(current_stack_base)?;
     vm.registers_mut()[STACK_POINTER_REGISTER] += mem::size_of::<i32>() as i32;
 
     // Overwrite the ebp with the current value of the esp.
     vm.registers_mut()[STACK_BASE_REGISTER] = vm.registers()[STACK_POINTER_REGISTER];
+
+    Ok(())
 }
 
 #[inline]
-pub fn ret(vm: &mut VM) {
+pub fn ret(vm: &mut VM) -> Result<()> {
     log::trace!("ret");
-    // We assume here that the return value of the function is stored in $0.
+    // The return value of the function, if any, is stored in $v0.
 
     // Pop the saved ebp from the stack - This tears down the stack frame.
-    let old_stack_base = vm.stack_mut().pop_i32();
+    let old_stack_base = vm.stack_mut().pop_i32().ok_or(Trap::StackUnderflow)?;
     vm.registers_mut()[STACK_BASE_REGISTER] = old_stack_base;
     vm.registers_mut()[STACK_POINTER_REGISTER] -= mem::size_of::<i32>() as i32;
 
-    let return_address = vm.stack_mut().pop_i32() as usize;
+    let return_address = vm.stack_mut().pop_i32().ok_or(Trap::StackUnderflow)? as usize;
     vm.registers_mut()[STACK_POINTER_REGISTER] -= mem::size_of::<i32>() as i32;
     vm.pc = return_address;
+
+    Ok(())
 }
@@ -1,8 +1,9 @@
+use crate::trap::{Result, Trap};
 use crate::VM;
 
 #[inline]
 pub fn add(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) {
-    let res = vm.registers()[reg_src_a as usize] + vm.registers()[reg_src_b as usize];
+    let res = vm.registers()[reg_src_a as usize].wrapping_add(vm.registers()[reg_src_b as usize]);
     log::trace!(
         "add ${}/{:#06x} ${}/{:#06x} => ${}/{:#06x}",
         reg_src_a,
@@ -17,7 +18,7 @@ pub fn add(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) {
 
 #[inline]
 pub fn sub(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) {
-    let res = vm.registers()[reg_src_a as usize] - vm.registers()[reg_src_b as usize];
+    let res = vm.registers()[reg_src_a as usize].wrapping_sub(vm.registers()[reg_src_b as usize]);
     log::trace!(
         "sub ${}/{:#06x} ${}/{:#06x} => ${}/{:#06x}",
         reg_src_a,
@@ -32,7 +33,7 @@ pub fn sub(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) {
 
 #[inline]
 pub fn mul(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) {
-    let res = vm.registers()[reg_src_a as usize] * vm.registers()[reg_src_b as usize];
+    let res = vm.registers()[reg_src_a as usize].wrapping_mul(vm.registers()[reg_src_b as usize]);
     log::trace!(
         "mul ${}/{:#06x} ${}/{:#06x} => ${}/{:#06x}",
         reg_src_a,
@@ -46,22 +47,29 @@ pub fn mul(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) {
 }
 
 #[inline]
-pub fn div(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) {
+pub fn div(reg_src_a: u8, reg_src_b: u8, reg_dst: u8, vm: &mut VM) -> Result<()> {
     let reg_a = reg_src_a as usize;
     let reg_b = reg_src_b as usize;
-    vm.registers_mut()[reg_dst as usize] = vm.registers()[reg_a] / vm.registers()[reg_b];
-    vm.set_remainder((vm.registers()[reg_a] % vm.registers()[reg_b]) as u32);
+    let (dividend, divisor) = (vm.registers()[reg_a], vm.registers()[reg_b]);
+    if divisor == 0 {
+        return Err(Trap::DivisionByZero);
+    }
+
+    vm.registers_mut()[reg_dst as usize] = dividend.wrapping_div(divisor);
+    vm.set_remainder(dividend.wrapping_rem(divisor) as u32);
 
     log::trace!(
         "div ${}/{:#06x} ${}/{:#06x} => ${}/{:#06x}r{}",
         reg_a,
-        vm.registers()[reg_a],
+        dividend,
         reg_b,
-        vm.registers()[reg_b],
+        divisor,
         reg_dst,
         vm.registers()[reg_dst as usize],
         vm.remainder()
     );
+
+    Ok(())
 }
 
 #[inline]
@@ -150,7 +158,7 @@ pub fn ltq(reg_1: u8, reg_2: u8, vm: &mut VM) {
 
 #[inline]
 pub fn neg(reg: u8, vm: &mut VM) {
-    let value = -vm.registers()[reg as usize];
+    let value = vm.registers()[reg as usize].wrapping_neg();
     vm.registers_mut()[reg as usize] = value;
     log::trace!("neg ${}/{:#06x}", reg, vm.registers()[reg as usize]);
 }
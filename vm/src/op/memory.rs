@@ -2,43 +2,74 @@ use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use instructor::{Address, MemorySection, STACK_POINTER_REGISTER};
 
+use crate::trap::{MemoryRegion, Result, Trap};
 use crate::VM;
 
+/// Resolves an address to a pointer, ensuring `width` bytes can be accessed from it
+/// in a memory block of size `len`.
 #[inline]
-pub fn sw(src_reg: u8, addr: &Address, vm: &mut VM) {
-    let ptr = (vm.registers()[addr.register as usize] + addr.offset) as usize;
+fn resolve(addr: &Address, width: usize, len: usize, vm: &VM) -> Result<usize> {
+    let ptr = vm.registers()[addr.register as usize] as i64 + addr.offset as i64;
+    if ptr < 0 || ptr as usize + width > len {
+        return Err(Trap::OutOfBounds {
+            region: MemoryRegion::from(addr.section),
+            address: ptr,
+            width,
+        });
+    }
+    Ok(ptr as usize)
+}
 
-    let value_to_write = vm.registers()[src_reg as usize];
+/// Resolves a stack address for writing, growing the stack if required.
+#[inline]
+fn resolve_stack_write(addr: &Address, width: usize, vm: &mut VM) -> Result<usize> {
+    let ptr = resolve(addr, width, usize::MAX, vm)?;
 
-    log::trace!("sw ${}/{:#06x} => @{:#06x}", src_reg, value_to_write, ptr);
+    // This can cause stack growth, need to update the esp.
+    vm.stack_mut().safe_grow(ptr + width)?;
+    vm.registers_mut()[STACK_POINTER_REGISTER] = vm.stack().len() as i32;
 
-    let mut memory_slice = match addr.section {
-        MemorySection::Heap => &mut vm.heap_mut().memory_mut()[ptr..ptr + 4],
-        MemorySection::Stack => {
-            // This can cause stack growth, need to update the esp
-            vm.stack_mut().safe_grow(ptr + 4);
+    Ok(ptr)
+}
 
-            vm.registers_mut()[STACK_POINTER_REGISTER] = vm.stack().len() as i32;
+#[inline]
+pub fn sw(src_reg: u8, addr: &Address, vm: &mut VM) -> Result<()> {
+    let value_to_write = vm.registers()[src_reg as usize];
 
+    let mut memory_slice = match addr.section {
+        MemorySection::Heap => {
+            let ptr = resolve(addr, 4, vm.heap().len(), vm)?;
+            log::trace!("sw ${}/{:#06x} => @{:#06x}", src_reg, value_to_write, ptr);
+            &mut vm.heap_mut().memory_mut()[ptr..ptr + 4]
+        }
+        MemorySection::Stack => {
+            let ptr = resolve_stack_write(addr, 4, vm)?;
+            log::trace!("sw ${}/{:#06x} => @{:#06x}", src_reg, value_to_write, ptr);
             &mut vm.stack_mut().memory_mut()[ptr..ptr + 4]
         }
     };
 
     memory_slice
         .write_i32::<LittleEndian>(value_to_write)
-        .unwrap(); // TODO: Catch error.
+        .unwrap(); // Cannot fail, the slice is exactly 4 bytes long.
+
+    Ok(())
 }
 
 #[inline]
-pub fn lw(dst_reg: u8, addr: &Address, vm: &mut VM) {
-    let ptr = (vm.registers()[addr.register as usize] + addr.offset) as usize;
-
-    let mut memory_slice = match addr.section {
-        MemorySection::Heap => &vm.heap().memory()[ptr..ptr + 4],
-        MemorySection::Stack => &vm.stack().memory()[ptr..ptr + 4],
+pub fn lw(dst_reg: u8, addr: &Address, vm: &mut VM) -> Result<()> {
+    let (ptr, mut memory_slice) = match addr.section {
+        MemorySection::Heap => {
+            let ptr = resolve(addr, 4, vm.heap().len(), vm)?;
+            (ptr, &vm.heap().memory()[ptr..ptr + 4])
+        }
+        MemorySection::Stack => {
+            let ptr = resolve(addr, 4, vm.stack().len(), vm)?;
+            (ptr, &vm.stack().memory()[ptr..ptr + 4])
+        }
     };
 
-    // TODO: Catch error
+    // Cannot fail, the slice is exactly 4 bytes long.
     vm.registers_mut()[dst_reg as usize] = memory_slice.read_i32::<LittleEndian>().unwrap();
 
     log::trace!(
@@ -47,38 +78,46 @@ pub fn lw(dst_reg: u8, addr: &Address, vm: &mut VM) {
         dst_reg,
         vm.registers()[dst_reg as usize]
     );
+
+    Ok(())
 }
 
 #[inline]
-pub fn sb(src_reg: u8, addr: &Address, vm: &mut VM) {
+pub fn sb(src_reg: u8, addr: &Address, vm: &mut VM) -> Result<()> {
     // Set byte.
     let value_to_write = vm.registers()[src_reg as usize] as u8; // TODO: Ensure < 256
 
-    let ptr = (vm.registers()[addr.register as usize] + addr.offset) as usize;
-
-    log::trace!("sb ${}/{:#04x} => @{:#06x}", src_reg, value_to_write, ptr);
-
     match addr.section {
-        MemorySection::Heap => vm.heap_mut().memory_mut()[ptr] = value_to_write,
+        MemorySection::Heap => {
+            let ptr = resolve(addr, 1, vm.heap().len(), vm)?;
+            log::trace!("sb ${}/{:#04x} => @{:#06x}", src_reg, value_to_write, ptr);
+            vm.heap_mut().memory_mut()[ptr] = value_to_write
+        }
         MemorySection::Stack => {
-            // This can cause stack growth, need to update the esp.
-            vm.stack_mut().safe_grow(ptr + 1);
-            vm.registers_mut()[STACK_POINTER_REGISTER] = vm.stack().len() as i32;
-
+            let ptr = resolve_stack_write(addr, 1, vm)?;
+            log::trace!("sb ${}/{:#04x} => @{:#06x}", src_reg, value_to_write, ptr);
             vm.stack_mut().memory_mut()[ptr] = value_to_write
         }
     }
+
+    Ok(())
 }
 
 #[inline]
-pub fn lb(dst_reg: u8, addr: &Address, vm: &mut VM) {
-    let ptr = (vm.registers()[addr.register as usize] + addr.offset) as usize;
-
-    let val = match addr.section {
-        MemorySection::Heap => vm.heap().memory()[ptr],
-        MemorySection::Stack => vm.stack().memory()[ptr],
+pub fn lb(dst_reg: u8, addr: &Address, vm: &mut VM) -> Result<()> {
+    let (ptr, val) = match addr.section {
+        MemorySection::Heap => {
+            let ptr = resolve(addr, 1, vm.heap().len(), vm)?;
+            (ptr, vm.heap().memory()[ptr])
+        }
+        MemorySection::Stack => {
+            let ptr = resolve(addr, 1, vm.stack().len(), vm)?;
+            (ptr, vm.stack().memory()[ptr])
+        }
     };
     vm.registers_mut()[dst_reg as usize] = val as i32;
 
     log::trace!("lb @{:#06x} => #{}/{:#06x}", ptr, dst_reg, val);
+
+    Ok(())
 }
@@ -8,13 +8,13 @@ pub fn load(register: u8, value: u16, vm: &mut VM) {
 
 #[inline]
 pub fn inc(register: u8, vm: &mut VM) {
-    vm.registers_mut()[register as usize] += 1;
+    vm.registers_mut()[register as usize] = vm.registers()[register as usize].wrapping_add(1);
     log::trace!("inc ${}/{}", register, vm.registers()[register as usize]);
 }
 
 #[inline]
 pub fn dec(register: u8, vm: &mut VM) {
-    vm.registers_mut()[register as usize] -= 1;
+    vm.registers_mut()[register as usize] = vm.registers()[register as usize].wrapping_sub(1);
     log::trace!("dec ${}/{}", register, vm.registers()[register as usize]);
 }
 
@@ -1,15 +1,24 @@
 use byteorder::{LittleEndian, ReadBytesExt};
 
+use crate::trap::{MemoryRegion, Result, Trap};
 use crate::VM;
 
 #[inline]
-pub fn lcw(register: u8, ro_offset: u16, vm: &mut VM) {
+pub fn lcw(register: u8, ro_offset: u16, vm: &mut VM) -> Result<()> {
     let offset = ro_offset as usize;
-    let val = (&vm.ro_block()[offset..offset + 4])
-        .read_i32::<LittleEndian>()
-        .unwrap();
+    let mut slice = vm
+        .ro_block()
+        .get(offset..offset + 4)
+        .ok_or(Trap::OutOfBounds {
+            region: MemoryRegion::ReadOnly,
+            address: offset as i64,
+            width: 4,
+        })?;
+    let val = slice.read_i32::<LittleEndian>().unwrap(); // Cannot fail, the slice is 4 bytes long.
 
     log::trace!("lcw @{:#06x}/{:#06x} => ${}", offset, val, register);
 
     vm.registers_mut()[register as usize] = val;
+
+    Ok(())
 }
@@ -2,51 +2,48 @@ use std::mem;
 
 use instructor::STACK_POINTER_REGISTER;
 
+use crate::trap::{Result, Trap};
 use crate::VM;
 
 #[inline]
-pub fn pushw(register: u8, vm: &mut VM) {
+pub fn pushw(register: u8, vm: &mut VM) -> Result<()> {
     let value = vm.registers()[register as usize];
 
     log::trace!("pushw ${}/{:#06x}", register, value);
 
-    vm.stack_mut().push_i32(value);
+    vm.stack_mut().push_i32(value)?;
 
-    vm.registers_mut()[STACK_POINTER_REGISTER] += std::mem::size_of::<i32>() as i32;
+    vm.registers_mut()[STACK_POINTER_REGISTER] += mem::size_of::<i32>() as i32;
+    Ok(())
 }
 
 #[inline]
-pub fn popw(register: u8, vm: &mut VM) {
-    if !vm.stack().is_empty() {
-        vm.registers_mut()[STACK_POINTER_REGISTER] -= mem::size_of::<i32>() as i32;
-        debug_assert!(vm.registers()[STACK_POINTER_REGISTER] >= 0);
-    }
-
-    let value = vm.stack_mut().pop_i32();
+pub fn popw(register: u8, vm: &mut VM) -> Result<()> {
+    let value = vm.stack_mut().pop_i32().ok_or(Trap::StackUnderflow)?;
+    vm.registers_mut()[STACK_POINTER_REGISTER] -= mem::size_of::<i32>() as i32;
 
     log::trace!("popw ${}/{:#06x}", register, value);
     vm.registers_mut()[register as usize] = value;
+    Ok(())
 }
 
 #[inline]
-pub fn pushb(register: u8, vm: &mut VM) {
+pub fn pushb(register: u8, vm: &mut VM) -> Result<()> {
     let value = vm.registers()[register as usize] as u8;
 
     log::trace!("pushb ${}/{:#04x}", register, value);
 
-    vm.stack_mut().push_u8(value);
+    vm.stack_mut().push_u8(value)?;
     vm.registers_mut()[STACK_POINTER_REGISTER] += 1;
+    Ok(())
 }
 
 #[inline]
-pub fn popb(register: u8, vm: &mut VM) {
-    if !vm.stack().is_empty() {
-        vm.registers_mut()[STACK_POINTER_REGISTER] -= 1;
-        debug_assert!(vm.registers()[STACK_POINTER_REGISTER] >= 0);
-    }
-
-    let value = vm.stack_mut().pop_u8();
+pub fn popb(register: u8, vm: &mut VM) -> Result<()> {
+    let value = vm.stack_mut().pop_u8().ok_or(Trap::StackUnderflow)?;
+    vm.registers_mut()[STACK_POINTER_REGISTER] -= 1;
 
     log::trace!("popb ${}/{:#04x}", register, value);
     vm.registers_mut()[register as usize] = value as i32;
+    Ok(())
 }
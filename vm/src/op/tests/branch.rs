@@ -1,5 +1,5 @@
 use crate::op::branch;
-use crate::VM;
+use crate::{Trap, VM};
 
 #[test]
 fn op_jmp() {
@@ -23,10 +23,17 @@ fn op_jmpf() {
 fn op_jmpb() {
     let mut vm = VM::new();
     vm.pc = 5;
-    branch::jmpb(5, &mut vm);
+    branch::jmpb(5, &mut vm).unwrap();
     assert_eq!(vm.pc, 0);
 }
 
+#[test]
+fn op_jmpb_underflow() {
+    let mut vm = VM::new();
+    vm.pc = 5;
+    assert_eq!(branch::jmpb(6, &mut vm), Err(Trap::PcOutOfRange { pc: 5 }));
+}
+
 #[test]
 fn op_rjmp() {
     let mut vm = VM::new();
@@ -1,5 +1,5 @@
 use crate::op::math;
-use crate::VM;
+use crate::{Trap, VM};
 
 #[test]
 fn op_add_3reg() {
@@ -60,20 +60,28 @@ fn op_div_nonzero() {
     vm.registers_mut()[0] = 8;
     vm.registers_mut()[1] = 3;
     vm.registers_mut()[2] = 0;
-    math::div(0, 1, 2, &mut vm);
+    math::div(0, 1, 2, &mut vm).unwrap();
 
     assert_eq!(&vm.registers()[0..3], vec![8, 3, 2].as_slice());
     assert_eq!(vm.remainder(), 2);
 }
 
 #[test]
-#[should_panic(expected = "attempt to divide by zero")]
 fn op_div_zero() {
     let mut vm = VM::new();
     vm.registers_mut()[0] = 1;
     vm.registers_mut()[1] = 0;
     vm.registers_mut()[2] = 0;
-    math::div(0, 1, 2, &mut vm);
+    assert_eq!(math::div(0, 1, 2, &mut vm), Err(Trap::DivisionByZero));
+}
+
+#[test]
+fn op_add_overflow() {
+    let mut vm = VM::new();
+    vm.registers_mut()[0] = i32::MAX;
+    vm.registers_mut()[1] = 1;
+    math::add(0, 1, 2, &mut vm);
+    assert_eq!(vm.registers()[2], i32::MIN);
 }
 
 #[test]
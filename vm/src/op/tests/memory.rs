@@ -2,7 +2,7 @@ use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use instructor::Address;
 
-use crate::{memutil, op::memory, VM};
+use crate::{memutil, op::memory, MemoryRegion, Trap, VM};
 
 #[test]
 fn op_sw() {
@@ -11,20 +11,26 @@ fn op_sw() {
     vm.heap_mut().alloc(4);
     vm.registers_mut()[0] = 42;
     vm.registers_mut()[1] = 0;
-    memory::sw(0, &Address::new_heap(1, 0), &mut vm);
+    memory::sw(0, &Address::new_heap(1, 0), &mut vm).unwrap();
 
     assert_eq!(&vm.registers()[0..2], vec![42, 0].as_slice());
     assert_eq!(vm.heap().memory().read_i32::<LittleEndian>().unwrap(), 42);
 }
 
 #[test]
-#[should_panic(expected = "out of range")]
 fn op_sw_invalid_ptr() {
     let mut vm = VM::new();
     vm.heap_mut().alloc(4);
     vm.registers_mut()[0] = 42;
     vm.registers_mut()[1] = 15;
-    memory::sw(0, &Address::new_heap(1, 0), &mut vm);
+    assert_eq!(
+        memory::sw(0, &Address::new_heap(1, 0), &mut vm),
+        Err(Trap::OutOfBounds {
+            region: MemoryRegion::Heap,
+            address: 15,
+            width: 4
+        })
+    );
 }
 
 #[test]
@@ -34,7 +40,7 @@ fn op_sw_offset() {
     vm.heap_mut().alloc(8);
     vm.registers_mut()[0] = 42;
     vm.registers_mut()[1] = 2;
-    memory::sw(0, &Address::new_heap(1, 2), &mut vm);
+    memory::sw(0, &Address::new_heap(1, 2), &mut vm).unwrap();
 
     assert_eq!(&vm.registers()[0..2], vec![42, 2].as_slice());
     assert_eq!(&vm.heap().memory()[0..4], vec![0; 4].as_slice());
@@ -47,14 +53,20 @@ fn op_sw_offset() {
 }
 
 #[test]
-#[should_panic(expected = "out of range")]
 fn op_sw_memory_too_small() {
     // Typical case of store-word.
     let mut vm = VM::new();
     vm.heap_mut().alloc(2);
     vm.registers_mut()[0] = 42;
     vm.registers_mut()[1] = (memutil::WORD_WIDTH - 2) as i32;
-    memory::sw(0, &Address::new_heap(1, 0), &mut vm);
+    assert_eq!(
+        memory::sw(0, &Address::new_heap(1, 0), &mut vm),
+        Err(Trap::OutOfBounds {
+            region: MemoryRegion::Heap,
+            address: (memutil::WORD_WIDTH - 2) as i64,
+            width: 4
+        })
+    );
 }
 
 #[test]
@@ -64,20 +76,26 @@ fn op_sb() {
     vm.heap_mut().alloc(4);
     vm.registers_mut()[0] = 42;
     vm.registers_mut()[1] = 0;
-    memory::sb(0, &Address::new_heap(1, 0), &mut vm);
+    memory::sb(0, &Address::new_heap(1, 0), &mut vm).unwrap();
 
     assert_eq!(&vm.registers()[0..2], vec![42, 0].as_slice());
     assert_eq!(&vm.heap().memory()[0..4], vec![42, 0, 0, 0].as_slice());
 }
 
 #[test]
-#[should_panic(expected = "out of bounds")]
 fn op_sb_invalid_ptr() {
     let mut vm = VM::new();
     vm.heap_mut().alloc(4);
     vm.registers_mut()[0] = 42;
     vm.registers_mut()[1] = 15;
-    memory::sb(0, &Address::new_heap(1, 0), &mut vm);
+    assert_eq!(
+        memory::sb(0, &Address::new_heap(1, 0), &mut vm),
+        Err(Trap::OutOfBounds {
+            region: MemoryRegion::Heap,
+            address: 15,
+            width: 1
+        })
+    );
 }
 
 #[test]
@@ -87,7 +105,7 @@ fn op_sb_offset() {
     vm.heap_mut().alloc(8);
     vm.registers_mut()[0] = 42;
     vm.registers_mut()[1] = 2;
-    memory::sb(0, &Address::new_heap(1, 2), &mut vm);
+    memory::sb(0, &Address::new_heap(1, 2), &mut vm).unwrap();
 
     assert_eq!(&vm.registers()[0..2], vec![42, 2].as_slice());
     assert_eq!(&vm.heap().memory()[0..4], vec![0; 4].as_slice());
@@ -108,7 +126,7 @@ fn op_lw() {
     (&mut vm.heap_mut().memory_mut()[4..8])
         .write_i32::<LittleEndian>(45)
         .unwrap();
-    memory::lw(0, &Address::new_heap(1, 0), &mut vm);
+    memory::lw(0, &Address::new_heap(1, 0), &mut vm).unwrap();
     assert_eq!(vm.registers()[0], 45);
 }
 
@@ -121,28 +139,40 @@ fn op_lw_offset() {
     (&mut vm.heap_mut().memory_mut()[4..8])
         .write_i32::<LittleEndian>(45)
         .unwrap();
-    memory::lw(0, &Address::new_heap(1, 2), &mut vm);
+    memory::lw(0, &Address::new_heap(1, 2), &mut vm).unwrap();
     assert_eq!(vm.registers()[0], 45);
 }
 
 #[test]
-#[should_panic(expected = "out of range")]
 fn op_lw_invalid_ptr() {
     let mut vm = VM::new();
     vm.heap_mut().alloc(4);
     vm.registers_mut()[0] = 0;
     vm.registers_mut()[1] = 18;
-    memory::lw(0, &Address::new_heap(1, 0), &mut vm);
+    assert_eq!(
+        memory::lw(0, &Address::new_heap(1, 0), &mut vm),
+        Err(Trap::OutOfBounds {
+            region: MemoryRegion::Heap,
+            address: 18,
+            width: 4
+        })
+    );
 }
 
 #[test]
-#[should_panic(expected = "out of range")]
 fn op_lw_memory_too_small() {
     let mut vm = VM::new();
     vm.heap_mut().alloc(2);
     vm.registers_mut()[0] = 0;
     vm.registers_mut()[1] = (memutil::WORD_WIDTH - 2) as i32;
-    memory::lw(0, &Address::new_heap(1, 0), &mut vm);
+    assert_eq!(
+        memory::lw(0, &Address::new_heap(1, 0), &mut vm),
+        Err(Trap::OutOfBounds {
+            region: MemoryRegion::Heap,
+            address: (memutil::WORD_WIDTH - 2) as i64,
+            width: 4
+        })
+    );
 }
 
 #[test]
@@ -152,7 +182,7 @@ fn op_lb() {
     vm.registers_mut()[0] = 0;
     vm.registers_mut()[1] = 3;
     vm.heap_mut().memory_mut()[3] = 18;
-    memory::lb(0, &Address::new_heap(1, 0), &mut vm);
+    memory::lb(0, &Address::new_heap(1, 0), &mut vm).unwrap();
     assert_eq!(vm.registers()[0], 18);
 }
 
@@ -163,25 +193,31 @@ fn op_lb_offset() {
     vm.registers_mut()[0] = 0;
     vm.registers_mut()[1] = 2;
     vm.heap_mut().memory_mut()[5] = 14;
-    memory::lb(0, &Address::new_heap(1, 3), &mut vm);
+    memory::lb(0, &Address::new_heap(1, 3), &mut vm).unwrap();
     assert_eq!(vm.registers()[0], 14);
 }
 
 #[test]
-#[should_panic(expected = "out of bounds")]
 fn op_lb_invalid_ptr() {
     let mut vm = VM::new();
     vm.heap_mut().alloc(4);
     vm.registers_mut()[0] = 0;
     vm.registers_mut()[1] = 18;
-    memory::lb(0, &Address::new_heap(1, 0), &mut vm);
+    assert_eq!(
+        memory::lb(0, &Address::new_heap(1, 0), &mut vm),
+        Err(Trap::OutOfBounds {
+            region: MemoryRegion::Heap,
+            address: 18,
+            width: 1
+        })
+    );
 }
 
 #[test]
 fn op_sw_stack_growth() {
     let mut vm = VM::new();
     assert_eq!(vm.stack().len(), 0);
-    memory::sw(0, &Address::new_stack(0, 4), &mut vm);
+    memory::sw(0, &Address::new_stack(0, 4), &mut vm).unwrap();
     assert_eq!(vm.stack().len(), 8);
 }
 
@@ -189,6 +225,29 @@ fn op_sw_stack_growth() {
 fn op_sb_stack_growth() {
     let mut vm = VM::new();
     assert_eq!(vm.stack().len(), 0);
-    memory::sb(0, &Address::new_stack(0, 4), &mut vm);
+    memory::sb(0, &Address::new_stack(0, 4), &mut vm).unwrap();
     assert_eq!(vm.stack().len(), 5);
 }
+
+#[test]
+fn op_lw_stack_negative_ptr() {
+    let mut vm = VM::new();
+    assert_eq!(
+        memory::lw(0, &Address::new_stack(0, -4), &mut vm),
+        Err(Trap::OutOfBounds {
+            region: MemoryRegion::Stack,
+            address: -4,
+            width: 4
+        })
+    );
+}
+
+#[test]
+fn op_sw_stack_overflow() {
+    let mut vm = VM::new();
+    vm.registers_mut()[1] = i32::MAX;
+    assert_eq!(
+        memory::sw(0, &Address::new_stack(1, 0), &mut vm),
+        Err(Trap::StackOverflow)
+    );
+}
@@ -1,5 +1,5 @@
 use crate::op::stack;
-use crate::VM;
+use crate::{Trap, VM};
 use instructor::STACK_POINTER_REGISTER;
 
 #[test]
@@ -8,7 +8,7 @@ fn op_pushw() {
     vm.registers_mut()[1] = 18;
     assert!(vm.stack().is_empty());
 
-    stack::pushw(1, &mut vm);
+    stack::pushw(1, &mut vm).unwrap();
     assert_eq!(vm.stack(), &vec![18, 0, 0, 0].into());
 }
 
@@ -18,18 +18,18 @@ fn op_pushb() {
     vm.registers_mut()[1] = 18;
     assert!(vm.stack().is_empty());
 
-    stack::pushb(1, &mut vm);
+    stack::pushb(1, &mut vm).unwrap();
     assert_eq!(vm.stack(), &vec![18].into());
 }
 
 #[test]
 fn op_popw() {
     let mut vm = VM::new();
-    vm.stack_mut().push_i32(42);
+    vm.stack_mut().push_i32(42).unwrap();
     assert_eq!(vm.stack(), &vec![42, 0, 0, 0].into());
     vm.registers_mut()[STACK_POINTER_REGISTER] = 4;
 
-    stack::popw(3, &mut vm);
+    stack::popw(3, &mut vm).unwrap();
 
     assert_eq!(vm.registers()[3], 42);
     assert_eq!(vm.stack(), &vec![].into());
@@ -38,11 +38,11 @@ fn op_popw() {
 #[test]
 fn op_popb() {
     let mut vm = VM::new();
-    vm.stack_mut().push_u8(42);
+    vm.stack_mut().push_u8(42).unwrap();
     assert_eq!(vm.stack(), &vec![42].into());
     vm.registers_mut()[STACK_POINTER_REGISTER] = 1;
 
-    stack::popb(3, &mut vm);
+    stack::popb(3, &mut vm).unwrap();
 
     assert_eq!(vm.registers()[3], 42);
     assert_eq!(vm.stack(), &vec![].into());
@@ -54,8 +54,8 @@ fn op_popw_empty_stack() {
     assert!(vm.stack().is_empty());
     vm.registers_mut()[2] = 5;
 
-    stack::popw(2, &mut vm);
-    assert_eq!(vm.registers()[2], 0);
+    assert_eq!(stack::popw(2, &mut vm), Err(Trap::StackUnderflow));
+    assert_eq!(vm.registers()[2], 5);
 }
 
 #[test]
@@ -64,6 +64,6 @@ fn op_popb_empty_stack() {
     assert!(vm.stack().is_empty());
     vm.registers_mut()[2] = 5;
 
-    stack::popb(2, &mut vm);
-    assert_eq!(vm.registers()[2], 0);
+    assert_eq!(stack::popb(2, &mut vm), Err(Trap::StackUnderflow));
+    assert_eq!(vm.registers()[2], 5);
 }
@@ -2,6 +2,9 @@ use std::mem;
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
+use crate::constants::STACK_SIZE_LIMIT;
+use crate::trap::{Result, Trap};
+
 #[derive(Debug, PartialEq)]
 pub struct Stack {
     data: Vec<u8>,
@@ -13,37 +16,46 @@ impl Stack {
     }
 
     #[inline]
-    pub fn push_i32(&mut self, v: i32) {
-        self.data.write_i32::<LittleEndian>(v).unwrap();
+    fn ensure_capacity(&self, upper_bound: usize) -> Result<()> {
+        if upper_bound > STACK_SIZE_LIMIT {
+            return Err(Trap::StackOverflow);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn push_i32(&mut self, v: i32) -> Result<()> {
+        self.ensure_capacity(self.data.len() + mem::size_of::<i32>())?;
+        self.data.write_i32::<LittleEndian>(v).unwrap(); // Writing to a vec cannot fail.
+        Ok(())
     }
 
     #[inline]
-    pub fn push_u8(&mut self, v: u8) {
+    pub fn push_u8(&mut self, v: u8) -> Result<()> {
+        self.ensure_capacity(self.data.len() + 1)?;
         self.data.push(v);
+        Ok(())
     }
 
     #[inline]
-    pub fn pop_i32(&mut self) -> i32 {
-        if self.data.len() < (mem::size_of::<i32>() as usize) {
-            return 0;
+    pub fn pop_i32(&mut self) -> Option<i32> {
+        if self.data.len() < mem::size_of::<i32>() {
+            return None;
         }
 
-        let stack_idx = self.data.len() - (mem::size_of::<i32>() as usize);
+        let stack_idx = self.data.len() - mem::size_of::<i32>();
         let value = (&self.data[stack_idx..])
             .read_i32::<LittleEndian>()
             .unwrap(); // Impossible b/c length is at least size_of<i32>
 
         self.data.resize(stack_idx, 0);
 
-        value
+        Some(value)
     }
 
     #[inline]
-    pub fn pop_u8(&mut self) -> u8 {
-        if self.data.is_empty() {
-            return 0;
-        }
-        self.data.pop().unwrap()
+    pub fn pop_u8(&mut self) -> Option<u8> {
+        self.data.pop()
     }
 
     #[inline]
@@ -57,11 +69,12 @@ impl Stack {
     }
 
     #[inline]
-    pub fn safe_grow(&mut self, upper_bound: usize) {
-        let abs_diff = (upper_bound as i32) - (self.data.len() as i32);
-        if abs_diff > 0 {
+    pub fn safe_grow(&mut self, upper_bound: usize) -> Result<()> {
+        self.ensure_capacity(upper_bound)?;
+        if upper_bound > self.data.len() {
             self.data.resize(upper_bound, 0);
         }
+        Ok(())
     }
 
     #[inline]
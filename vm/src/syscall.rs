@@ -1,59 +1,171 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use instructor::SysCall;
 
 use crate::constants::SYSCALL_REGISTER;
+use crate::trap::{MemoryRegion, Result, Trap};
 use crate::VM;
 
-fn syscall_cprint(vm: &VM) -> bool {
-    // Print a constant.
-    // Expects the RO offset of the string in $0.
+/// A function callable from a program through the `syscall` instruction.
+///
+/// Handlers have full access to the VM (registers, heap, stack and ro block),
+/// and return whether execution should continue. Handlers are `Send`, so that a VM can be moved
+/// to another thread.
+pub type SyscallHandler = Box<dyn FnMut(&mut VM) -> Result<bool> + Send>;
 
-    let start_offset = vm.registers()[0] as usize;
-    let mut end_offset = start_offset;
-    while end_offset < vm.ro_block().len() && vm.ro_block()[end_offset] != 0 {
-        end_offset += 1;
+/// Registry of the syscalls available to a program, by syscall number.
+pub struct SyscallTable {
+    handlers: HashMap<i32, SyscallHandler>,
+}
+
+impl SyscallTable {
+    /// Creates an empty syscall table.
+    pub fn new() -> SyscallTable {
+        SyscallTable {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Creates a syscall table containing the built-in syscalls.
+    pub fn with_builtins() -> SyscallTable {
+        let mut table = SyscallTable::new();
+        table.register(SysCall::NOP as i32, |_vm| Ok(true));
+        table.register(SysCall::CPRINT as i32, syscall_cprint);
+        table.register(SysCall::EXIT as i32, |_vm| Ok(false));
+        table.register(SysCall::ALLOC as i32, syscall_alloc);
+        table.register(SysCall::FREE as i32, syscall_free);
+        table.register(SysCall::PRINTS as i32, syscall_prints);
+        table.register(SysCall::READLN as i32, syscall_readln);
+        table.register(SysCall::READI as i32, syscall_readi);
+        table.register(SysCall::READB as i32, syscall_readb);
+        table.register(SysCall::PRINTI as i32, |vm| {
+            let v = vm.registers()[0];
+            write_output(vm, v.to_string().as_bytes())
+        });
+        table.register(SysCall::PRINTU as i32, |vm| {
+            let v = vm.registers()[0] as u32;
+            write_output(vm, v.to_string().as_bytes())
+        });
+        table.register(SysCall::PRINTX as i32, |vm| {
+            let v = vm.registers()[0];
+            write_output(vm, format!("{:x}", v).as_bytes())
+        });
+        table.register(SysCall::PRINTC as i32, |vm| {
+            let v = vm.registers()[0] as u8;
+            write_output(vm, &[v])
+        });
+        table.register(SysCall::FMTI as i32, syscall_fmti);
+        table.register(SysCall::ABORT as i32, syscall_abort);
+        table
+    }
+
+    /// Registers a handler for a syscall number, replacing any existing handler.
+    pub fn register<F>(&mut self, id: i32, handler: F) -> Option<SyscallHandler>
+    where
+        F: FnMut(&mut VM) -> Result<bool> + Send + 'static,
+    {
+        self.handlers.insert(id, Box::new(handler))
+    }
+
+    /// Removes the handler of a syscall number.
+    pub fn remove(&mut self, id: i32) -> Option<SyscallHandler> {
+        self.handlers.remove(&id)
+    }
+
+    /// Returns whether a handler is registered for a syscall number.
+    pub fn contains(&self, id: i32) -> bool {
+        self.handlers.contains_key(&id)
     }
+}
+
+impl Default for SyscallTable {
+    fn default() -> SyscallTable {
+        SyscallTable::with_builtins()
+    }
+}
+
+impl fmt::Debug for SyscallTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ids: Vec<&i32> = self.handlers.keys().collect();
+        ids.sort();
+        f.debug_struct("SyscallTable").field("ids", &ids).finish()
+    }
+}
+
+/// Reads a null-terminated UTF-8 string starting at `start` in `memory`.
+fn read_str(memory: &[u8], start: usize, region: MemoryRegion) -> Result<&str> {
+    if start > memory.len() {
+        return Err(Trap::OutOfBounds {
+            region,
+            address: start as i64,
+            width: 1,
+        });
+    }
+
+    let end = memory[start..]
+        .iter()
+        .position(|b| *b == 0)
+        .map(|len| start + len)
+        .unwrap_or_else(|| memory.len());
 
     // The VM expects the string to be UTF-8 encoded.
-    let result = std::str::from_utf8(&vm.ro_block()[start_offset..end_offset]);
-    match result {
-        Ok(s) => {
-            print!("{}", s);
+    std::str::from_utf8(&memory[start..end]).map_err(|e| {
+        log::error!("Error decoding string for print syscall: {:#?}", e);
+        Trap::InvalidString {
+            region,
+            address: start,
         }
-        Err(e) => {
-            log::error!("Error decoding for print syscall: {:#?}", e);
-            return false;
-        }
-    };
+    })
+}
 
-    true
+fn syscall_cprint(vm: &mut VM) -> Result<bool> {
+    // Print a constant.
+    // Expects the RO offset of the string in $0.
+    let start_offset = vm.registers()[0] as usize;
+    let s = read_str(vm.ro_block(), start_offset, MemoryRegion::ReadOnly)?.to_owned();
+    write_output(vm, s.as_bytes())
 }
 
-fn syscall_prints(vm: &VM) -> bool {
+fn syscall_prints(vm: &mut VM) -> Result<bool> {
     // Prints a string from memory.
     // Expects a ptr. to the beginning of the string in $0.
-
     let start_ptr = vm.registers()[0] as usize;
-    let mut end_ptr = start_ptr;
-    while end_ptr < vm.heap().memory().len() && vm.heap().memory()[end_ptr] != 0 {
-        end_ptr += 1;
-    }
+    let s = read_str(vm.heap().memory(), start_ptr, MemoryRegion::Heap)?.to_owned();
+    write_output(vm, s.as_bytes())
+}
 
-    // The VM expects the string to be UTF-8 encoded.
-    let result = std::str::from_utf8(&vm.heap().memory()[start_ptr..end_ptr]);
-    match result {
-        Ok(s) => {
-            print!("{}", s);
-        }
-        Err(e) => {
-            log::error!("Error decoding string for print syscall: {:#?}", e);
-            return false;
-        }
-    };
+fn write_output(vm: &mut VM, data: &[u8]) -> Result<bool> {
+    vm.console_mut().output().write_all(data)?;
+    Ok(true)
+}
 
-    true
+/// Copies a string into a newly allocated null-terminated heap buffer, returning its address.
+fn alloc_str(vm: &mut VM, data: &[u8]) -> usize {
+    let heap = vm.heap_mut();
+    let ptr = heap.alloc(data.len() + 1);
+    let buffer = &mut heap.memory_mut()[ptr..ptr + data.len() + 1];
+    buffer[..data.len()].copy_from_slice(data);
+    buffer[data.len()] = 0;
+    ptr
 }
 
-fn syscall_alloc(vm: &mut VM) -> bool {
+fn syscall_fmti(vm: &mut VM) -> Result<bool> {
+    let formatted = vm.registers()[0].to_string();
+    let ptr = alloc_str(vm, formatted.as_bytes());
+    vm.registers_mut()[SYSCALL_REGISTER] = ptr as i32;
+
+    Ok(true)
+}
+
+fn syscall_abort(vm: &mut VM) -> Result<bool> {
+    // Expects the RO offset of the message in $0.
+    let start_offset = vm.registers()[0] as usize;
+    let message = read_str(vm.ro_block(), start_offset, MemoryRegion::ReadOnly)?.to_owned();
+    Err(Trap::Abort { message })
+}
+
+fn syscall_alloc(vm: &mut VM) -> Result<bool> {
     let amt_to_allocate = vm.registers()[0] as u16;
 
     let heap = vm.heap_mut();
@@ -61,30 +173,276 @@ fn syscall_alloc(vm: &mut VM) -> bool {
 
     vm.registers_mut()[SYSCALL_REGISTER] = allocated_ptr as i32; // OK b-c the heap is currently 16-bit.
 
-    true
+    Ok(true)
 }
 
-fn syscall_free(vm: &mut VM) -> bool {
+fn syscall_free(vm: &mut VM) -> Result<bool> {
     let ptr_to_free = vm.registers()[0] as u16;
 
     let heap = vm.heap_mut();
-    heap.free(ptr_to_free as usize);
-
-    true
-}
-
-pub fn execute_syscall(syscall: SysCall, vm: &mut VM) -> bool {
-    log::trace!("{:?}", syscall);
-    match syscall {
-        SysCall::NOP => true,
-        SysCall::CPRINT => syscall_cprint(vm),
-        SysCall::EXIT => false,
-        SysCall::ALLOC => syscall_alloc(vm),
-        SysCall::FREE => syscall_free(vm),
-        SysCall::PRINTS => syscall_prints(vm),
-        _ => {
-            eprintln!("Illegal Syscall. Terminating.",);
-            false
+    heap.free(ptr_to_free as usize)?;
+
+    Ok(true)
+}
+
+fn read_line(vm: &mut VM) -> Result<Vec<u8>> {
+    let console = vm.console_mut();
+
+    // Make sure prompts are visible before blocking on input.
+    console.output().flush()?;
+
+    let mut line = Vec::new();
+    console.input().read_until(b'\n', &mut line)?;
+
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    }
+
+    Ok(line)
+}
+
+fn syscall_readln(vm: &mut VM) -> Result<bool> {
+    let line = read_line(vm)?;
+    let ptr = alloc_str(vm, &line);
+    vm.registers_mut()[SYSCALL_REGISTER] = ptr as i32;
+
+    Ok(true)
+}
+
+fn syscall_readi(vm: &mut VM) -> Result<bool> {
+    let line = read_line(vm)?;
+    let input = String::from_utf8_lossy(&line);
+
+    let value = input
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| Trap::InvalidIntegerInput {
+            input: input.to_string(),
+        })?;
+
+    vm.registers_mut()[SYSCALL_REGISTER] = value;
+
+    Ok(true)
+}
+
+fn syscall_readb(vm: &mut VM) -> Result<bool> {
+    let console = vm.console_mut();
+    console.output().flush()?;
+
+    let input = console.input();
+    let value = match input.fill_buf()?.first() {
+        Some(&b) => {
+            input.consume(1);
+            b as i32
         }
+        None => -1,
+    };
+
+    vm.registers_mut()[SYSCALL_REGISTER] = value;
+
+    Ok(true)
+}
+
+/// Executes a syscall, returning whether execution should continue.
+pub fn execute_syscall(id: i32, vm: &mut VM) -> Result<bool> {
+    log::trace!("{:?}", SysCall::from(id));
+
+    // The handler is taken out of the table for the duration of the call
+    // so it can borrow the VM mutably.
+    let mut handler = vm
+        .syscalls_mut()
+        .remove(id)
+        .ok_or(Trap::IllegalSyscall { id })?;
+    let result = handler(vm);
+
+    // Keep any handler registered for this number during the call.
+    vm.syscalls_mut().handlers.entry(id).or_insert(handler);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use instructor::SysCall;
+
+    use super::{execute_syscall, SyscallTable};
+    use crate::{SharedBuffer, Trap, VM};
+
+    #[test]
+    fn builtins_registered() {
+        let table = SyscallTable::with_builtins();
+        assert!(table.contains(SysCall::NOP as i32));
+        assert!(table.contains(SysCall::PRINTS as i32));
+        assert!(!table.contains(SysCall::IGL as i32));
+    }
+
+    #[test]
+    fn host_syscall() {
+        let mut vm = VM::new();
+        vm.syscalls_mut().register(0x100, |vm| {
+            vm.registers_mut()[0] *= 2;
+            Ok(true)
+        });
+
+        vm.registers_mut()[0] = 21;
+        assert!(execute_syscall(0x100, &mut vm).unwrap());
+        assert_eq!(vm.registers()[0], 42);
+    }
+
+    #[test]
+    fn override_builtin() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut vm = VM::new();
+
+        let counter = calls.clone();
+        let previous = vm
+            .syscalls_mut()
+            .register(SysCall::EXIT as i32, move |_vm| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                Ok(true)
+            });
+        assert!(previous.is_some());
+
+        assert!(execute_syscall(SysCall::EXIT as i32, &mut vm).unwrap());
+        assert!(execute_syscall(SysCall::EXIT as i32, &mut vm).unwrap());
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn table_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<SyscallTable>();
+    }
+
+    #[test]
+    fn host_trap() {
+        let mut vm = VM::new();
+        vm.syscalls_mut().register(0x100, |_vm| {
+            Err(Trap::Host {
+                message: String::from("nope"),
+            })
+        });
+
+        assert_eq!(
+            execute_syscall(0x100, &mut vm),
+            Err(Trap::Host {
+                message: String::from("nope")
+            })
+        );
+        assert!(vm.syscalls().contains(0x100));
+    }
+
+    #[test]
+    fn readln() {
+        let mut vm = VM::new();
+        vm.console_mut().set_input(&b"hello\r\nworld"[..]);
+
+        assert!(execute_syscall(SysCall::READLN as i32, &mut vm).unwrap());
+        let ptr = vm.registers()[32] as usize;
+        assert_eq!(&vm.heap().memory()[ptr..ptr + 6], b"hello\0");
+
+        assert!(execute_syscall(SysCall::READLN as i32, &mut vm).unwrap());
+        let ptr = vm.registers()[32] as usize;
+        assert_eq!(&vm.heap().memory()[ptr..ptr + 6], b"world\0");
+
+        assert!(execute_syscall(SysCall::READLN as i32, &mut vm).unwrap());
+        let ptr = vm.registers()[32] as usize;
+        assert_eq!(vm.heap().memory()[ptr], 0);
+    }
+
+    #[test]
+    fn readi() {
+        let mut vm = VM::new();
+        vm.console_mut().set_input(&b" -42 \nabc\n"[..]);
+
+        assert!(execute_syscall(SysCall::READI as i32, &mut vm).unwrap());
+        assert_eq!(vm.registers()[32], -42);
+
+        assert_eq!(
+            execute_syscall(SysCall::READI as i32, &mut vm),
+            Err(Trap::InvalidIntegerInput {
+                input: String::from("abc")
+            })
+        );
+    }
+
+    #[test]
+    fn readb() {
+        let mut vm = VM::new();
+        vm.console_mut().set_input(&b"a"[..]);
+
+        assert!(execute_syscall(SysCall::READB as i32, &mut vm).unwrap());
+        assert_eq!(vm.registers()[32], 97);
+
+        assert!(execute_syscall(SysCall::READB as i32, &mut vm).unwrap());
+        assert_eq!(vm.registers()[32], -1);
+    }
+
+    fn print_with(id: SysCall, value: i32) -> String {
+        let output = SharedBuffer::new();
+        let mut vm = VM::new();
+        vm.console_mut().set_output(output.clone());
+        vm.registers_mut()[0] = value;
+
+        assert!(execute_syscall(id as i32, &mut vm).unwrap());
+        output.to_string_lossy()
+    }
+
+    #[test]
+    fn print_integers() {
+        assert_eq!(print_with(SysCall::PRINTI, -42), "-42");
+        assert_eq!(print_with(SysCall::PRINTU, 42), "42");
+        assert_eq!(print_with(SysCall::PRINTU, -1), "4294967295");
+        assert_eq!(print_with(SysCall::PRINTX, 255), "ff");
+        assert_eq!(print_with(SysCall::PRINTX, -1), "ffffffff");
+        assert_eq!(print_with(SysCall::PRINTC, 0x141), "A");
+    }
+
+    #[test]
+    fn fmti() {
+        let mut vm = VM::new();
+        vm.registers_mut()[0] = -1234;
+
+        assert!(execute_syscall(SysCall::FMTI as i32, &mut vm).unwrap());
+        let ptr = vm.registers()[32] as usize;
+        assert_eq!(&vm.heap().memory()[ptr..ptr + 6], b"-1234\0");
+    }
+
+    #[test]
+    fn abort() {
+        let mut vm = VM::with_ro_block(b"oops\0".to_vec());
+        assert_eq!(
+            execute_syscall(SysCall::ABORT as i32, &mut vm),
+            Err(Trap::Abort {
+                message: String::from("oops")
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_syscall() {
+        let mut vm = VM::new();
+        assert_eq!(
+            execute_syscall(0x100, &mut vm),
+            Err(Trap::IllegalSyscall { id: 0x100 })
+        );
+    }
+
+    #[test]
+    fn removed_syscall() {
+        let mut vm = VM::new();
+        assert!(vm.syscalls_mut().remove(SysCall::ALLOC as i32).is_some());
+        assert_eq!(
+            execute_syscall(SysCall::ALLOC as i32, &mut vm),
+            Err(Trap::IllegalSyscall {
+                id: SysCall::ALLOC as i32
+            })
+        );
     }
 }
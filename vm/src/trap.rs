@@ -0,0 +1,95 @@
+use std::io;
+
+use instructor::MemorySection;
+
+use snafu::Snafu;
+
+/// Memory regions addressable by a running program.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemoryRegion {
+    Heap,
+    Stack,
+    ReadOnly,
+}
+
+impl From<MemorySection> for MemoryRegion {
+    fn from(s: MemorySection) -> MemoryRegion {
+        match s {
+            MemorySection::Heap => MemoryRegion::Heap,
+            MemorySection::Stack => MemoryRegion::Stack,
+        }
+    }
+}
+
+/// Runtime faults raised while executing an instruction.
+///
+/// A trap always terminates the current run. The VM reports it wrapped in a
+/// [`VMError::Fault`](crate::VMError::Fault) along with the faulting pc and opcode.
+#[derive(Clone, Debug, PartialEq, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum Trap {
+    #[snafu(display("Aborted: {}", message))]
+    Abort { message: String },
+
+    #[snafu(display("Division by zero"))]
+    DivisionByZero,
+
+    #[snafu(display("Double free of [{:#06x}]", ptr))]
+    DoubleFree { ptr: usize },
+
+    #[snafu(display("Host error: {}", message))]
+    Host { message: String },
+
+    #[snafu(display("Illegal opcode: {:#04x}", byte))]
+    IllegalOpcode { byte: u8 },
+
+    #[snafu(display("Illegal syscall: {:#06x}", id))]
+    IllegalSyscall { id: i32 },
+
+    #[snafu(display("I/O error: {}", message))]
+    Io { message: String },
+
+    #[snafu(display("Invalid free of [{:#06x}]", ptr))]
+    InvalidFree { ptr: usize },
+
+    #[snafu(display("Invalid integer input: {:?}", input))]
+    InvalidIntegerInput { input: String },
+
+    #[snafu(display("Invalid memory section: {:#04x}", section))]
+    InvalidMemorySection { section: u8 },
+
+    #[snafu(display("Invalid register: ${}", register))]
+    InvalidRegister { register: u8 },
+
+    #[snafu(display("Invalid UTF-8 string at {:?}[{:#06x}]", region, address))]
+    InvalidString {
+        region: MemoryRegion,
+        address: usize,
+    },
+
+    #[snafu(display("Out of bounds access at {:?}[{:#06x}] ({}b)", region, address, width))]
+    OutOfBounds {
+        region: MemoryRegion,
+        address: i64,
+        width: usize,
+    },
+
+    #[snafu(display("Program counter out of range: {:#06x}", pc))]
+    PcOutOfRange { pc: usize },
+
+    #[snafu(display("Stack overflow"))]
+    StackOverflow,
+
+    #[snafu(display("Stack underflow"))]
+    StackUnderflow,
+}
+
+impl From<io::Error> for Trap {
+    fn from(e: io::Error) -> Trap {
+        Trap::Io {
+            message: e.to_string(),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Trap>;
@@ -0,0 +1,11 @@
+.data
+.text
+main: ld $0 10
+call @double
+move $0 $5
+ld $v0 2
+syscall
+
+double: add $0 $0 $0
+ld $1 1
+ret
@@ -0,0 +1,10 @@
+.text
+ld $0 42
+ld $v0 9
+syscall
+
+ld $1 0
+div $0 $1 $2
+
+ld $v0 2
+syscall
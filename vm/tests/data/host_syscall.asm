@@ -0,0 +1,9 @@
+.data
+.text
+ld $0 21
+ld $v0 256
+syscall
+move $v0 $1
+
+ld $v0 2
+syscall
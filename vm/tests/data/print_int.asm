@@ -0,0 +1,34 @@
+.data
+sep: .asciiz " "
+
+.text
+ld $0 1234
+ld $v0 9
+syscall
+
+ld $0 @sep
+ld $v0 1
+syscall
+
+ld $0 255
+ld $v0 11
+syscall
+
+ld $0 10
+ld $v0 12
+syscall
+
+ld $0 42
+ld $v0 13
+syscall
+move $v0 $1
+
+move $1 $0
+ld $v0 5
+syscall
+
+ld $v0 4
+syscall
+
+ld $v0 2
+syscall
@@ -0,0 +1,18 @@
+.data
+.text
+ld $0 0
+ld $3 10
+ld $4 0
+dec $4
+
+readloop: ld $v0 8
+syscall
+eq $v0 $3
+jeq @end
+eq $v0 $4
+jeq @end
+inc $0
+jmp @readloop
+
+end: ld $v0 2
+syscall
@@ -0,0 +1,14 @@
+.data
+.text
+ld $v0 7
+syscall
+move $v0 $1
+
+ld $v0 7
+syscall
+move $v0 $2
+
+add $1 $2 $0
+
+ld $v0 2
+syscall
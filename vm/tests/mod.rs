@@ -4,24 +4,34 @@ mod tests {
     use byteorder::{LittleEndian, ReadBytesExt};
 
     use assembler::Assembler;
-    use vm::{memutil, VM};
+    use instructor::LabelConverter;
+    use vm::debug::{Debugger, StopReason, Watch};
+    use vm::{memutil, SharedBuffer, Status, Trap, VMError, VM};
+
+    fn execute_test(source: &str, max_instructions: u64) -> VM {
+        execute_test_with_output(source, max_instructions).0
+    }
+
+    fn execute_test_with_output(source: &str, max_instructions: u64) -> (VM, String) {
+        execute_test_with_io(source, max_instructions, "")
+    }
+
+    fn execute_test_with_io(source: &str, max_instructions: u64, input: &str) -> (VM, String) {
+        let output = SharedBuffer::new();
 
-    fn execute_test(source: &str, max_instructions: usize) -> VM {
         let mut vm = VM::new();
         let asm = Assembler::new().assemble(source).unwrap();
         vm.load_bytecode(asm).unwrap();
+        vm.set_fuel(Some(max_instructions));
+        vm.console_mut()
+            .set_input(std::io::Cursor::new(input.to_string()));
+        vm.console_mut().set_output(output.clone());
 
-        let mut current_instr = 0;
-        let mut keepalive = true;
-        while keepalive {
-            keepalive = vm.run_once();
-            current_instr += 1;
-            if current_instr >= max_instructions {
-                panic!("Test took too long");
-            }
+        if vm.run().unwrap() == Status::OutOfFuel {
+            panic!("Test took too long");
         }
 
-        vm
+        (vm, output.to_string_lossy())
     }
 
     // TODO: Ensure all instructions are covered by the FTs as well.
@@ -46,6 +56,230 @@ mod tests {
         assert_eq!(vm.registers()[0], 11);
     }
 
+    #[test]
+    fn ft_loop_resume() {
+        const SOURCE: &str = include_str!("./data/loop.asm");
+        let mut vm = VM::new();
+        vm.load_bytecode(Assembler::new().assemble(SOURCE).unwrap())
+            .unwrap();
+        vm.set_fuel(Some(10));
+
+        assert_eq!(vm.run().unwrap(), Status::OutOfFuel);
+        assert!(vm.registers()[0] < 11);
+
+        vm.refuel(30);
+        assert_eq!(vm.run().unwrap(), Status::Halted);
+        assert_eq!(vm.registers()[0], 11);
+    }
+
+    #[test]
+    fn ft_host_syscall() {
+        const SOURCE: &str = include_str!("./data/host_syscall.asm");
+        let mut vm = VM::new();
+        vm.load_bytecode(Assembler::new().assemble(SOURCE).unwrap())
+            .unwrap();
+        vm.syscalls_mut().register(256, |vm| {
+            let registers = vm.registers_mut();
+            registers[32] = registers[0] * 2;
+            Ok(true)
+        });
+        vm.set_fuel(Some(10));
+
+        assert_eq!(vm.run().unwrap(), Status::Halted);
+        assert_eq!(vm.registers()[1], 42);
+    }
+
+    #[test]
+    fn ft_echo() {
+        const SOURCE: &str = include_str!("./data/echo.asm");
+        let (vm, output) = execute_test_with_io(SOURCE, 20, "slang\n");
+        assert_eq!(output, "What is your name? Hello, slang");
+        assert!(vm.heap().is_empty());
+    }
+
+    #[test]
+    fn ft_read_int() {
+        const SOURCE: &str = include_str!("./data/read_int.asm");
+        let (vm, _) = execute_test_with_io(SOURCE, 12, "40\n-2\n");
+        assert_eq!(vm.registers()[0], 38);
+    }
+
+    #[test]
+    fn ft_read_byte() {
+        const SOURCE: &str = include_str!("./data/read_byte.asm");
+        let (vm, _) = execute_test_with_io(SOURCE, 64, "abcd\nef");
+        assert_eq!(vm.registers()[0], 4);
+
+        let (vm, _) = execute_test_with_io(SOURCE, 64, "ab");
+        assert_eq!(vm.registers()[0], 2);
+    }
+
+    #[test]
+    fn ft_fault_flushes_output() {
+        const SOURCE: &str = include_str!("./data/fault.asm");
+        let bytecode = Assembler::new().assemble(SOURCE).unwrap();
+        let is_division_by_zero = |e: Option<VMError>| {
+            matches!(
+                e,
+                Some(VMError::Fault {
+                    source: Trap::DivisionByZero,
+                    ..
+                })
+            )
+        };
+
+        let output = SharedBuffer::new();
+        let mut vm = VM::new();
+        vm.load_bytecode(bytecode.clone()).unwrap();
+        vm.console_mut()
+            .set_output(std::io::BufWriter::new(output.clone()));
+        assert!(is_division_by_zero(vm.run().err()));
+        assert_eq!(output.to_string_lossy(), "42");
+
+        let output = SharedBuffer::new();
+        let mut vm = VM::new();
+        vm.load_bytecode(bytecode).unwrap();
+        vm.console_mut()
+            .set_output(std::io::BufWriter::new(output.clone()));
+        let mut debugger = Debugger::new();
+        assert!(is_division_by_zero(debugger.run_until_break(&mut vm).err()));
+        assert_eq!(output.to_string_lossy(), "42");
+    }
+
+    #[test]
+    fn ft_print_int() {
+        const SOURCE: &str = include_str!("./data/print_int.asm");
+        let (vm, output) = execute_test_with_output(SOURCE, 32);
+        assert_eq!(output, "1234 ff\n42");
+        assert!(vm.heap().is_empty());
+    }
+
+    fn debug_vm(source: &str) -> (VM, Assembler) {
+        let mut asm = Assembler::new();
+        let mut vm = VM::new();
+        vm.load_bytecode(asm.assemble(source).unwrap()).unwrap();
+        (vm, asm)
+    }
+
+    #[test]
+    fn ft_debug_breakpoint() {
+        const SOURCE: &str = include_str!("./data/debug.asm");
+        let (mut vm, asm) = debug_vm(SOURCE);
+
+        let mut debugger = Debugger::new();
+        let pc = debugger
+            .add_label_breakpoint("double", asm.symbols())
+            .unwrap();
+        assert!(debugger
+            .add_label_breakpoint("nope", asm.symbols())
+            .is_none());
+
+        assert_eq!(
+            debugger.run_until_break(&mut vm).unwrap(),
+            StopReason::Breakpoint { pc }
+        );
+        assert_eq!(vm.pc, pc);
+        assert_eq!(vm.registers()[0], 10);
+
+        assert_eq!(debugger.step(&mut vm).unwrap(), StopReason::Step);
+        assert_eq!(vm.registers()[0], 20);
+
+        assert_eq!(
+            debugger.run_until_break(&mut vm).unwrap(),
+            StopReason::Halted
+        );
+        assert_eq!(vm.registers()[5], 20);
+    }
+
+    #[test]
+    fn ft_debug_step_over() {
+        const SOURCE: &str = include_str!("./data/debug.asm");
+        let (mut vm, _asm) = debug_vm(SOURCE);
+        let mut debugger = Debugger::new();
+
+        // ld
+        assert_eq!(debugger.step_over(&mut vm).unwrap(), StopReason::Step);
+
+        // call, as a unit.
+        let pc = vm.pc;
+        assert_eq!(debugger.step_over(&mut vm).unwrap(), StopReason::Step);
+        assert_eq!(vm.pc, pc + 3);
+        assert_eq!(vm.registers()[0], 20);
+        assert_eq!(vm.registers()[1], 1);
+        assert!(vm.stack().is_empty());
+    }
+
+    #[test]
+    fn ft_debug_step_into() {
+        const SOURCE: &str = include_str!("./data/debug.asm");
+        let (mut vm, asm) = debug_vm(SOURCE);
+        let mut debugger = Debugger::new();
+
+        debugger.step(&mut vm).unwrap();
+        debugger.step(&mut vm).unwrap();
+        assert_eq!(vm.pc, asm.symbols().offset_of("double").unwrap() as usize);
+    }
+
+    #[test]
+    fn ft_debug_step_over_breakpoint() {
+        const SOURCE: &str = include_str!("./data/debug.asm");
+        let (mut vm, asm) = debug_vm(SOURCE);
+
+        let mut debugger = Debugger::new();
+        let pc = debugger
+            .add_label_breakpoint("double", asm.symbols())
+            .unwrap();
+
+        debugger.step_over(&mut vm).unwrap();
+        assert_eq!(
+            debugger.step_over(&mut vm).unwrap(),
+            StopReason::Breakpoint { pc }
+        );
+    }
+
+    #[test]
+    fn ft_debug_watchpoint() {
+        const SOURCE: &str = include_str!("./data/debug.asm");
+        let (mut vm, _asm) = debug_vm(SOURCE);
+
+        let mut debugger = Debugger::new();
+        debugger.add_watchpoint(Watch::Register(1), &vm);
+        debugger.add_watchpoint(Watch::Stack(0), &vm);
+
+        // The return address is pushed on the stack by the call.
+        assert_eq!(
+            debugger.run_until_break(&mut vm).unwrap(),
+            StopReason::Watchpoint {
+                watch: Watch::Stack(0),
+                old: None,
+                new: Some(7),
+            }
+        );
+
+        assert_eq!(
+            debugger.run_until_break(&mut vm).unwrap(),
+            StopReason::Watchpoint {
+                watch: Watch::Register(1),
+                old: Some(0),
+                new: Some(1),
+            }
+        );
+
+        assert_eq!(
+            debugger.run_until_break(&mut vm).unwrap(),
+            StopReason::Watchpoint {
+                watch: Watch::Stack(0),
+                old: Some(7),
+                new: None,
+            }
+        );
+
+        assert_eq!(
+            debugger.run_until_break(&mut vm).unwrap(),
+            StopReason::Halted
+        );
+    }
+
     #[test]
     fn ft_stack() {
         const SOURCE: &str = include_str!("./data/stack.asm");
@@ -71,13 +305,15 @@ mod tests {
     #[test]
     fn ft_prints() {
         const SOURCE: &str = include_str!("./data/prints.asm");
-        let _vm = execute_test(SOURCE, 16);
+        let (_vm, output) = execute_test_with_output(SOURCE, 16);
+        assert_eq!(output, "Hello everyone!Something else");
     }
 
     #[test]
     fn ft_dyn_str() {
         const SOURCE: &str = include_str!("./data/dyn_str.asm");
-        let _vm = execute_test(SOURCE, 64);
+        let (_vm, output) = execute_test_with_output(SOURCE, 64);
+        assert_eq!(output, "abcd\n");
     }
 
     #[test]